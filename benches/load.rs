@@ -0,0 +1,143 @@
+//! Compare load time and peak RSS of the memory and trace loaders.
+//!
+//! Usage: `cargo bench --bench load`. The size of the synthetic dumps can be changed with the
+//! `BENCH_MEMORY_CELLS` and `BENCH_TRACE_STEPS` environment variables.
+
+use bincode::de::read::SliceReader;
+use sierra2casm_dbg::{Memory, Trace};
+use starknet_types_core::felt::Felt;
+use std::{
+    env,
+    fs::{self, File},
+    hint::black_box,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+fn main() {
+    let num_cells = env_or("BENCH_MEMORY_CELLS", 4_000_000);
+    let num_steps = env_or("BENCH_TRACE_STEPS", 2_000_000);
+
+    let dir = env::temp_dir().join(format!("sierra2casm-dbg-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let memory_path = dir.join("memory.bin");
+    let trace_path = dir.join("trace.bin");
+
+    println!("Generating {num_cells} memory cells and {num_steps} trace steps.");
+    write_memory(&memory_path, num_cells);
+    write_trace(&trace_path, num_steps);
+    println!();
+
+    // The mapped loaders go first so that the peak RSS of the others doesn't hide theirs on
+    // systems where it can't be reset.
+    measure("memory: map_file", || {
+        Memory::map_file(&memory_path).unwrap().len()
+    });
+    measure("memory: map_file + full scan", || {
+        let memory = Memory::map_file(&memory_path).unwrap();
+        memory.iter().fold(Felt::ZERO, |acc, (_, x)| acc + x)
+    });
+    measure("memory: read + decode", || {
+        Memory::decode(SliceReader::new(&fs::read(&memory_path).unwrap())).len()
+    });
+    measure("memory: read + legacy dense decode", || {
+        legacy_decode_memory(&fs::read(&memory_path).unwrap()).len()
+    });
+
+    measure("trace: map_file", || {
+        Trace::map_file(&trace_path).unwrap().len()
+    });
+    measure("trace: map_file + full scan", || {
+        let trace = Trace::map_file(&trace_path).unwrap();
+        trace.iter().map(|x| x.pc).sum::<usize>()
+    });
+    measure("trace: read + decode", || {
+        Trace::decode(SliceReader::new(&fs::read(&trace_path).unwrap())).len()
+    });
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name).map_or(default, |x| x.parse().unwrap())
+}
+
+/// Write a memory dump with a hole of 100 cells every 1000 cells.
+fn write_memory(path: &Path, num_cells: usize) {
+    let mut file = BufWriter::new(File::create(path).unwrap());
+    for i in 0..num_cells {
+        let addr = 1 + i + (i / 1000) * 100;
+        file.write_all(&(addr as u64).to_le_bytes()).unwrap();
+        file.write_all(&Felt::from(i).to_bytes_le()).unwrap();
+    }
+    file.flush().unwrap();
+}
+
+fn write_trace(path: &Path, num_steps: usize) {
+    let mut file = BufWriter::new(File::create(path).unwrap());
+    for i in 0..num_steps {
+        for value in [1000 + i, 1000, 1 + i % 100] {
+            file.write_all(&(value as u64).to_le_bytes()).unwrap();
+        }
+    }
+    file.flush().unwrap();
+}
+
+/// The original decoder, which grows a dense vector one record at a time.
+fn legacy_decode_memory(data: &[u8]) -> Vec<Option<Felt>> {
+    let mut memory = Vec::new();
+    for record in data.chunks_exact(40) {
+        let addr = u64::from_le_bytes(record[..8].try_into().unwrap()) as usize;
+        let value = Felt::from_bytes_le(record[8..].try_into().unwrap());
+
+        if addr >= memory.len() {
+            memory.resize(addr + 1, None);
+        }
+        assert!(
+            memory[addr].replace(value).is_none(),
+            "duplicated memory cell"
+        );
+    }
+    memory
+}
+
+fn measure<T>(name: &str, f: impl FnOnce() -> T) {
+    reset_peak_rss();
+    let base_rss = peak_rss();
+
+    let start = Instant::now();
+    black_box(f());
+    let elapsed = start.elapsed();
+
+    let peak = match (base_rss, peak_rss()) {
+        (Some(base), Some(peak)) => format!("{:>8} KiB", peak.saturating_sub(base)),
+        _ => "n/a".to_string(),
+    };
+    println!(
+        "{name:<40} {:>10} peak RSS +{peak}",
+        format_duration(elapsed)
+    );
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Reset the peak RSS of the process (Linux only).
+fn reset_peak_rss() {
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Return the peak RSS of the process in KiB (Linux only).
+fn peak_rss() -> Option<usize> {
+    fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|x| x.strip_prefix("VmHWM:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()
+}
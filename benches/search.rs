@@ -0,0 +1,158 @@
+//! Compare the path enumeration search with the shortest path search on a synthetic trace.
+//!
+//! Usage: `cargo bench --bench search`. The number of steps can be changed with the
+//! `BENCH_TRACE_STEPS` environment variable, and the number of steps given to the path
+//! enumeration search (which doesn't scale) with `BENCH_ENUMERATION_STEPS`.
+
+use bincode::de::read::SliceReader;
+use cairo_lang_casm::casm;
+use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
+use sierra2casm_dbg::{
+    run_search_algorithm,
+    search::{
+        bidirectional::find_path_bidirectional,
+        predicates::Dataflow,
+        shortest::{find_shortest_path, StepDistance, Unweighted},
+        BfsQueue, Predicates,
+    },
+    GraphMappings, Memory, Trace, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::{collections::HashMap, env, time::Instant};
+
+/// Address of the first value of the chain.
+const BASE: usize = 1000;
+
+fn main() {
+    let num_steps = env_or("BENCH_TRACE_STEPS", 1_000_000);
+    let num_enumeration_steps = env_or("BENCH_ENUMERATION_STEPS", 2_000);
+
+    let (memory, trace, mappings) = generate(num_steps);
+    let source = ValueId(BASE - 1);
+    let target = ValueId(BASE + num_steps - 1);
+
+    measure("shortest: unweighted", || {
+        find_shortest_path(
+            &memory,
+            &trace,
+            &mappings,
+            source,
+            target,
+            Predicates::new(),
+            Unweighted,
+        )
+    });
+    measure("shortest: step distance", || {
+        find_shortest_path(
+            &memory,
+            &trace,
+            &mappings,
+            source,
+            target,
+            Predicates::new(),
+            StepDistance,
+        )
+    });
+    measure("shortest: dataflow", || {
+        let predicates = Predicates::new()
+            .with_value(Dataflow(&mappings))
+            .with_step(Dataflow(&mappings));
+        find_shortest_path(
+            &memory, &trace, &mappings, source, target, predicates, Unweighted,
+        )
+    });
+    measure("bidirectional", || {
+        find_path_bidirectional(
+            &memory,
+            &trace,
+            &mappings,
+            source,
+            target,
+            Predicates::new(),
+        )
+    });
+
+    println!();
+    let (memory, trace, mappings) = generate(num_enumeration_steps);
+    let target = ValueId(BASE + num_enumeration_steps - 1);
+    measure("enumeration: bfs + dataflow", || {
+        let predicates = Predicates::new()
+            .with_value(Dataflow(&mappings))
+            .with_step(Dataflow(&mappings));
+        run_search_algorithm::<BfsQueue<_>>(&memory, &trace, &mappings, source, target, predicates)
+            .next()
+    });
+    measure("shortest: dataflow", || {
+        let predicates = Predicates::new()
+            .with_value(Dataflow(&mappings))
+            .with_step(Dataflow(&mappings));
+        find_shortest_path(
+            &memory, &trace, &mappings, source, target, predicates, Unweighted,
+        )
+    });
+}
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name).map_or(default, |x| x.parse().unwrap())
+}
+
+/// Generate a trace where every step runs `[ap] = [ap - 1] + [fp - 3], ap++`.
+///
+/// The steps form a chain through the cells they write, while all of them also read the same
+/// cell, which connects every step with every other one.
+fn generate(num_steps: usize) -> (Memory, Trace, GraphMappings) {
+    let start = Instant::now();
+
+    let instruction = casm! { [ap + 0] = [ap + -1] + [fp + -3], ap++; }.instructions[0]
+        .assemble()
+        .encode()[0]
+        .clone();
+    // Every step reads `[BASE - 2]` through `[fp - 3]`.
+    let fp = BASE + 1;
+
+    let mut memory = Vec::new();
+    let mut push_cell = |addr: usize, value: Felt| {
+        memory.extend((addr as u64).to_le_bytes());
+        memory.extend(value.to_bytes_le());
+    };
+    push_cell(1, Felt::from(&instruction));
+    push_cell(BASE - 2, Felt::ONE);
+    push_cell(BASE - 1, Felt::ONE);
+    for i in 0..num_steps {
+        push_cell(BASE + i, Felt::from(i + 2));
+    }
+
+    let mut trace = Vec::new();
+    for i in 0..num_steps {
+        let entry = RelocatedTraceEntry {
+            pc: 1,
+            ap: BASE + i,
+            fp,
+        };
+        for value in [entry.ap, entry.fp, entry.pc] {
+            trace.extend((value as u64).to_le_bytes());
+        }
+    }
+
+    let memory = Memory::decode(SliceReader::new(&memory));
+    let trace = Trace::decode(SliceReader::new(&trace));
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    println!(
+        "Generated {num_steps} steps in {:.1}ms.",
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+    (memory, trace, mappings)
+}
+
+fn measure<T>(name: &str, f: impl FnOnce() -> Option<Vec<T>>) {
+    let start = Instant::now();
+    let path = f();
+    let elapsed = start.elapsed();
+
+    let nodes = path.map_or("no path".to_string(), |x| format!("{} nodes", x.len()));
+    println!(
+        "{name:<30} {:>10.1}ms   {nodes}",
+        elapsed.as_secs_f64() * 1000.0
+    );
+}
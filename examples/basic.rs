@@ -1,20 +1,17 @@
 use bincode::de::read::SliceReader;
-use sierra2casm_dbg::{decode_instruction, GraphMappings, Memory, Trace, ValueId};
-use starknet_types_core::felt::Felt;
-use std::{collections::HashMap, fs, str::FromStr};
+use sierra2casm_dbg::{decode_any_instruction, GraphMappings, Memory, Trace, ValueId};
+use std::{collections::HashMap, fs};
 
 fn main() {
     let memory = Memory::decode(SliceReader::new(&fs::read("memory-2.bin").unwrap()));
     let trace = Trace::decode(SliceReader::new(&fs::read("trace-2.bin").unwrap()));
 
-    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new());
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
 
     // let value_idx = memory
     //     .iter()
-    //     .copied()
-    //     .enumerate()
     //     .filter_map(|(idx, val)| {
-    //         (val == Some(Felt::from_str("9980669810").unwrap())).then_some(idx)
+    //         (val == Felt::from_str("9980669810").unwrap()).then_some(idx)
     //     })
     //     .collect::<Vec<_>>();
 
@@ -24,15 +21,15 @@ fn main() {
     for id in &mappings[value_idx] {
         println!(
             "{id:?} => {} [{:?}]",
-            decode_instruction(&memory, trace[id.0].pc),
-            trace[id.0]
+            decode_any_instruction(&memory, trace.get(id.0).unwrap().pc),
+            trace.get(id.0).unwrap()
         );
     }
 
-    println!("[93139] = {}", memory[93139].unwrap());
-    println!("[93140] = {}", memory[93140].unwrap());
-    println!("[93142] = {}", memory[93142].unwrap());
+    println!("[93139] = {}", memory.get(93139).unwrap());
+    println!("[93140] = {}", memory.get(93140).unwrap());
+    println!("[93142] = {}", memory.get(93142).unwrap());
 
-    // dbg!(memory[25386].unwrap().to_string());
+    // dbg!(memory.get(25386).unwrap().to_string());
     // dbg!(value_idx);
 }
@@ -0,0 +1,223 @@
+//! Builtin segments and the semantics of the builtins which deduce some of their cells.
+
+use crate::{Error, RelocationTable, SegmentKind, ValueId};
+use serde_json::Value;
+use std::{fmt, ops::Range, str::FromStr};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Builtin {
+    Output,
+    Pedersen,
+    RangeCheck,
+    Ecdsa,
+    Bitwise,
+    EcOp,
+    Keccak,
+    Poseidon,
+    RangeCheck96,
+    AddMod,
+    MulMod,
+    SegmentArena,
+}
+
+impl Builtin {
+    const ALL: [Builtin; 12] = [
+        Builtin::Output,
+        Builtin::Pedersen,
+        Builtin::RangeCheck,
+        Builtin::Ecdsa,
+        Builtin::Bitwise,
+        Builtin::EcOp,
+        Builtin::Keccak,
+        Builtin::Poseidon,
+        Builtin::RangeCheck96,
+        Builtin::AddMod,
+        Builtin::MulMod,
+        Builtin::SegmentArena,
+    ];
+
+    /// The name used by the runner and the program JSON.
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::Output => "output",
+            Builtin::Pedersen => "pedersen",
+            Builtin::RangeCheck => "range_check",
+            Builtin::Ecdsa => "ecdsa",
+            Builtin::Bitwise => "bitwise",
+            Builtin::EcOp => "ec_op",
+            Builtin::Keccak => "keccak",
+            Builtin::Poseidon => "poseidon",
+            Builtin::RangeCheck96 => "range_check96",
+            Builtin::AddMod => "add_mod",
+            Builtin::MulMod => "mul_mod",
+            Builtin::SegmentArena => "segment_arena",
+        }
+    }
+
+    pub fn cells_per_instance(self) -> usize {
+        match self {
+            Builtin::Output | Builtin::RangeCheck | Builtin::RangeCheck96 => 1,
+            Builtin::Ecdsa => 2,
+            Builtin::Pedersen | Builtin::SegmentArena => 3,
+            Builtin::Bitwise => 5,
+            Builtin::Poseidon => 6,
+            Builtin::EcOp | Builtin::AddMod | Builtin::MulMod => 7,
+            Builtin::Keccak => 16,
+        }
+    }
+
+    /// The cells of an instance which the builtin deduces from the ones before them.
+    ///
+    /// The other builtins only validate their cells, except for `AddMod` and `MulMod` whose
+    /// results are written by a hint.
+    pub fn outputs(self) -> Range<usize> {
+        match self {
+            Builtin::Pedersen => 2..3,
+            Builtin::Bitwise => 2..5,
+            Builtin::EcOp => 5..7,
+            Builtin::Keccak => 8..16,
+            Builtin::Poseidon => 3..6,
+            _ => 0..0,
+        }
+    }
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse a builtin by name, with or without the `_builtin` suffix.
+impl FromStr for Builtin {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.strip_suffix("_builtin").unwrap_or(s);
+        Self::ALL
+            .into_iter()
+            .find(|x| x.name() == name)
+            .ok_or_else(|| Error::UnknownBuiltin(s.to_string()))
+    }
+}
+
+/// The relocated addresses of a builtin segment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BuiltinSegment {
+    pub builtin: Builtin,
+    pub addrs: Range<usize>,
+}
+
+/// A memory cell within a builtin segment.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BuiltinCell {
+    pub builtin: Builtin,
+    pub instance: usize,
+    /// The index of the cell within its instance.
+    pub offset: usize,
+}
+
+impl BuiltinCell {
+    pub fn is_output(&self) -> bool {
+        self.builtin.outputs().contains(&self.offset)
+    }
+}
+
+impl fmt::Display for BuiltinCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outputs = self.builtin.outputs();
+        write!(f, "{} #{} ", self.builtin, self.instance)?;
+        if self.is_output() {
+            write!(f, "output {}", self.offset - outputs.start)
+        } else if outputs.is_empty() {
+            write!(f, "cell {}", self.offset)
+        } else {
+            write!(f, "input {}", self.offset)
+        }
+    }
+}
+
+/// The builtin segments of an execution, sorted by address.
+#[derive(Clone, Debug, Default)]
+pub struct BuiltinSegments(Vec<BuiltinSegment>);
+
+impl BuiltinSegments {
+    pub fn new(mut segments: Vec<BuiltinSegment>) -> Self {
+        segments.sort_by_key(|x| x.addrs.start);
+        Self(segments)
+    }
+
+    /// Find the builtin segments of a relocation table from the names of its segments. Each
+    /// segment extends up to the next one, and the last one up to the end of the memory.
+    pub fn from_relocation_table(table: &RelocationTable) -> Result<Self, Error> {
+        let mut segments = Vec::new();
+        for (segment, base) in table.bases().iter().enumerate() {
+            if let SegmentKind::Builtin(name) = table.segment_kind(segment) {
+                segments.push(BuiltinSegment {
+                    builtin: name.parse()?,
+                    addrs: *base..table.segment_end(segment).unwrap_or(usize::MAX),
+                });
+            }
+        }
+
+        Ok(Self::new(segments))
+    }
+
+    /// Parse the `memory_segments` of the AIR public input written by the runner, which has the
+    /// relocated start and stop pointers of every builtin segment.
+    pub fn from_public_input(json: &str) -> Result<Self, Error> {
+        let error = |e: &dyn fmt::Display| Error::InvalidPublicInput(e.to_string());
+        let input: Value = serde_json::from_str(json).map_err(|e| error(&e))?;
+        let memory_segments = input
+            .get("memory_segments")
+            .and_then(Value::as_object)
+            .ok_or_else(|| error(&"missing memory_segments"))?;
+
+        let mut segments = Vec::new();
+        for (name, addrs) in memory_segments {
+            if name == "program" || name == "execution" {
+                continue;
+            }
+
+            let addr = |key| {
+                addrs
+                    .get(key)
+                    .and_then(Value::as_u64)
+                    .map(|x| x as usize)
+                    .ok_or_else(|| error(&format_args!("invalid {key} for segment {name}")))
+            };
+            segments.push(BuiltinSegment {
+                builtin: name.parse()?,
+                addrs: addr("begin_addr")?..addr("stop_ptr")?,
+            });
+        }
+
+        Ok(Self::new(segments))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BuiltinSegment> {
+        self.0.iter()
+    }
+
+    /// Return the builtin segment a value belongs to.
+    pub fn segment(&self, value: ValueId) -> Option<&BuiltinSegment> {
+        let index = self
+            .0
+            .partition_point(|x| x.addrs.start <= value.0)
+            .checked_sub(1)?;
+        let segment = &self.0[index];
+        segment.addrs.contains(&value.0).then_some(segment)
+    }
+
+    /// Classify a value within its builtin segment, if any.
+    pub fn cell(&self, value: ValueId) -> Option<BuiltinCell> {
+        let segment = self.segment(value)?;
+        let offset = value.0 - segment.addrs.start;
+        let cells = segment.builtin.cells_per_instance();
+        Some(BuiltinCell {
+            builtin: segment.builtin,
+            instance: offset / cells,
+            offset: offset % cells,
+        })
+    }
+}
@@ -0,0 +1,203 @@
+//! Reconstruct the call tree of an execution from its `call` and `ret` instructions.
+
+use crate::{program::try_decode_repr, GraphMappings, Memory, StepId, Trace, ValueId};
+use cairo_vm::types::instruction::{
+    FpUpdate, Instruction as InstructionRepr, Op1Addr, Opcode, PcUpdate, Register, Res,
+};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct FrameId(pub usize);
+
+/// A function invocation.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub parent: Option<FrameId>,
+    pub children: Vec<FrameId>,
+    /// The pc of the function's first instruction.
+    pub entry_pc: usize,
+    pub fp: usize,
+    /// The `call` which created the frame, or `None` for the root.
+    pub call_step: Option<StepId>,
+    /// The first step executed within the frame.
+    pub entry_step: StepId,
+    /// The `ret` which left the frame, or `None` if the function never returned.
+    pub exit_step: Option<StepId>,
+    /// The arguments, from the lowest address up to `[fp - 3]`.
+    pub arguments: Vec<ValueId>,
+    /// The return values, from the lowest address up to `[ap - 1]` at the `ret`. Empty when none
+    /// of them is used after the `ret`, as for the outermost frame.
+    pub return_values: Vec<ValueId>,
+}
+
+/// The tree of function frames of an execution.
+#[derive(Clone, Debug)]
+pub struct CallTree {
+    /// Every frame in the order they were entered. The root, if any, is the first one.
+    pub frames: Vec<Frame>,
+    /// The frame every step was executed in.
+    step_frames: Vec<FrameId>,
+}
+
+impl CallTree {
+    /// Build the call tree of an execution.
+    ///
+    /// Every `call` enters a new frame and every `ret` leaves the current one. If the frame
+    /// pointer restored by a `ret` belongs to an outer frame, the frames in between are left too,
+    /// so a call that wasn't recognised doesn't shift the rest of the tree.
+    ///
+    /// The number of arguments isn't encoded anywhere, so the arguments are the cells down to the
+    /// lowest `[fp - 3 - n]` accessed by the frame's instructions. Likewise, the return values
+    /// are the cells below the `ap` of the `ret`, down to the lowest one which is accessed after
+    /// the function returns.
+    pub fn new(memory: &Memory, trace: &Trace, mappings: &GraphMappings) -> Self {
+        let mut step_frames = Vec::with_capacity(trace.len());
+        let Some(first) = trace.first() else {
+            return Self {
+                frames: Vec::new(),
+                step_frames,
+            };
+        };
+
+        let mut frames = vec![Frame::new(None, first.pc, first.fp, None, StepId(0))];
+        // The highest `n` of the `[fp - 3 - n]` cells accessed by every frame.
+        let mut max_args = vec![None];
+
+        let mut stack = vec![FrameId(0)];
+        let mut entries = trace.iter().enumerate().peekable();
+        while let Some((step, entry)) = entries.next() {
+            let current = *stack.last().unwrap();
+            step_frames.push(current);
+
+            let Ok(repr) = try_decode_repr(memory, entry.pc) else {
+                continue;
+            };
+            if let Some(n) = fp_arguments(&repr).max() {
+                max_args[current.0] = max_args[current.0].max(Some(n));
+            }
+
+            let next = entries.peek().map(|x| &x.1);
+            match (repr.opcode, next) {
+                (Opcode::Call, Some(next)) => {
+                    let id = FrameId(frames.len());
+                    frames.push(Frame::new(
+                        Some(current),
+                        next.pc,
+                        next.fp,
+                        Some(StepId(step)),
+                        StepId(step + 1),
+                    ));
+                    max_args.push(None);
+                    frames[current.0].children.push(id);
+                    stack.push(id);
+                }
+                (Opcode::Ret, _) => {
+                    frames[current.0].exit_step = Some(StepId(step));
+                    if stack.len() > 1 {
+                        stack.pop();
+                    }
+                    if let Some(next) = next {
+                        if let Some(depth) = stack.iter().rposition(|x| frames[x.0].fp == next.fp) {
+                            stack.truncate(depth + 1);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (frame, max_args) in frames.iter_mut().zip(max_args) {
+            if let Some(n) = max_args {
+                frame.arguments = (0..=n)
+                    .rev()
+                    .filter_map(|n| frame.fp.checked_sub(3 + n))
+                    .map(ValueId)
+                    .collect();
+            }
+
+            let Some(exit_step) = frame.exit_step else {
+                continue;
+            };
+            let ap = trace.get(exit_step.0).unwrap().ap;
+            let is_used_later = |addr| {
+                mappings
+                    .value2step()
+                    .get(&ValueId(addr))
+                    .is_some_and(|steps| steps.iter().any(|x| *x > exit_step))
+            };
+            if let Some(start) = (frame.fp..ap).find(|x| is_used_later(*x)) {
+                frame.return_values = (start..ap).map(ValueId).collect();
+            }
+        }
+
+        Self {
+            frames,
+            step_frames,
+        }
+    }
+
+    pub fn root(&self) -> Option<FrameId> {
+        (!self.frames.is_empty()).then_some(FrameId(0))
+    }
+
+    pub fn frame(&self, id: FrameId) -> &Frame {
+        &self.frames[id.0]
+    }
+
+    /// Return the frame a step was executed in.
+    pub fn step_frame(&self, step: StepId) -> Option<FrameId> {
+        self.step_frames.get(step.0).copied()
+    }
+
+    /// Return the frame a value belongs to: the one of the first step which accesses it.
+    pub fn value_frame(&self, mappings: &GraphMappings, value: ValueId) -> Option<FrameId> {
+        self.step_frame(mappings.first_access(value)?)
+    }
+
+    /// Iterate over a frame and its ancestors, from the innermost to the root.
+    pub fn backtrace(&self, id: FrameId) -> impl Iterator<Item = FrameId> + '_ {
+        std::iter::successors(Some(id), |x| self.frame(*x).parent)
+    }
+}
+
+impl Frame {
+    fn new(
+        parent: Option<FrameId>,
+        entry_pc: usize,
+        fp: usize,
+        call_step: Option<StepId>,
+        entry_step: StepId,
+    ) -> Self {
+        Self {
+            parent,
+            children: Vec::new(),
+            entry_pc,
+            fp,
+            call_step,
+            entry_step,
+            exit_step: None,
+            arguments: Vec::new(),
+            return_values: Vec::new(),
+        }
+    }
+}
+
+/// Iterate over the `n` of the `[fp - 3 - n]` cells accessed by an instruction.
+fn fp_arguments(repr: &InstructionRepr) -> impl Iterator<Item = usize> {
+    let dst = match repr.opcode {
+        Opcode::AssertEq | Opcode::Call | Opcode::Ret => true,
+        Opcode::NOp => repr.pc_update == PcUpdate::Jnz || repr.fp_update == FpUpdate::Dst,
+    };
+    let op0 = repr.opcode == Opcode::Call
+        || matches!(repr.res, Res::Add | Res::Mul)
+        || repr.op1_addr == Op1Addr::Op0;
+
+    [
+        (dst && repr.dst_register == Register::FP).then_some(repr.off0),
+        (op0 && repr.op0_register == Register::FP).then_some(repr.off1),
+        (repr.op1_addr == Op1Addr::FP).then_some(repr.off2),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|offset| usize::try_from(-3 - offset).ok())
+}
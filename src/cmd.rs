@@ -0,0 +1,352 @@
+use bincode::de::read::SliceReader;
+use cairo_lang_casm::hints::Hint;
+use cairo_vm::serde::deserialize_program::HintParams;
+use clap::{Args, ValueEnum};
+use serde_json::Value;
+use sierra2casm_dbg::{
+    relocation::PROGRAM_SEGMENT, search::NodeId, try_decode_any_instruction, BuiltinSegments,
+    Error, GraphMappings, Memory, RelocationTable, SierraLocation, SierraMapping, StepId, Trace,
+    ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+pub mod calls;
+pub mod diff;
+pub mod disasm;
+pub mod export;
+pub mod gas;
+pub mod profile;
+pub mod provenance;
+pub mod repl;
+pub mod search;
+pub mod taint;
+pub mod verify;
+
+/// Options to load an execution from disk.
+#[derive(Debug, Args)]
+pub struct LoadArgs {
+    #[clap(long)]
+    memory_path: PathBuf,
+    #[clap(long)]
+    trace_path: PathBuf,
+    #[clap(long)]
+    program_path: Option<PathBuf>,
+    /// Relocated address of the program segment. Defaults to the relocation table's, or `1`.
+    #[clap(long)]
+    program_offset: Option<usize>,
+
+    #[clap(long, value_enum, default_value_t = MemoryFormat::Relocated)]
+    memory_format: MemoryFormat,
+    #[clap(long, value_enum, default_value_t = TraceFormat::Relocated)]
+    trace_format: TraceFormat,
+    /// Base addresses of every segment, separated by whitespace. Computed from the memory when
+    /// it's segment-relative.
+    #[clap(long)]
+    relocation_table: Option<PathBuf>,
+    /// Names of the builtin segments, in order. Defaults to the `builtins` of the program JSON.
+    #[clap(long, num_args = 1..)]
+    builtins: Vec<String>,
+    /// AIR public input written by the runner, with the addresses of every builtin segment. Takes
+    /// precedence over the builtin names of the relocation table.
+    #[clap(long)]
+    air_public_input: Option<PathBuf>,
+
+    /// Sierra program, as JSON or text, used to annotate steps with their Sierra statements.
+    #[clap(long, requires = "sierra_debug_info_path")]
+    sierra_path: Option<PathBuf>,
+    /// Sierra to CASM debug info as JSON, with the `sierra_statement_info` offsets.
+    #[clap(long, requires = "sierra_path")]
+    sierra_debug_info_path: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum MemoryFormat {
+    /// Flat dump with relocated addresses.
+    Relocated,
+    /// Segment-relative dump with `(segment, offset)` addresses and pointers.
+    Segments,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum TraceFormat {
+    /// Trace with relocated registers.
+    Relocated,
+    /// Trace with a segment-relative pc and execution segment offsets for ap and fp.
+    Unrelocated,
+}
+
+/// Everything loaded from disk, along with the graph mappings.
+pub struct Session {
+    pub memory: Memory,
+    pub trace: Trace,
+    pub hints: HashMap<usize, Vec<Hint>>,
+    pub table: Option<RelocationTable>,
+    pub builtins: BuiltinSegments,
+    pub program_offset: usize,
+    pub mappings: GraphMappings,
+    pub sierra: Option<SierraMapping>,
+}
+
+impl Session {
+    pub fn load(args: LoadArgs) -> Result<Self, Error> {
+        //
+        // Load data from disk.
+        //
+        println!("Loading memory and trace.");
+        let table = args
+            .relocation_table
+            .map(|path| read_to_string(path)?.parse::<RelocationTable>())
+            .transpose()?;
+        let (memory, table) = match args.memory_format {
+            MemoryFormat::Relocated => (Memory::map_file(&args.memory_path)?, table),
+            MemoryFormat::Segments => {
+                let (memory, table) = Memory::try_decode_segments(
+                    SliceReader::new(&read(&args.memory_path)?),
+                    table,
+                )?;
+                (memory, Some(table))
+            }
+        };
+        let trace = match args.trace_format {
+            TraceFormat::Relocated => Trace::map_file(&args.trace_path)?,
+            TraceFormat::Unrelocated => Trace::try_decode_unrelocated(
+                SliceReader::new(&read(&args.trace_path)?),
+                table.as_ref().ok_or(Error::MissingRelocationTable)?,
+            )?,
+        };
+        println!("  {:?}", trace.first().ok_or(Error::EmptyTrace)?);
+        println!("  {:?}", trace.last().ok_or(Error::EmptyTrace)?);
+
+        let mut builtin_names = args.builtins;
+        let hints = match args.program_path {
+            None => HashMap::default(),
+            Some(program_path) => {
+                println!("Loading hints from provided program JSON.");
+                let invalid = |e: &dyn fmt::Display| Error::InvalidProgram(e.to_string());
+                let mut program_json: Value =
+                    serde_json::from_slice(&read(program_path)?).map_err(|e| invalid(&e))?;
+                let program_json = program_json
+                    .as_object_mut()
+                    .ok_or_else(|| invalid(&"not an object"))?;
+                if let Some(builtins) = program_json
+                    .remove("builtins")
+                    .filter(|_| builtin_names.is_empty())
+                {
+                    builtin_names = serde_json::from_value(builtins).map_err(|e| invalid(&e))?;
+                }
+                let hints = program_json
+                    .remove("hints")
+                    .ok_or_else(|| invalid(&"missing hints"))?;
+
+                let hints: HashMap<usize, Vec<HintParams>> =
+                    serde_json::from_value(hints).map_err(|e| invalid(&e))?;
+                hints
+                    .into_iter()
+                    .map(|(offset, value)| {
+                        let hints = value
+                            .into_iter()
+                            .map(|hint_params| {
+                                serde_json::from_str(&hint_params.code).map_err(|e| {
+                                    Error::InvalidHint {
+                                        offset,
+                                        error: e.to_string(),
+                                    }
+                                })
+                            })
+                            .collect::<Result<_, _>>()?;
+                        Ok((offset, hints))
+                    })
+                    .collect::<Result<_, Error>>()?
+            }
+        };
+
+        let table = table.map(|x| x.with_end(memory.len()).with_builtins(builtin_names));
+        let builtins = match args.air_public_input {
+            Some(path) => {
+                println!("Loading builtin segments from the AIR public input.");
+                BuiltinSegments::from_public_input(&read_to_string(path)?)?
+            }
+            None => table.as_ref().map_or(Ok(BuiltinSegments::default()), |x| {
+                BuiltinSegments::from_relocation_table(x)
+            })?,
+        };
+
+        let sierra = args
+            .sierra_path
+            .zip(args.sierra_debug_info_path)
+            .map(|(program_path, debug_info_path)| {
+                println!("Loading Sierra program and debug info.");
+                SierraMapping::parse(
+                    &read_to_string(program_path)?,
+                    &read_to_string(debug_info_path)?,
+                )
+            })
+            .transpose()?;
+
+        //
+        // Generate graph mappings.
+        //
+        println!("Generating graph mappings.");
+        let program_offset = args
+            .program_offset
+            .or_else(|| table.as_ref().and_then(|x| x.relocate(PROGRAM_SEGMENT, 0)))
+            .unwrap_or(1);
+        let mappings = GraphMappings::new(&memory, &trace, &hints, program_offset)
+            .with_builtins(&memory, &builtins);
+
+        Ok(Self {
+            memory,
+            trace,
+            hints,
+            table,
+            builtins,
+            program_offset,
+            mappings,
+            sierra,
+        })
+    }
+
+    /// Format a value's address. Values are reported as `segment:offset` when the segments are
+    /// known.
+    pub fn format_value(&self, id: ValueId) -> String {
+        match &self.table {
+            Some(table) => table.display(id),
+            None => id.0.to_string(),
+        }
+    }
+
+    pub fn format_addr(&self, addr: usize) -> String {
+        self.format_value(ValueId(addr))
+    }
+
+    /// Format the builtin cell a value is, as a trailing comment, or nothing if it isn't one.
+    pub fn format_builtin(&self, id: ValueId) -> String {
+        self.builtins
+            .cell(id)
+            .map(|x| format!(" // {x}"))
+            .unwrap_or_default()
+    }
+
+    /// Resolve an address given by the user.
+    pub fn resolve(&self, addr: Address) -> Result<usize, String> {
+        match addr {
+            Address::Relocated(addr) => Ok(addr),
+            Address::Segment(segment, offset) => self
+                .table
+                .as_ref()
+                .ok_or("Segment-relative addresses require a relocation table.")?
+                .relocate(segment, offset)
+                .ok_or_else(|| format!("Unknown segment {segment}.")),
+        }
+    }
+
+    /// Return the hints attached to the instruction at `pc`.
+    pub fn hints_at(&self, pc: usize) -> &[Hint] {
+        pc.checked_sub(self.program_offset)
+            .and_then(|pc| self.hints.get(&pc))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Return the Sierra origin of the instruction at `pc`, if a Sierra program was loaded.
+    pub fn locate(&self, pc: usize) -> Option<SierraLocation<'_>> {
+        self.sierra
+            .as_ref()?
+            .locate(pc.checked_sub(self.program_offset)?)
+    }
+
+    /// Format the function starting at `pc`, by name if a Sierra program was loaded.
+    pub fn format_function(&self, pc: usize) -> String {
+        match self.locate(pc).and_then(|x| x.function) {
+            Some(function) => function.to_string(),
+            None => format!("pc {}", self.format_addr(pc)),
+        }
+    }
+
+    /// Format a step as a single line with its pc, instruction and Sierra origin.
+    pub fn format_step(&self, step: StepId) -> String {
+        let entry = self.trace.get(step.0).unwrap();
+        let instr = try_decode_any_instruction(&self.memory, entry.pc)
+            .map_or_else(|e| format!("<{e}>"), |x| x.to_string());
+        let location = self
+            .locate(entry.pc)
+            .map(|x| format!(" // {x}"))
+            .unwrap_or_default();
+        format!(
+            "step {} (pc = {}): {instr}{location}",
+            step.0,
+            self.format_addr(entry.pc)
+        )
+    }
+
+    /// Print a path with the instruction of every step.
+    pub fn print_path(&self, path: &[NodeId]) {
+        println!("Connecting path (spans {} steps):", path.len() >> 1);
+        for id in path {
+            match id {
+                NodeId::Step(offset) => {
+                    let entry = self.trace.get(offset.0).unwrap();
+                    if let Some(location) = self.locate(entry.pc) {
+                        println!("// {location}");
+                    }
+                    for hint in self.hints_at(entry.pc) {
+                        println!("; {}", hint.representing_string());
+                    }
+                    match try_decode_any_instruction(&self.memory, entry.pc) {
+                        Ok(instr) => println!("{instr}"),
+                        Err(e) => println!("<{e}>"),
+                    }
+                    println!("    {entry:?}");
+                }
+                NodeId::Value(offset) => {
+                    let value = match self.memory.get(offset.0) {
+                        Some(value) => value.to_string(),
+                        None => "<uninitialized>".to_string(),
+                    };
+                    println!(
+                        "  [{}] = {value}{}",
+                        self.format_value(*offset),
+                        self.format_builtin(*offset),
+                    );
+                    println!();
+                }
+            }
+        }
+    }
+}
+
+/// An address, either relocated or as `segment:offset`.
+#[derive(Clone, Copy, Debug)]
+pub enum Address {
+    Relocated(usize),
+    Segment(usize, usize),
+}
+
+impl FromStr for Address {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |x: &str| x.parse::<usize>().map_err(|e| e.to_string());
+        match s.split_once(':') {
+            Some((segment, offset)) => Ok(Address::Segment(parse(segment)?, parse(offset)?)),
+            None => Ok(Address::Relocated(parse(s)?)),
+        }
+    }
+}
+
+pub fn parse_felt252(input: &str) -> Result<Felt, String> {
+    Felt::from_str(input).map_err(|e| e.to_string())
+}
+
+fn read(path: impl AsRef<Path>) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(Error::Io)
+}
+
+fn read_to_string(path: impl AsRef<Path>) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(Error::Io)
+}
@@ -0,0 +1,108 @@
+use super::{LoadArgs, Session};
+use clap::Args;
+use sierra2casm_dbg::{
+    calls::{CallTree, FrameId},
+    Error, StepId, ValueId,
+};
+
+#[derive(Debug, Args)]
+pub struct CallsArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    /// Only print frames up to this many calls deep.
+    #[clap(long)]
+    max_depth: Option<usize>,
+    /// Print the frames enclosing a step instead of the whole tree.
+    #[clap(long)]
+    step: Option<usize>,
+}
+
+pub fn run(args: CallsArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+    let tree = CallTree::new(&session.memory, &session.trace, &session.mappings);
+
+    println!();
+    match args.step {
+        Some(step) => {
+            let frame = tree
+                .step_frame(StepId(step))
+                .unwrap_or_else(|| panic!("Step {step} is out of range."));
+            print_backtrace(&session, &tree, frame);
+        }
+        None => print_call_tree(&session, &tree, args.max_depth),
+    }
+
+    Ok(())
+}
+
+/// Print the call tree, one frame per line. Frames deeper than `max_depth` are summarized.
+pub fn print_call_tree(session: &Session, tree: &CallTree, max_depth: Option<usize>) {
+    let mut stack = tree.root().map(|x| (x, 0)).into_iter().collect::<Vec<_>>();
+    while let Some((id, depth)) = stack.pop() {
+        let frame = tree.frame(id);
+        println!(
+            "{:indent$}{}",
+            "",
+            format_frame(session, tree, id),
+            indent = 2 * depth
+        );
+
+        if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            if !frame.children.is_empty() {
+                println!(
+                    "{:indent$}... {} calls",
+                    "",
+                    frame.children.len(),
+                    indent = 2 * (depth + 1)
+                );
+            }
+            continue;
+        }
+        stack.extend(frame.children.iter().rev().map(|x| (*x, depth + 1)));
+    }
+
+    println!();
+    println!("Found {} frames.", tree.frames.len());
+}
+
+/// Print a frame and the frames enclosing it, along with the calls which created them.
+pub fn print_backtrace(session: &Session, tree: &CallTree, id: FrameId) {
+    for (index, id) in tree.backtrace(id).enumerate() {
+        println!("#{index} {}", format_frame(session, tree, id));
+        if let Some(step) = tree.frame(id).call_step {
+            println!("   called from {}", session.format_step(step));
+        }
+    }
+}
+
+/// Format a frame as a single line with its function, arguments, return values and steps.
+fn format_frame(session: &Session, tree: &CallTree, id: FrameId) -> String {
+    let frame = tree.frame(id);
+    let format_values = |values: &[ValueId]| {
+        values
+            .iter()
+            .map(|x| {
+                format!(
+                    "[{}] = {}",
+                    session.format_value(*x),
+                    session.memory.get(x.0).unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let exit_step = frame
+        .exit_step
+        .map(|x| format!("={}", x.0))
+        .unwrap_or_default();
+
+    format!(
+        "{}({}) -> ({}) // fp = {}, steps {}..{exit_step}",
+        session.format_function(frame.entry_pc),
+        format_values(&frame.arguments),
+        format_values(&frame.return_values),
+        session.format_addr(frame.fp),
+        frame.entry_step.0,
+    )
+}
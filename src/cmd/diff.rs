@@ -0,0 +1,133 @@
+use super::{LoadArgs, Session};
+use clap::Args;
+use sierra2casm_dbg::{
+    diff::{diff_executions, DiffOp, Execution, TraceDiff},
+    try_decode_any_instruction, Error, Memory, Trace,
+};
+use starknet_types_core::felt::Felt;
+use std::{path::PathBuf, process};
+
+/// Maximum number of differing cells printed.
+const MAX_ROWS: usize = 32;
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    /// Relocated memory of the execution to compare against.
+    #[clap(long)]
+    other_memory_path: PathBuf,
+    /// Relocated trace of the execution to compare against.
+    #[clap(long)]
+    other_trace_path: PathBuf,
+    /// Relocated address of the other execution's program segment. Defaults to the first one's.
+    #[clap(long)]
+    other_program_offset: Option<usize>,
+}
+
+pub fn run(args: DiffArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+
+    println!("Loading the other memory and trace.");
+    let memory = Memory::map_file(&args.other_memory_path)?;
+    let trace = Trace::map_file(&args.other_trace_path)?;
+    let other = Execution {
+        memory: &memory,
+        trace: &trace,
+        program_offset: args.other_program_offset.unwrap_or(session.program_offset),
+    };
+
+    let diff = diff_executions(
+        &Execution {
+            memory: &session.memory,
+            trace: &session.trace,
+            program_offset: session.program_offset,
+        },
+        &other,
+    );
+
+    println!();
+    print_diff(&session, &other, &diff);
+    if !diff.is_empty() {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Print a summary of the alignment, the first divergence, the differing cells and the path which
+/// leads to the first of them.
+pub fn print_diff(session: &Session, other: &Execution, diff: &TraceDiff) {
+    let (mut equal, mut removed, mut inserted) = (0, 0, 0);
+    for op in &diff.ops {
+        match op {
+            DiffOp::Equal(..) => equal += 1,
+            DiffOp::Removed(_) => removed += 1,
+            DiffOp::Inserted(_) => inserted += 1,
+        }
+    }
+    println!(
+        "Aligned {} steps with {} steps: {equal} match, {removed} only in the first execution and \
+         {inserted} only in the second.",
+        session.trace.len(),
+        other.trace.len(),
+    );
+
+    let Some(first_divergence) = diff.first_divergence else {
+        println!("The executions are identical.");
+        return;
+    };
+    match first_divergence {
+        DiffOp::Equal(left, right) => {
+            println!(
+                "First divergence at step {} of the first execution and step {} of the second:",
+                left.0, right.0
+            );
+            println!("  {}", session.format_step(left));
+        }
+        DiffOp::Removed(left) => {
+            println!(
+                "First divergence at step {}, which is only in the first execution:",
+                left.0
+            );
+            println!("  {}", session.format_step(left));
+        }
+        DiffOp::Inserted(right) => {
+            println!(
+                "First divergence at step {}, which is only in the second execution:",
+                right.0
+            );
+            let pc = other.trace.get(right.0).unwrap().pc;
+            let instr = try_decode_any_instruction(other.memory, pc)
+                .map_or_else(|e| format!("<{e}>"), |x| x.to_string());
+            println!("  step {} (pc = {pc}): {instr}", right.0);
+        }
+    }
+
+    println!();
+    println!("Differing cells ({}):", diff.cells.len());
+    let format_cell = |x: Option<Felt>| match x {
+        Some(value) => value.to_string(),
+        None => "<uninitialized>".to_string(),
+    };
+    for cell in diff.cells.iter().take(MAX_ROWS) {
+        println!(
+            "  [{}] = {} vs [{}] = {} (steps {} and {})",
+            session.format_value(cell.left),
+            format_cell(cell.left_value),
+            cell.right.0,
+            format_cell(cell.right_value),
+            cell.left_step.0,
+            cell.right_step.0,
+        );
+    }
+    if diff.cells.len() > MAX_ROWS {
+        println!("  ... and {} more.", diff.cells.len() - MAX_ROWS);
+    }
+
+    if let Some(path) = diff.divergence_path(&session.mappings) {
+        println!();
+        session.print_path(&path);
+    }
+}
@@ -0,0 +1,116 @@
+use super::{Address, LoadArgs, Session};
+use clap::Args;
+use sierra2casm_dbg::{
+    disassemble, relocation::PROGRAM_SEGMENT, AnyInstruction, Error, ListingEntry,
+};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Args)]
+pub struct DisasmArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    /// First address to disassemble. Defaults to the start of the program segment.
+    #[clap(long)]
+    start: Option<Address>,
+    /// Address where the listing stops. Defaults to the end of the program segment, or to the
+    /// first uninitialized cell.
+    #[clap(long)]
+    end: Option<Address>,
+    /// Emit CASM text that can be assembled again, instead of a listing.
+    #[clap(long)]
+    casm: bool,
+}
+
+pub fn run(args: DisasmArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+    let resolve = |x: Address| session.resolve(x).unwrap_or_else(|e| panic!("{e}"));
+
+    let start = args.start.map_or(session.program_offset, resolve);
+    let end = args
+        .end
+        .map(resolve)
+        .or_else(|| {
+            session
+                .table
+                .as_ref()
+                .and_then(|x| x.relocate(PROGRAM_SEGMENT + 1, 0))
+        })
+        .unwrap_or(usize::MAX);
+    let listing = disassemble(&session.memory, start..end).collect::<Vec<_>>();
+
+    // Name every jump and call target within the listing after its offset in the program.
+    let labels = listing
+        .iter()
+        .filter_map(ListingEntry::target)
+        .filter(|x| listing.iter().any(|entry| entry.pc == *x))
+        .map(|x| (x, format!("L{}", x.wrapping_sub(session.program_offset))))
+        .collect::<BTreeMap<_, _>>();
+
+    println!();
+    for entry in &listing {
+        if args.casm {
+            print_casm(&session, entry, &labels);
+        } else {
+            print_listing(&session, entry, &labels);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_listing(session: &Session, entry: &ListingEntry, labels: &BTreeMap<usize, String>) {
+    if let Some(label) = labels.get(&entry.pc) {
+        println!("{label}:");
+    }
+    if let Some(location) = session.locate(entry.pc) {
+        println!("  // {location}");
+    }
+    for hint in session.hints_at(entry.pc) {
+        println!("  ; {}", hint.representing_string());
+    }
+
+    let raw = entry
+        .raw
+        .iter()
+        .map(|x| format!("{x:#x}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let body = match &entry.instruction {
+        Some(instr) => match entry.target().and_then(|x| labels.get(&x)) {
+            Some(label) => format!("{instr} ({label})"),
+            None => instr.to_string(),
+        },
+        None => "<data>".to_string(),
+    };
+    println!("  {:>8}: {raw:<40} {body}", session.format_addr(entry.pc));
+}
+
+fn print_casm(session: &Session, entry: &ListingEntry, labels: &BTreeMap<usize, String>) {
+    if let Some(label) = labels.get(&entry.pc) {
+        println!("// {label}:");
+    }
+
+    match &entry.instruction {
+        Some(AnyInstruction::Casm(instr)) => {
+            let mut instr = instr.clone();
+            instr.hints = session.hints_at(entry.pc).to_vec();
+            match entry.target().and_then(|x| labels.get(&x)) {
+                Some(label) => println!("{instr}; // {label}"),
+                None => println!("{instr};"),
+            }
+        }
+        // Neither data nor instructions outside of the Sierra compiler's subset can be represented
+        // as CASM.
+        Some(AnyInstruction::Generic(instr)) => {
+            let raw = entry
+                .raw
+                .iter()
+                .map(|x| format!("{x:#x}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!("// dw {raw}; {instr}");
+        }
+        None => println!("// dw {:#x};", entry.raw[0]),
+    }
+}
@@ -0,0 +1,128 @@
+use super::{Address, LoadArgs, Session};
+use clap::{ArgGroup, Args, ValueEnum};
+use sierra2casm_dbg::{
+    export::{to_dot, JsonGraph, Subgraph},
+    search::NodeId,
+    Error, StepId, ValueId,
+};
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Args)]
+#[clap(group(ArgGroup::new("selection").required(true).args(["value", "step", "steps"])))]
+pub struct ExportArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    #[clap(long, value_enum, default_value_t = ExportFormat::Dot)]
+    format: ExportFormat,
+    #[clap(short, long)]
+    output: PathBuf,
+
+    /// Export the neighbourhood of a memory cell.
+    #[clap(long)]
+    value: Option<Address>,
+    /// Export the neighbourhood of a step.
+    #[clap(long)]
+    step: Option<usize>,
+    /// Maximum number of edges between the center of a neighbourhood and its nodes.
+    #[clap(long, default_value_t = 2)]
+    radius: usize,
+    /// Export the steps within a window, as `<start>..<end>`, and the values they access.
+    #[clap(long, value_parser = parse_range)]
+    steps: Option<Range<usize>>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ExportFormat {
+    /// Graphviz DOT.
+    Dot,
+    /// JSON, with the nodes and the edges between them.
+    Json,
+}
+
+fn parse_range(input: &str) -> Result<Range<usize>, String> {
+    let (start, end) = input.split_once("..").ok_or("expected `<start>..<end>`")?;
+    let parse = |x: &str| x.parse::<usize>().map_err(|e| e.to_string());
+    Ok(parse(start)?..parse(end)?)
+}
+
+/// Options to export the paths found by a search.
+#[derive(Debug, Args)]
+pub struct ExportPathsArgs {
+    /// Write the paths found as Graphviz DOT.
+    #[clap(long)]
+    dot: Option<PathBuf>,
+    /// Write the paths found as JSON.
+    #[clap(long)]
+    json: Option<PathBuf>,
+}
+
+impl ExportPathsArgs {
+    /// Whether the paths should be kept to be exported.
+    pub fn is_enabled(&self) -> bool {
+        self.dot.is_some() || self.json.is_some()
+    }
+
+    /// Export the subgraph made of the paths, highlighting them.
+    pub fn write(&self, session: &Session, paths: &[Vec<NodeId>]) {
+        let graph = Subgraph::from_paths(paths.iter().map(Vec::as_slice));
+        if let Some(path) = &self.dot {
+            write_graph(session, &graph, paths, ExportFormat::Dot, path);
+        }
+        if let Some(path) = &self.json {
+            write_graph(session, &graph, paths, ExportFormat::Json, path);
+        }
+    }
+}
+
+pub fn run(args: ExportArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+    let graph = match (args.value, args.step, args.steps) {
+        (Some(value), _, _) => {
+            let value = ValueId(session.resolve(value).unwrap_or_else(|e| panic!("{e}")));
+            Subgraph::neighborhood(&session.mappings, NodeId::Value(value), args.radius)
+        }
+        (_, Some(step), _) => {
+            Subgraph::neighborhood(&session.mappings, NodeId::Step(StepId(step)), args.radius)
+        }
+        (_, _, Some(steps)) => Subgraph::step_window(&session.mappings, steps),
+        _ => unreachable!(),
+    };
+
+    write_graph(&session, &graph, &[], args.format, &args.output);
+
+    Ok(())
+}
+
+fn write_graph(
+    session: &Session,
+    graph: &Subgraph,
+    paths: &[Vec<NodeId>],
+    format: ExportFormat,
+    path: &Path,
+) {
+    let Session {
+        memory,
+        trace,
+        mappings,
+        ..
+    } = session;
+
+    let data = match format {
+        ExportFormat::Dot => to_dot(memory, trace, mappings, graph, paths),
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&JsonGraph::new(memory, trace, mappings, graph, paths))
+                .unwrap()
+        }
+    };
+    fs::write(path, data).unwrap();
+    println!(
+        "Exported {} nodes to {}.",
+        graph.nodes.len(),
+        path.display()
+    );
+}
@@ -0,0 +1,83 @@
+use super::{Address, LoadArgs, Session};
+use clap::Args;
+use sierra2casm_dbg::{
+    calls::CallTree,
+    search::gas::{find_gas_argument, track_gas, GasTimeline},
+    Error, ValueId,
+};
+
+#[derive(Debug, Args)]
+pub struct GasArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    /// Memory cell with the initial gas counter, as `<addr>` or `<segment>:<offset>`. Defaults
+    /// to the gas argument of the outermost Sierra function which takes one.
+    #[clap(long)]
+    gas: Option<Address>,
+    /// Print the copies of the gas counter too.
+    #[clap(long)]
+    all: bool,
+}
+
+pub fn run(args: GasArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+    let initial = match args.gas {
+        Some(addr) => ValueId(session.resolve(addr).unwrap_or_else(|e| panic!("{e}"))),
+        None => {
+            let sierra = session
+                .sierra
+                .as_ref()
+                .expect("The gas counter must be given with `--gas` without a Sierra program.");
+            let tree = CallTree::new(&session.memory, &session.trace, &session.mappings);
+            find_gas_argument(&tree, sierra, session.program_offset)
+                .expect("No Sierra function takes a gas counter.")
+        }
+    };
+
+    println!();
+    let timeline = track_gas(
+        &session.memory,
+        &session.trace,
+        &session.mappings,
+        &session.hints,
+        session.program_offset,
+        initial,
+    );
+    print_timeline(&session, &timeline, args.all);
+
+    Ok(())
+}
+
+/// Print every change of the gas counter along with the step responsible for it.
+pub fn print_timeline(session: &Session, timeline: &GasTimeline, all: bool) {
+    println!(
+        "Initial gas counter: [{}] = {}",
+        session.format_value(timeline.initial),
+        session.memory.get(timeline.initial.0).unwrap_or_default()
+    );
+    for change in &timeline.changes {
+        if change.delta == 0 && !all {
+            continue;
+        }
+        println!(
+            "  {:+} -> [{}] = {}: {}",
+            change.delta,
+            session.format_value(change.value),
+            change.gas,
+            session.format_step(change.step)
+        );
+    }
+
+    let (withdrawn, redeposited) = timeline
+        .updates()
+        .fold((0, 0), |(w, r), x| match x.delta < 0 {
+            true => (w - x.delta, r),
+            false => (w, r + x.delta),
+        });
+    println!();
+    println!(
+        "Withdrew {withdrawn} and redeposited {redeposited} gas over {} updates.",
+        timeline.updates().count()
+    );
+}
@@ -0,0 +1,113 @@
+use super::{LoadArgs, Session};
+use clap::Args;
+use sierra2casm_dbg::{
+    calls::CallTree,
+    profile::{Cost, Costs, Profile},
+    try_decode_any_instruction, Error,
+};
+use std::{cmp::Reverse, collections::BTreeMap, fs, path::PathBuf};
+
+/// Maximum number of functions or pcs printed.
+const MAX_ROWS: usize = 32;
+
+#[derive(Debug, Args)]
+pub struct ProfileArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    /// Print the cost of every pc instead of every function.
+    #[clap(long)]
+    pcs: bool,
+    /// Print every row instead of only the most expensive ones.
+    #[clap(long)]
+    all: bool,
+    /// Write the steps of every call stack as folded stacks, for flamegraph tools.
+    #[clap(long)]
+    folded: Option<PathBuf>,
+    /// Write the profile in the pprof format, uncompressed.
+    #[clap(long)]
+    pprof: Option<PathBuf>,
+}
+
+pub fn run(args: ProfileArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+    let tree = CallTree::new(&session.memory, &session.trace, &session.mappings);
+    let profile = Profile::new(
+        &session.memory,
+        &session.trace,
+        &tree,
+        session.sierra.as_ref(),
+        session.program_offset,
+    );
+
+    println!();
+    let max_rows = if args.all { usize::MAX } else { MAX_ROWS };
+    match args.pcs {
+        true => print_rows(&profile.pcs, max_rows, "pc", |pc| {
+            let instr = try_decode_any_instruction(&session.memory, pc)
+                .map_or_else(|e| format!("<{e}>"), |x| x.to_string());
+            format!("{}: {instr}", session.format_addr(pc))
+        }),
+        false => print_rows(&profile.functions, max_rows, "function", |pc| {
+            session.format_function(pc)
+        }),
+    }
+
+    let name = |pc| session.format_function(pc);
+    if let Some(path) = &args.folded {
+        fs::write(path, profile.folded_stacks(&tree, name)).unwrap();
+        println!("Exported the folded stacks to {}.", path.display());
+    }
+    if let Some(path) = &args.pprof {
+        fs::write(path, profile.to_pprof(&tree, name)).unwrap();
+        println!("Exported the pprof profile to {}.", path.display());
+    }
+
+    Ok(())
+}
+
+/// Print the most expensive rows by inclusive steps.
+fn print_rows(
+    rows: &BTreeMap<usize, Costs>,
+    max_rows: usize,
+    title: &str,
+    name: impl Fn(usize) -> String,
+) {
+    let mut rows = rows.iter().collect::<Vec<_>>();
+    rows.sort_by_key(|(pc, costs)| (Reverse(costs.inclusive.steps), **pc));
+
+    println!(
+        "{:>8} {:>12} {:>12} {:>10} {:>10}  {title}",
+        "count", "self steps", "total steps", "self gas", "total gas"
+    );
+    for (pc, costs) in rows.iter().take(max_rows) {
+        println!(
+            "{:>8} {:>12} {:>12} {:>10} {:>10}  {}{}",
+            costs.count,
+            costs.exclusive.steps,
+            costs.inclusive.steps,
+            costs.exclusive.gas,
+            costs.inclusive.gas,
+            name(**pc),
+            format_builtins(&costs.inclusive),
+        );
+    }
+    if rows.len() > max_rows {
+        println!("  ... and {} more.", rows.len() - max_rows);
+    }
+    println!();
+}
+
+fn format_builtins(cost: &Cost) -> String {
+    if cost.builtins.is_empty() {
+        return String::new();
+    }
+
+    let builtins = cost
+        .builtins
+        .iter()
+        .map(|(name, count)| format!("{name}: {count}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(" ({builtins})")
+}
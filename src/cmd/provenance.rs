@@ -0,0 +1,69 @@
+use super::{Address, LoadArgs, Session};
+use clap::Args;
+use sierra2casm_dbg::{
+    search::provenance::{find_provenance, Origin, Provenance},
+    Error, ValueId,
+};
+use std::collections::HashSet;
+
+#[derive(Debug, Args)]
+pub struct ProvenanceArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    /// Memory cell to explain, as `<addr>` or `<segment>:<offset>`.
+    addr: Address,
+    /// Only expand values up to this many steps away.
+    #[clap(long)]
+    max_depth: Option<usize>,
+}
+
+pub fn run(args: ProvenanceArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+    let value = ValueId(session.resolve(args.addr).unwrap_or_else(|e| panic!("{e}")));
+
+    println!();
+    print_provenance(
+        &session,
+        &find_provenance(&session.mappings, value, args.max_depth),
+    );
+
+    Ok(())
+}
+
+/// Print a provenance slice as a tree. Values reachable through more than one path are only
+/// expanded the first time.
+pub fn print_provenance(session: &Session, provenance: &Provenance) {
+    let mut printed = HashSet::new();
+    let mut stack = vec![(provenance.root, 0)];
+    while let Some((value, indent)) = stack.pop() {
+        let cell = match session.memory.get(value.0) {
+            Some(x) => x.to_string(),
+            None => "<uninitialized>".to_string(),
+        };
+        let prefix = format!("{:indent$}[{}] = {cell}", "", session.format_value(value));
+
+        if !printed.insert(value) {
+            println!("{prefix} (see above)");
+            continue;
+        }
+        match &provenance.origins[&value] {
+            Origin::Step { step, inputs } => {
+                println!("{prefix} <- {}", session.format_step(*step));
+                stack.extend(inputs.iter().rev().map(|x| (*x, indent + 2)));
+            }
+            Origin::Hint { step } => {
+                println!("{prefix} <- hint of {}", session.format_step(*step))
+            }
+            Origin::Input => println!("{prefix} (input)"),
+            Origin::Truncated => println!("{prefix} ..."),
+        }
+    }
+
+    println!();
+    println!(
+        "Slice has {} values and {} leaves.",
+        provenance.origins.len(),
+        provenance.leaves().count()
+    );
+}
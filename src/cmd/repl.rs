@@ -0,0 +1,312 @@
+use super::{
+    parse_felt252, provenance::print_provenance, taint::print_taint, Address, LoadArgs, Session,
+};
+use clap::Parser;
+use rustyline::{error::ReadlineError, DefaultEditor};
+use sierra2casm_dbg::{
+    search::{
+        provenance::find_provenance,
+        shortest::{find_shortest_path, Unweighted},
+        taint::find_taint,
+        Predicates,
+    },
+    try_decode_any_instruction, Error, StepId, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::str::FromStr;
+
+/// Maximum number of results printed by commands which list steps or cells.
+const MAX_RESULTS: usize = 32;
+
+#[derive(Debug, Parser)]
+#[command(multicall = true)]
+enum ReplCommand {
+    /// Show a step, its instruction and the memory cells it accesses.
+    Step { step: usize },
+    /// List the steps which executed the instruction at a pc.
+    Pc { pc: Address },
+    /// Show memory cells, as `<addr>` or `<addr>..<len>`.
+    Mem { range: MemRange },
+    /// List the steps which wrote or asserted a memory cell.
+    WhoWrote { addr: Address },
+    /// List the steps which read a memory cell.
+    WhoRead { addr: Address },
+    /// Find the shortest path between two memory cells.
+    Path { source: Address, target: Address },
+    /// Show the values a memory cell was computed from.
+    Provenance {
+        addr: Address,
+        /// Only expand values up to this many steps away.
+        max_depth: Option<usize>,
+    },
+    /// Find the values and sinks which depend on some memory cells.
+    Taint {
+        #[clap(required = true)]
+        addrs: Vec<Address>,
+    },
+    /// Find the memory cells containing a value.
+    Find {
+        #[clap(value_parser = parse_felt252)]
+        value: Felt,
+    },
+    /// Disassemble instructions starting at a pc.
+    Disasm {
+        pc: Address,
+        #[clap(default_value_t = 10)]
+        count: usize,
+    },
+    /// Exit the REPL.
+    #[command(alias = "exit")]
+    Quit,
+}
+
+/// A range of memory cells.
+#[derive(Clone, Copy, Debug)]
+struct MemRange {
+    addr: Address,
+    len: usize,
+}
+
+impl FromStr for MemRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once("..") {
+            Some((addr, len)) => Ok(MemRange {
+                addr: addr.parse()?,
+                len: len.parse().map_err(|e| format!("{e}"))?,
+            }),
+            None => Ok(MemRange {
+                addr: s.parse()?,
+                len: 1,
+            }),
+        }
+    }
+}
+
+pub fn run(args: LoadArgs) -> Result<(), Error> {
+    let session = Session::load(args)?;
+    println!();
+    println!("Type `help` for the list of commands.");
+
+    let mut editor = DefaultEditor::new().unwrap();
+    loop {
+        let line = match editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => panic!("{e}"),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line).unwrap();
+
+        match ReplCommand::try_parse_from(line.split_whitespace()) {
+            Ok(ReplCommand::Quit) => break,
+            Ok(command) => {
+                if let Err(e) = execute(&session, command) {
+                    println!("{e}");
+                }
+            }
+            Err(e) => e.print().unwrap(),
+        }
+    }
+
+    Ok(())
+}
+
+fn execute(session: &Session, command: ReplCommand) -> Result<(), String> {
+    let Session {
+        memory,
+        trace,
+        mappings,
+        ..
+    } = session;
+
+    match command {
+        ReplCommand::Step { step } => {
+            let entry = trace
+                .get(step)
+                .ok_or_else(|| format!("Step {step} is out of range."))?;
+            println!(
+                "Step {step}: pc = {}, ap = {}, fp = {}",
+                session.format_addr(entry.pc),
+                session.format_addr(entry.ap),
+                session.format_addr(entry.fp),
+            );
+            print_instruction(session, entry.pc);
+
+            let mut values = mappings
+                .step2value()
+                .get(&StepId(step))
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>();
+            values.sort();
+            for value in values {
+                println!(
+                    "  {:?} [{}] = {}",
+                    mappings.access_kind(StepId(step), value).unwrap(),
+                    session.format_value(value),
+                    format_cell(session, value.0),
+                );
+            }
+        }
+        ReplCommand::Pc { pc } => {
+            let pc = session.resolve(pc)?;
+            print_instruction(session, pc);
+            print_steps(
+                session,
+                trace
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, x)| x.pc == pc)
+                    .map(|(step, _)| StepId(step)),
+            );
+        }
+        ReplCommand::Mem { range } => {
+            let addr = session.resolve(range.addr)?;
+            let end = addr.checked_add(range.len).ok_or_else(|| {
+                format!(
+                    "Memory range at {} overflows the address space.",
+                    session.format_addr(addr)
+                )
+            })?;
+            for addr in addr..end {
+                println!(
+                    "  [{}] = {}",
+                    session.format_addr(addr),
+                    format_cell(session, addr)
+                );
+            }
+        }
+        ReplCommand::WhoWrote { addr } => {
+            let mut steps = mappings
+                .producers(ValueId(session.resolve(addr)?))
+                .collect::<Vec<_>>();
+            steps.sort();
+            print_steps(session, steps.into_iter());
+        }
+        ReplCommand::WhoRead { addr } => {
+            let mut steps = mappings
+                .consumers(ValueId(session.resolve(addr)?))
+                .collect::<Vec<_>>();
+            steps.sort();
+            print_steps(session, steps.into_iter());
+        }
+        ReplCommand::Path { source, target } => {
+            let source = ValueId(session.resolve(source)?);
+            let target = ValueId(session.resolve(target)?);
+            for value in [source, target] {
+                if !mappings.value2step().contains_key(&value) {
+                    return Err(format!(
+                        "Memory cell {} is never accessed.",
+                        session.format_value(value)
+                    ));
+                }
+            }
+
+            match find_shortest_path(
+                memory,
+                trace,
+                mappings,
+                source,
+                target,
+                Predicates::new(),
+                Unweighted,
+            ) {
+                Some(path) => session.print_path(&path),
+                None => println!("No path found."),
+            }
+        }
+        ReplCommand::Provenance { addr, max_depth } => {
+            let value = ValueId(session.resolve(addr)?);
+            print_provenance(session, &find_provenance(mappings, value, max_depth));
+        }
+        ReplCommand::Taint { addrs } => {
+            let seeds = addrs
+                .into_iter()
+                .map(|x| session.resolve(x).map(ValueId))
+                .collect::<Result<Vec<_>, _>>()?;
+            let taint = find_taint(
+                memory,
+                trace,
+                mappings,
+                &session.hints,
+                &session.builtins,
+                session.program_offset,
+                &seeds,
+            );
+            print_taint(session, &taint, MAX_RESULTS);
+        }
+        ReplCommand::Find { value } => {
+            let addrs = memory
+                .iter()
+                .filter(|(_, x)| *x == value)
+                .map(|(addr, _)| addr)
+                .collect::<Vec<_>>();
+            for addr in addrs.iter().take(MAX_RESULTS) {
+                println!("  [{}]", session.format_addr(*addr));
+            }
+            print_total(addrs.len());
+        }
+        ReplCommand::Disasm { pc, count } => {
+            let mut pc = session.resolve(pc)?;
+            for _ in 0..count {
+                if let Some(location) = session.locate(pc) {
+                    println!("  // {location}");
+                }
+                for hint in session.hints_at(pc) {
+                    println!("  ; {}", hint.representing_string());
+                }
+                let instr = try_decode_any_instruction(memory, pc).map_err(|e| e.to_string())?;
+                println!("  {}: {instr}", session.format_addr(pc));
+                pc += instr.size();
+            }
+        }
+        ReplCommand::Quit => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn format_cell(session: &Session, addr: usize) -> String {
+    let value = match session.memory.get(addr) {
+        Some(value) => value.to_string(),
+        None => "<uninitialized>".to_string(),
+    };
+    value + &session.format_builtin(ValueId(addr))
+}
+
+fn print_instruction(session: &Session, pc: usize) {
+    if let Some(location) = session.locate(pc) {
+        println!("  // {location}");
+    }
+    for hint in session.hints_at(pc) {
+        println!("  ; {}", hint.representing_string());
+    }
+    match try_decode_any_instruction(&session.memory, pc) {
+        Ok(instr) => println!("  {instr}"),
+        Err(e) => println!("  <{e}>"),
+    }
+}
+
+fn print_steps(session: &Session, steps: impl Iterator<Item = StepId>) {
+    let mut total = 0;
+    for step in steps {
+        if total < MAX_RESULTS {
+            println!("  {}", session.format_step(step));
+        }
+        total += 1;
+    }
+    print_total(total);
+}
+
+fn print_total(total: usize) {
+    if total > MAX_RESULTS {
+        println!("  ... and {} more.", total - MAX_RESULTS);
+    }
+    println!("Total: {total}.");
+}
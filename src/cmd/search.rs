@@ -0,0 +1,346 @@
+use super::{export::ExportPathsArgs, parse_felt252, LoadArgs, Session};
+use clap::{ArgGroup, Args};
+use sierra2casm_dbg::{
+    run_search_algorithm,
+    search::{
+        bidirectional::find_path_bidirectional,
+        diverse,
+        predicates::{Dataflow, NonIncreasing, PcInRange, ValueInRange, ValueInSet},
+        shortest::{find_k_shortest_paths, find_shortest_path, StepDistance, Unweighted},
+        DfsQueue, NodeId, Predicates,
+    },
+    Error,
+};
+use starknet_types_core::felt::Felt;
+
+#[derive(Debug, Args)]
+#[clap(group(ArgGroup::new("ranked").args(["shortest", "max_paths"])))]
+pub struct SearchArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    #[clap(short, long, value_parser = parse_felt252)]
+    source_value: Felt,
+    #[clap(short, long, value_parser = parse_felt252)]
+    target_value: Felt,
+
+    /// Only visit values greater or equal than this one.
+    #[clap(long, value_parser = parse_felt252)]
+    min_value: Option<Felt>,
+    /// Only visit values lower or equal than this one.
+    #[clap(long, value_parser = parse_felt252)]
+    max_value: Option<Felt>,
+    /// Only visit values which are one of these.
+    #[clap(long, value_parser = parse_felt252, num_args = 1..)]
+    value_in: Vec<Felt>,
+    /// Only visit values that do not increase along the path (ex. the gas counter).
+    #[clap(long)]
+    non_increasing: bool,
+
+    /// Only follow producer to consumer dataflow edges.
+    #[clap(long)]
+    dataflow: bool,
+
+    /// Only visit steps whose pc is greater or equal than this one.
+    #[clap(long)]
+    min_pc: Option<usize>,
+    /// Only visit steps whose pc is lower or equal than this one.
+    #[clap(long)]
+    max_pc: Option<usize>,
+
+    /// Only find the shortest path, visiting every node at most once.
+    #[clap(long)]
+    shortest: bool,
+    /// Only find the shortest path, searching from both the source and the target at once.
+    #[clap(long, conflicts_with_all = ["ranked", "diverse"])]
+    bidirectional: bool,
+    /// Find up to this many paths, from shortest to longest.
+    #[clap(long)]
+    max_paths: Option<usize>,
+    /// Find the paths which span the fewest steps instead of the ones with the fewest nodes.
+    #[clap(long, requires = "ranked")]
+    step_distance: bool,
+    /// Skip paths which share more than this fraction of their nodes with a previous one.
+    #[clap(long, value_parser = parse_fraction)]
+    diverse: Option<f64>,
+
+    #[clap(flatten)]
+    export: ExportPathsArgs,
+}
+
+fn parse_fraction(input: &str) -> Result<f64, String> {
+    match input.parse::<f64>() {
+        Ok(x) if (0.0..=1.0).contains(&x) => Ok(x),
+        Ok(_) => Err("must be between 0 and 1".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn run(args: SearchArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+    let Session {
+        memory,
+        trace,
+        mappings,
+        ..
+    } = &session;
+
+    //
+    // Find initial and final values.
+    //
+    println!("Finding initial and final values within the data.");
+    let source_value = mappings
+        .value2step()
+        .keys()
+        .copied()
+        .filter(|x| memory.get(x.0) == Some(args.source_value))
+        .min()
+        .expect("Source value not found within accessed memory.");
+    let target_value = mappings
+        .value2step()
+        .keys()
+        .copied()
+        .filter(|x| memory.get(x.0) == Some(args.target_value))
+        .max()
+        .expect("Target value not found within accessed memory.");
+    println!(
+        "  Source value found at {}.",
+        session.format_value(source_value)
+    );
+    println!(
+        "  Target value found at {}.",
+        session.format_value(target_value)
+    );
+
+    println!();
+
+    //
+    // Find a path between the source and target nodes.
+    //
+    // Queue containers:
+    //   - BfsQueue: Will find the shortest path using the BFS algorithm.
+    //   - DfsQueue: Will find the left-most path using the DFS algorithm.
+    //
+    let mut predicates = Predicates::new();
+    if args.min_value.is_some() || args.max_value.is_some() {
+        predicates = predicates.with_value(ValueInRange(
+            args.min_value.unwrap_or(Felt::ZERO)..=args.max_value.unwrap_or(Felt::MAX),
+        ));
+    }
+    if !args.value_in.is_empty() {
+        predicates = predicates.with_value(ValueInSet(args.value_in.into_iter().collect()));
+    }
+    if args.non_increasing {
+        predicates = predicates.with_value(NonIncreasing);
+    }
+    if args.dataflow {
+        predicates = predicates
+            .with_value(Dataflow(mappings))
+            .with_step(Dataflow(mappings));
+    }
+    if args.min_pc.is_some() || args.max_pc.is_some() {
+        predicates = predicates.with_step(PcInRange(
+            args.min_pc.unwrap_or(0)..=args.max_pc.unwrap_or(usize::MAX),
+        ));
+    }
+
+    if args.shortest {
+        println!("Starting shortest path search.");
+        let path = if args.step_distance {
+            find_shortest_path(
+                memory,
+                trace,
+                mappings,
+                source_value,
+                target_value,
+                predicates,
+                StepDistance,
+            )
+        } else {
+            find_shortest_path(
+                memory,
+                trace,
+                mappings,
+                source_value,
+                target_value,
+                predicates,
+                Unweighted,
+            )
+        };
+        println!();
+
+        match &path {
+            Some(path) => {
+                println!("Found solution spanning {} steps.", path.len() >> 1);
+                print_values(&session, path);
+            }
+            None => println!("No path found."),
+        }
+        args.export.write(&session, &Vec::from_iter(path));
+        return Ok(());
+    }
+
+    if args.bidirectional {
+        println!("Starting bidirectional search.");
+        let path = find_path_bidirectional(
+            memory,
+            trace,
+            mappings,
+            source_value,
+            target_value,
+            predicates,
+        );
+        println!();
+
+        match &path {
+            Some(path) => {
+                println!("Found solution spanning {} steps.", path.len() >> 1);
+                print_values(&session, path);
+            }
+            None => println!("No path found."),
+        }
+        args.export.write(&session, &Vec::from_iter(path));
+        return Ok(());
+    }
+
+    if let Some(max_paths) = args.max_paths {
+        println!("Starting k-shortest paths search.");
+        let paths: Box<dyn Iterator<Item = Vec<NodeId>>> = if args.step_distance {
+            Box::new(find_k_shortest_paths(
+                memory,
+                trace,
+                mappings,
+                source_value,
+                target_value,
+                predicates,
+                StepDistance,
+            ))
+        } else {
+            Box::new(find_k_shortest_paths(
+                memory,
+                trace,
+                mappings,
+                source_value,
+                target_value,
+                predicates,
+                Unweighted,
+            ))
+        };
+        println!();
+
+        let num_solutions = match args.diverse {
+            Some(max_overlap) => print_solutions(
+                &session,
+                diverse(paths, max_overlap).take(max_paths),
+                &args.export,
+            ),
+            None => print_solutions(&session, paths.take(max_paths), &args.export),
+        };
+        println!("Done! Found {num_solutions} solutions.");
+        return Ok(());
+    }
+
+    println!("Starting search algorithm.");
+    let mut iter = run_search_algorithm::<DfsQueue<_>>(
+        memory,
+        trace,
+        mappings,
+        source_value,
+        target_value,
+        predicates,
+    );
+    println!();
+    println!();
+
+    if let Some(max_overlap) = args.diverse {
+        let num_solutions = print_solutions(&session, diverse(iter, max_overlap), &args.export);
+        println!("Done! Found {num_solutions} solutions.");
+        return Ok(());
+    }
+
+    let mut num_solutions = 0;
+    let mut exported = Vec::new();
+    while let Some(path) = iter.next() {
+        num_solutions += 1;
+
+        println!("Found solution at step {}.", iter.queue().current_step());
+        print_values(&session, &path);
+        println!();
+        println!();
+
+        if args.export.is_enabled() {
+            exported.push(path);
+        }
+    }
+
+    println!("Done! Found {num_solutions} solutions.");
+    args.export.write(&session, &exported);
+
+    Ok(())
+}
+
+/// Print and export every path, then return how many there were.
+fn print_solutions(
+    session: &Session,
+    paths: impl Iterator<Item = Vec<NodeId>>,
+    export: &ExportPathsArgs,
+) -> usize {
+    let mut num_solutions = 0;
+    let mut exported = Vec::new();
+    for path in paths {
+        num_solutions += 1;
+
+        println!(
+            "Found solution {num_solutions} spanning {} steps.",
+            path.len() >> 1
+        );
+        print_values(session, &path);
+        println!();
+        println!();
+
+        if export.is_enabled() {
+            exported.push(path);
+        }
+    }
+
+    export.write(session, &exported);
+    num_solutions
+}
+
+/// Print the values of a path, along with how much they change.
+fn print_values(session: &Session, path: &[NodeId]) {
+    let mut prev_value: Option<Felt> = None;
+    for (index, id) in path.iter().enumerate() {
+        let NodeId::Value(id) = *id else {
+            continue;
+        };
+
+        // Annotate values with the step that reached them, or the one that follows the
+        // source value.
+        let step = match (index.checked_sub(1), path.get(index + 1)) {
+            (Some(prev), _) => Some(&path[prev]),
+            (None, next) => next,
+        };
+        let location = match step {
+            Some(NodeId::Step(step)) => session
+                .trace
+                .get(step.0)
+                .and_then(|entry| session.locate(entry.pc))
+                .map(|x| format!(" // {x}"))
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+
+        let curr_value = session.memory.get(id.0).unwrap();
+        match prev_value {
+            Some(prev_value) if curr_value != prev_value => println!(
+                "  [{}] = {curr_value} (Δ{}){location}",
+                session.format_value(id),
+                curr_value.to_bigint() - prev_value.to_bigint()
+            ),
+            None => println!("  [{}] = {curr_value}{location}", session.format_value(id)),
+            _ => {}
+        }
+        prev_value = Some(curr_value);
+    }
+}
@@ -0,0 +1,118 @@
+use super::{Address, LoadArgs, Session};
+use clap::Args;
+use sierra2casm_dbg::{
+    search::taint::{find_taint, Sink, Taint},
+    Error, ValueId,
+};
+
+/// Maximum number of tainted values printed.
+const MAX_VALUES: usize = 64;
+
+#[derive(Debug, Args)]
+pub struct TaintArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+
+    /// Memory cells to taint, as `<addr>` or `<segment>:<offset>`.
+    #[clap(required = true)]
+    addrs: Vec<Address>,
+    /// Print every tainted value instead of only the first ones.
+    #[clap(long)]
+    all: bool,
+}
+
+pub fn run(args: TaintArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+    let seeds = args
+        .addrs
+        .into_iter()
+        .map(|x| ValueId(session.resolve(x).unwrap_or_else(|e| panic!("{e}"))))
+        .collect::<Vec<_>>();
+
+    println!();
+    let taint = find_taint(
+        &session.memory,
+        &session.trace,
+        &session.mappings,
+        &session.hints,
+        &session.builtins,
+        session.program_offset,
+        &seeds,
+    );
+    print_taint(
+        &session,
+        &taint,
+        if args.all { usize::MAX } else { MAX_VALUES },
+    );
+
+    Ok(())
+}
+
+/// Print the values tainted by the seeds (up to `max_values` of them), then every sink they reach.
+pub fn print_taint(session: &Session, taint: &Taint, max_values: usize) {
+    println!("Tainted values:");
+    for (value, step) in taint.values.iter().take(max_values) {
+        let origin = match step {
+            Some(step) => format!(" <- {}", session.format_step(*step)),
+            None => " (seed)".to_string(),
+        };
+        println!(
+            "  [{}] = {}{origin}",
+            session.format_value(*value),
+            session.memory.get(value.0).unwrap_or_default()
+        );
+    }
+    if taint.values.len() > max_values {
+        println!("  ... and {} more.", taint.values.len() - max_values);
+    }
+
+    println!();
+    println!("Tainted sinks:");
+    for sink in &taint.sinks {
+        match sink {
+            Sink::Branch { step, condition } => println!(
+                "  branch on [{}]: {}",
+                session.format_value(*condition),
+                session.format_step(*step)
+            ),
+            Sink::Jump { step, target } => println!(
+                "  jump to [{}]: {}",
+                session.format_value(*target),
+                session.format_step(*step)
+            ),
+            Sink::Syscall {
+                step,
+                selector,
+                cells,
+            } => {
+                let selector = String::from_utf8_lossy(&selector.to_bytes_be())
+                    .trim_start_matches('\0')
+                    .to_string();
+                let cells = cells
+                    .iter()
+                    .map(|x| format!("[{}]", session.format_value(*x)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!(
+                    "  syscall {selector} with {cells}: {}",
+                    session.format_step(*step)
+                );
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "Found {} tainted values, {} tainted steps and {} sinks.",
+        taint.values.len(),
+        taint.steps.len(),
+        taint.sinks.len()
+    );
+    if !taint.undecodable.is_empty() {
+        println!(
+            "Warning: skipped {} steps whose instruction couldn't be decoded, starting with {}.",
+            taint.undecodable.len(),
+            session.format_step(taint.undecodable[0])
+        );
+    }
+}
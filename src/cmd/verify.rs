@@ -0,0 +1,37 @@
+use super::{LoadArgs, Session};
+use clap::Args;
+use sierra2casm_dbg::{verify::verify_trace, Error};
+use std::process;
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    #[clap(flatten)]
+    load: LoadArgs,
+}
+
+pub fn run(args: VerifyArgs) -> Result<(), Error> {
+    let session = Session::load(args.load)?;
+
+    println!();
+    println!("Replaying {} steps.", session.trace.len());
+    let Err(divergence) = verify_trace(&session.memory, &session.trace) else {
+        println!("The trace is consistent with the memory.");
+        return Ok(());
+    };
+
+    let step = divergence.step.0;
+    println!("Step {step} diverges: {}", divergence.kind);
+    println!("  {}", session.format_step(divergence.step));
+    for (name, entry) in [("this", step), ("next", step + 1)]
+        .into_iter()
+        .filter_map(|(name, step)| Some((name, session.trace.get(step)?)))
+    {
+        println!(
+            "  {name} step: pc = {}, ap = {}, fp = {}",
+            session.format_addr(entry.pc),
+            session.format_addr(entry.ap),
+            session.format_addr(entry.fp),
+        );
+    }
+    process::exit(1);
+}
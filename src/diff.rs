@@ -0,0 +1,303 @@
+//! Compare two executions of the same program, step by step.
+
+use crate::{search::NodeId, AccessKind, GraphMappings, Memory, StepId, Trace, ValueId};
+use starknet_types_core::felt::Felt;
+use std::collections::{HashMap, HashSet};
+
+/// The maximum number of removed and inserted steps the alignment looks for. Past it, the rest of
+/// both traces is reported as removed and inserted.
+const MAX_EDITS: usize = 2048;
+
+/// An execution to compare.
+#[derive(Clone, Copy, Debug)]
+pub struct Execution<'a> {
+    pub memory: &'a Memory,
+    pub trace: &'a Trace,
+    /// The relocated address of the program segment, so that executions with different layouts
+    /// can be aligned.
+    pub program_offset: usize,
+}
+
+/// A step of the alignment between two traces.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DiffOp {
+    /// Both steps execute the same pc.
+    Equal(StepId, StepId),
+    /// A step only in the left trace.
+    Removed(StepId),
+    /// A step only in the right trace.
+    Inserted(StepId),
+}
+
+/// A cell accessed by a pair of aligned steps which has different values in each execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CellDiff {
+    pub left_step: StepId,
+    pub right_step: StepId,
+    pub left: ValueId,
+    pub right: ValueId,
+    pub left_value: Option<Felt>,
+    pub right_value: Option<Felt>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TraceDiff {
+    pub ops: Vec<DiffOp>,
+    /// The first step which executes a different pc or accesses a different value.
+    pub first_divergence: Option<DiffOp>,
+    /// The cells with different values, in the order they're first accessed in the left trace.
+    pub cells: Vec<CellDiff>,
+    /// The cell of the right execution every cell of the left one was compared against.
+    counterparts: HashMap<ValueId, ValueId>,
+}
+
+impl TraceDiff {
+    /// Whether every step executes the same pc and accesses the same values.
+    pub fn is_empty(&self) -> bool {
+        self.first_divergence.is_none()
+    }
+
+    /// Return the cell of the right execution a cell of the left one was compared against.
+    pub fn counterpart(&self, left: ValueId) -> Option<ValueId> {
+        self.counterparts.get(&left).copied()
+    }
+
+    /// Find the dataflow path in the left execution which leads to the first differing cell.
+    ///
+    /// Memory is write-once, so a cell was computed by the first step which accesses it, if that
+    /// step produces it. Starting from the first differing cell, follow the step which computed
+    /// it back to one of its inputs which differs too, until reaching a cell which wasn't
+    /// computed by a step, such as an argument or a hint output. The path goes from that cell to
+    /// the first differing one.
+    pub fn divergence_path(&self, mappings: &GraphMappings) -> Option<Vec<NodeId>> {
+        // Uninitialized cells can't be on a path.
+        let cells = || self.cells.iter().filter(|x| x.left_value.is_some());
+        let differing = cells().map(|x| x.left).collect::<HashSet<_>>();
+
+        // Prefer a cell computed by the first divergent step over one of its inputs.
+        let first = cells().next()?;
+        let mut value = cells()
+            .take_while(|x| x.left_step == first.left_step)
+            .find(|x| {
+                mappings
+                    .access_kind(x.left_step, x.left)
+                    .is_some_and(AccessKind::is_producer)
+            })
+            .unwrap_or(first)
+            .left;
+        let mut path = vec![NodeId::Value(value)];
+        while let Some(step) = mappings.first_access(value) {
+            if mappings.is_hint_output(step, value)
+                || !mappings
+                    .access_kind(step, value)
+                    .is_some_and(AccessKind::is_producer)
+            {
+                break;
+            }
+
+            let Some(input) = mappings
+                .inputs(step)
+                .filter(|x| differing.contains(x))
+                .min()
+            else {
+                break;
+            };
+            path.extend([NodeId::Step(step), NodeId::Value(input)]);
+            value = input;
+        }
+
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Align two traces by the pc of their steps and compare the cells accessed by the aligned steps.
+///
+/// The cells are matched by the order in which the instructions access them, so values are
+/// compared as is: both executions must use the same memory layout for pointers to match. Hints
+/// are not compared, only the cells their results are read from.
+pub fn diff_executions(left: &Execution, right: &Execution) -> TraceDiff {
+    let pcs = |x: &Execution| {
+        x.trace
+            .iter()
+            .map(|entry| entry.pc.wrapping_sub(x.program_offset))
+            .collect::<Vec<_>>()
+    };
+    let ops = align(&pcs(left), &pcs(right));
+
+    let mut first_divergence = None;
+    let mut cells = Vec::new();
+    let mut counterparts = HashMap::new();
+    let (mut left_accesses, mut right_accesses) = (Vec::new(), Vec::new());
+    for op in &ops {
+        let DiffOp::Equal(left_step, right_step) = *op else {
+            first_divergence.get_or_insert(*op);
+            continue;
+        };
+
+        let left_entry = left.trace.get(left_step.0).unwrap();
+        let right_entry = right.trace.get(right_step.0).unwrap();
+        if left.memory.get(left_entry.pc) != right.memory.get(right_entry.pc) {
+            first_divergence.get_or_insert(*op);
+            continue;
+        }
+
+        left_accesses.clear();
+        right_accesses.clear();
+        let accessed = GraphMappings::iter_memory_references(left.memory, &left_entry, |x, _| {
+            left_accesses.push(ValueId(x))
+        })
+        .and_then(|_| {
+            GraphMappings::iter_memory_references(right.memory, &right_entry, |x, _| {
+                right_accesses.push(ValueId(x))
+            })
+        });
+        if accessed.is_err() || left_accesses.len() != right_accesses.len() {
+            first_divergence.get_or_insert(*op);
+            continue;
+        }
+
+        for (&left_value, &right_value) in left_accesses.iter().zip(&right_accesses) {
+            if counterparts.insert(left_value, right_value).is_some() {
+                continue;
+            }
+
+            let diff = CellDiff {
+                left_step,
+                right_step,
+                left: left_value,
+                right: right_value,
+                left_value: left.memory.get(left_value.0),
+                right_value: right.memory.get(right_value.0),
+            };
+            if diff.left_value != diff.right_value {
+                first_divergence.get_or_insert(*op);
+                cells.push(diff);
+            }
+        }
+    }
+
+    TraceDiff {
+        ops,
+        first_divergence,
+        cells,
+        counterparts,
+    }
+}
+
+/// Find the longest common subsequence of two sequences, as the operations which turn the left one
+/// into the right one.
+///
+/// The common prefix and suffix are skipped, and the rest is aligned with Myers' algorithm, which
+/// is fast when the sequences are similar.
+pub fn align<T: Eq>(left: &[T], right: &[T]) -> Vec<DiffOp> {
+    let prefix = left.iter().zip(right).take_while(|(a, b)| a == b).count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let left_middle = &left[prefix..left.len() - suffix];
+    let right_middle = &right[prefix..right.len() - suffix];
+
+    let mut ops = (0..prefix)
+        .map(|i| DiffOp::Equal(StepId(i), StepId(i)))
+        .collect::<Vec<_>>();
+    let offset = |x: StepId| StepId(x.0 + prefix);
+    match myers(left_middle, right_middle) {
+        Some(middle) => ops.extend(middle.into_iter().map(|op| match op {
+            DiffOp::Equal(a, b) => DiffOp::Equal(offset(a), offset(b)),
+            DiffOp::Removed(a) => DiffOp::Removed(offset(a)),
+            DiffOp::Inserted(b) => DiffOp::Inserted(offset(b)),
+        })),
+        None => {
+            ops.extend((0..left_middle.len()).map(|i| DiffOp::Removed(offset(StepId(i)))));
+            ops.extend((0..right_middle.len()).map(|i| DiffOp::Inserted(offset(StepId(i)))));
+        }
+    }
+    ops.extend((0..suffix).map(|i| {
+        DiffOp::Equal(
+            StepId(left.len() - suffix + i),
+            StepId(right.len() - suffix + i),
+        )
+    }));
+
+    ops
+}
+
+/// Align two sequences with Myers' algorithm, or return `None` if it takes more than
+/// [`MAX_EDITS`] removals and insertions.
+fn myers<T: Eq>(left: &[T], right: &[T]) -> Option<Vec<DiffOp>> {
+    let (n, m) = (left.len() as isize, right.len() as isize);
+    let max = (left.len() + right.len()).min(MAX_EDITS) as isize;
+
+    // The furthest `x` reached on every diagonal `k = x - y`, and its values within `-d..=d`
+    // after every number of edits `d`.
+    let offset = max + 1;
+    let mut furthest = vec![0; 2 * offset as usize + 1];
+    let mut history = Vec::new();
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let i = (offset + k) as usize;
+            let mut x = match k == -d || (k != d && furthest[i - 1] < furthest[i + 1]) {
+                true => furthest[i + 1],
+                false => furthest[i - 1] + 1,
+            };
+            let mut y = x - k;
+            while x < n && y < m && left[x as usize] == right[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[i] = x;
+
+            if x >= n && y >= m {
+                return Some(backtrack(&history, n, m));
+            }
+        }
+        history.push(furthest[(offset - d) as usize..=(offset + d) as usize].to_vec());
+    }
+
+    None
+}
+
+/// Walk back from the end of both sequences through the furthest points of every number of edits.
+fn backtrack(history: &[Vec<isize>], n: isize, m: isize) -> Vec<DiffOp> {
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+
+    for d in (1..=history.len() as isize).rev() {
+        let previous = &history[d as usize - 1];
+        let previous = |k: isize| previous[(k + d - 1) as usize];
+
+        let k = x - y;
+        let previous_k = match k == -d || (k != d && previous(k - 1) < previous(k + 1)) {
+            true => k + 1,
+            false => k - 1,
+        };
+        let previous_x = previous(previous_k);
+        let previous_y = previous_x - previous_k;
+
+        // The snake after the edit, then the edit itself.
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            ops.push(DiffOp::Equal(StepId(x as usize), StepId(y as usize)));
+        }
+        if previous_k == k + 1 {
+            y -= 1;
+            ops.push(DiffOp::Inserted(StepId(y as usize)));
+        } else {
+            x -= 1;
+            ops.push(DiffOp::Removed(StepId(x as usize)));
+        }
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        ops.push(DiffOp::Equal(StepId(x as usize), StepId(y as usize)));
+    }
+
+    ops.reverse();
+    ops
+}
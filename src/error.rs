@@ -0,0 +1,98 @@
+use bincode::error::DecodeError;
+use starknet_types_core::felt::Felt;
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to open or map a file.
+    Io(io::Error),
+    /// The data ends in the middle of a record which starts at `offset` bytes.
+    TruncatedRecord { offset: usize },
+    /// The underlying reader failed.
+    Decode(DecodeError),
+    /// A record in a segment-relative memory dump has an unknown value tag.
+    InvalidValueTag { offset: usize, tag: u64 },
+    /// An address refers to a segment that is not in the relocation table.
+    UnknownSegment { segment: usize },
+    /// An address refers to a temporary segment, which can't be relocated.
+    TemporarySegment { segment: isize },
+    /// The relocation table's entry for `segment` is not a valid address, or is lower than the
+    /// previous one.
+    InvalidRelocationTable { segment: usize },
+    /// The Sierra program could not be parsed.
+    InvalidSierraProgram(String),
+    /// The Sierra to CASM debug info could not be parsed or doesn't match the program.
+    InvalidSierraDebugInfo(String),
+    /// A memory cell appears more than once in the memory dump.
+    DuplicateCell { addr: usize },
+    /// The value at `pc` is not an instruction supported by the decoder.
+    UnknownInstructionEncoding { pc: usize, raw: Felt },
+    /// The instruction at `pc` requires an immediate value, but the next cell is not initialized.
+    MissingImmediate { pc: usize },
+    /// A memory cell that should have a value is not initialized.
+    UninitializedCell { addr: usize },
+    /// The trace has no steps.
+    EmptyTrace,
+    /// An unrelocated trace was given without a relocation table.
+    MissingRelocationTable,
+    /// The program JSON could not be parsed or lacks its hints.
+    InvalidProgram(String),
+    /// The code of a hint attached to the instruction at `offset` within the program could not be
+    /// parsed.
+    InvalidHint { offset: usize, error: String },
+    /// A builtin segment has a name which is not a known builtin.
+    UnknownBuiltin(String),
+    /// The AIR public input could not be parsed.
+    InvalidPublicInput(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "i/o error: {e}"),
+            Error::TruncatedRecord { offset } => {
+                write!(f, "truncated record at byte offset {offset}")
+            }
+            Error::Decode(e) => write!(f, "decode error: {e}"),
+            Error::InvalidValueTag { offset, tag } => {
+                write!(
+                    f,
+                    "invalid value tag {tag} in record at byte offset {offset}"
+                )
+            }
+            Error::UnknownSegment { segment } => {
+                write!(f, "segment {segment} is not in the relocation table")
+            }
+            Error::TemporarySegment { segment } => {
+                write!(f, "temporary segment {segment} can't be relocated")
+            }
+            Error::InvalidRelocationTable { segment } => {
+                write!(f, "invalid relocation table entry for segment {segment}")
+            }
+            Error::InvalidSierraProgram(e) => write!(f, "invalid sierra program: {e}"),
+            Error::InvalidSierraDebugInfo(e) => write!(f, "invalid sierra debug info: {e}"),
+            Error::DuplicateCell { addr } => write!(f, "duplicated memory cell at {addr}"),
+            Error::UnknownInstructionEncoding { pc, raw } => {
+                write!(f, "unknown instruction encoding {raw:#x} at pc {pc}")
+            }
+            Error::MissingImmediate { pc } => {
+                write!(f, "missing immediate value for the instruction at pc {pc}")
+            }
+            Error::UninitializedCell { addr } => {
+                write!(f, "uninitialized memory cell at {addr}")
+            }
+            Error::EmptyTrace => write!(f, "the trace is empty"),
+            Error::MissingRelocationTable => {
+                write!(f, "an unrelocated trace requires a relocation table")
+            }
+            Error::InvalidProgram(e) => write!(f, "invalid program: {e}"),
+            Error::InvalidHint { offset, error } => {
+                write!(f, "invalid hint at program offset {offset}: {error}")
+            }
+            Error::UnknownBuiltin(name) => write!(f, "unknown builtin {name}"),
+            Error::InvalidPublicInput(e) => write!(f, "invalid air public input: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,293 @@
+//! Export the value/step graph and the paths found within it as Graphviz DOT or JSON.
+
+use crate::{
+    search::NodeId, try_decode_any_instruction, AccessKind, GraphMappings, Memory, StepId, Trace,
+    ValueId,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet, VecDeque},
+    fmt::Write as _,
+    ops::Range,
+};
+
+/// A part of the graph: a set of nodes along with every edge between them.
+#[derive(Clone, Debug, Default)]
+pub struct Subgraph {
+    pub nodes: BTreeSet<NodeId>,
+}
+
+impl Subgraph {
+    /// Select the nodes at most `radius` edges away from `center`, regardless of the step order.
+    pub fn neighborhood(mappings: &GraphMappings, center: NodeId, radius: usize) -> Self {
+        let mut nodes = BTreeSet::from([center]);
+        let mut queue = VecDeque::from([(center, 0)]);
+        while let Some((node, distance)) = queue.pop_front() {
+            if distance == radius {
+                continue;
+            }
+
+            let neighbors: Box<dyn Iterator<Item = NodeId>> = match node {
+                NodeId::Step(id) => Box::new(
+                    mappings
+                        .step2value()
+                        .get(&id)
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .map(NodeId::Value),
+                ),
+                NodeId::Value(id) => Box::new(
+                    mappings
+                        .value2step()
+                        .get(&id)
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .map(NodeId::Step),
+                ),
+            };
+            for next in neighbors {
+                if nodes.insert(next) {
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        Self { nodes }
+    }
+
+    /// Select the steps within a window and every value they access.
+    pub fn step_window(mappings: &GraphMappings, steps: Range<usize>) -> Self {
+        let mut nodes = BTreeSet::new();
+        for step in steps.map(StepId) {
+            let Some(values) = mappings.step2value().get(&step) else {
+                continue;
+            };
+
+            nodes.insert(NodeId::Step(step));
+            nodes.extend(values.iter().copied().map(NodeId::Value));
+        }
+
+        Self { nodes }
+    }
+
+    /// Select the nodes of some paths.
+    pub fn from_paths<'a>(paths: impl IntoIterator<Item = &'a [NodeId]>) -> Self {
+        Self {
+            nodes: paths.into_iter().flatten().copied().collect(),
+        }
+    }
+
+    /// Iterate over the edges between the nodes of the subgraph, along with their access kind.
+    pub fn edges<'a>(
+        &'a self,
+        mappings: &'a GraphMappings,
+    ) -> impl Iterator<Item = (StepId, ValueId, AccessKind)> + 'a {
+        self.nodes
+            .iter()
+            .filter_map(|x| match x {
+                NodeId::Step(id) => Some(*id),
+                NodeId::Value(_) => None,
+            })
+            .flat_map(move |step| {
+                let mut values = mappings
+                    .step2value()
+                    .get(&step)
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(|x| self.nodes.contains(&NodeId::Value(*x)))
+                    .collect::<Vec<_>>();
+                values.sort();
+                values
+                    .into_iter()
+                    .map(move |value| (step, value, mappings.access_kind(step, value).unwrap()))
+            })
+    }
+}
+
+/// Render a subgraph as Graphviz DOT.
+///
+/// Steps are boxes labeled with their instruction and values are ellipses labeled with their
+/// contents. Edges point in the direction of the dataflow: from a value to the steps which read
+/// it and from a step to the values it writes. Asserted values use dashed edges. The edges of
+/// `paths` are highlighted.
+pub fn to_dot(
+    memory: &Memory,
+    trace: &Trace,
+    mappings: &GraphMappings,
+    graph: &Subgraph,
+    paths: &[Vec<NodeId>],
+) -> String {
+    let highlighted = paths
+        .iter()
+        .flat_map(|x| x.windows(2))
+        .map(|x| (x[0], x[1]))
+        .collect::<HashSet<_>>();
+
+    let mut dot = String::from("digraph {\n");
+    for node in &graph.nodes {
+        match node {
+            NodeId::Step(id) => {
+                let label = match trace.get(id.0) {
+                    Some(entry) => {
+                        let instr = try_decode_any_instruction(memory, entry.pc)
+                            .map_or_else(|e| format!("<{e}>"), |x| x.to_string());
+                        format!("step {} (pc = {})\n{instr}", id.0, entry.pc)
+                    }
+                    None => format!("step {}", id.0),
+                };
+                writeln!(dot, "  s{} [shape=box, label={}];", id.0, quote(&label)).unwrap();
+            }
+            NodeId::Value(id) => {
+                let label = match memory.get(id.0) {
+                    Some(value) => format!("[{}] = {value}", id.0),
+                    None => format!("[{}]", id.0),
+                };
+                writeln!(dot, "  v{} [shape=ellipse, label={}];", id.0, quote(&label)).unwrap();
+            }
+        }
+    }
+
+    for (step, value, kind) in graph.edges(mappings) {
+        let (step_node, value_node) = (NodeId::Step(step), NodeId::Value(value));
+        let mut attrs = Vec::new();
+        if kind == AccessKind::Assert {
+            attrs.push("style=dashed");
+        }
+        if highlighted.contains(&(step_node, value_node))
+            || highlighted.contains(&(value_node, step_node))
+        {
+            attrs.push("color=red, penwidth=2");
+        }
+        let attrs = match attrs.is_empty() {
+            true => String::new(),
+            false => format!(" [{}]", attrs.join(", ")),
+        };
+
+        match kind {
+            AccessKind::Read => writeln!(dot, "  v{} -> s{}{attrs};", value.0, step.0),
+            AccessKind::Write | AccessKind::Assert => {
+                writeln!(dot, "  s{} -> v{}{attrs};", step.0, value.0)
+            }
+        }
+        .unwrap();
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn quote(label: &str) -> String {
+    format!(
+        "\"{}\"",
+        label
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// A node as exported to JSON.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JsonNode {
+    /// The registers are `None` if the step is not in the trace.
+    Step {
+        id: usize,
+        pc: Option<usize>,
+        ap: Option<usize>,
+        fp: Option<usize>,
+        /// The disassembled instruction, or `None` if it can't be decoded.
+        instruction: Option<String>,
+    },
+    Value {
+        address: usize,
+        /// The contents of the cell in decimal, or `None` if it's not initialized.
+        value: Option<String>,
+    },
+}
+
+impl JsonNode {
+    pub fn new(memory: &Memory, trace: &Trace, node: NodeId) -> Self {
+        match node {
+            NodeId::Step(id) => {
+                let entry = trace.get(id.0);
+                JsonNode::Step {
+                    id: id.0,
+                    pc: entry.as_ref().map(|x| x.pc),
+                    ap: entry.as_ref().map(|x| x.ap),
+                    fp: entry.as_ref().map(|x| x.fp),
+                    instruction: entry
+                        .and_then(|x| try_decode_any_instruction(memory, x.pc).ok())
+                        .map(|x| x.to_string()),
+                }
+            }
+            NodeId::Value(id) => JsonNode::Value {
+                address: id.0,
+                value: memory.get(id.0).map(|x| x.to_string()),
+            },
+        }
+    }
+}
+
+/// An edge as exported to JSON, between the nodes at two indices within `nodes`.
+#[derive(Clone, Debug, Serialize)]
+pub struct JsonEdge {
+    pub step: usize,
+    pub value: usize,
+    pub access: AccessKind,
+}
+
+/// A subgraph as exported to JSON, along with the paths found within it.
+///
+/// Edges and paths refer to nodes by their index within `nodes`. Path nodes outside of the
+/// subgraph are skipped.
+#[derive(Clone, Debug, Serialize)]
+pub struct JsonGraph {
+    pub nodes: Vec<JsonNode>,
+    pub edges: Vec<JsonEdge>,
+    pub paths: Vec<Vec<usize>>,
+}
+
+impl JsonGraph {
+    pub fn new(
+        memory: &Memory,
+        trace: &Trace,
+        mappings: &GraphMappings,
+        graph: &Subgraph,
+        paths: &[Vec<NodeId>],
+    ) -> Self {
+        let indices = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (*node, index))
+            .collect::<BTreeMap<_, _>>();
+
+        Self {
+            nodes: graph
+                .nodes
+                .iter()
+                .map(|x| JsonNode::new(memory, trace, *x))
+                .collect(),
+            edges: graph
+                .edges(mappings)
+                .map(|(step, value, access)| JsonEdge {
+                    step: indices[&NodeId::Step(step)],
+                    value: indices[&NodeId::Value(value)],
+                    access,
+                })
+                .collect(),
+            paths: paths
+                .iter()
+                .map(|path| {
+                    path.iter()
+                        .filter_map(|x| indices.get(x).copied())
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
@@ -1,13 +1,34 @@
 pub use self::{
-    mappings::GraphMappings, memory::Memory, program::decode_instruction,
-    search::run_search_algorithm, trace::Trace,
+    builtins::{Builtin, BuiltinCell, BuiltinSegment, BuiltinSegments},
+    error::Error,
+    mappings::{AccessKind, GraphMappings},
+    memory::Memory,
+    program::{
+        decode_any_instruction, decode_instruction, disassemble, try_decode_any_instruction,
+        try_decode_instruction, AnyInstruction, GenericInstruction, ListingEntry,
+    },
+    relocation::{RelocationTable, SegmentAddr, SegmentKind},
+    search::run_search_algorithm,
+    sierra::{BuiltinSignature, SierraLocation, SierraMapping, SierraStatementInfo},
+    syscalls::{Syscall, SyscallLayout},
+    trace::Trace,
 };
 
+mod builtins;
+pub mod calls;
+pub mod diff;
+mod error;
+pub mod export;
 mod mappings;
 mod memory;
+pub mod profile;
 mod program;
+pub mod relocation;
 pub mod search;
+mod sierra;
+mod syscalls;
 mod trace;
+pub mod verify;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[repr(transparent)]
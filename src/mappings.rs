@@ -1,50 +1,148 @@
-use crate::{decode_instruction, Memory, StepId, Trace, ValueId};
+use crate::{
+    builtins::BuiltinSegments, try_decode_any_instruction, AnyInstruction, Error,
+    GenericInstruction, Memory, StepId, SyscallLayout, Trace, ValueId,
+};
 use cairo_lang_casm::{
     hints::{CoreHint, CoreHintBase, DeprecatedHint, ExternalHint, Hint, StarknetHint},
     instructions::InstructionBody,
-    operand::{CellRef, DerefOrImmediate, Register, ResOperand},
+    operand::{CellRef, DerefOrImmediate, Operation, Register, ResOperand},
+};
+use cairo_vm::{
+    types::instruction::{FpUpdate, Op1Addr, Opcode, PcUpdate, Register as VmRegister, Res},
+    vm::trace::trace_entry::RelocatedTraceEntry,
 };
-use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
+use serde::Serialize;
+use starknet_types_core::felt::Felt;
 use std::{
     collections::{HashMap, HashSet},
     ops::Index,
 };
 
+/// How a step accesses a memory cell.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessKind {
+    /// The step uses the value as an input.
+    Read,
+    /// The step (or one of its hints, or a builtin it triggers) produces the value.
+    Write,
+    /// The value is the destination of an `AssertEq`. Either the step deduced it, or it checked it
+    /// against an existing value.
+    Assert,
+}
+
+impl AccessKind {
+    /// Whether the step is (potentially) the origin of the value.
+    pub fn is_producer(self) -> bool {
+        matches!(self, Self::Write | Self::Assert)
+    }
+}
+
 #[derive(Debug)]
 pub struct GraphMappings {
     step2value: HashMap<StepId, HashSet<ValueId>>,
     value2step: HashMap<ValueId, HashSet<StepId>>,
+    access_kinds: HashMap<(StepId, ValueId), AccessKind>,
+    hint_accesses: HashMap<(StepId, ValueId), AccessKind>,
 }
 
 impl GraphMappings {
-    pub fn new(memory: &Memory, trace: &Trace, hints: &HashMap<usize, Vec<Hint>>) -> Self {
-        let mut step2value = HashMap::<StepId, HashSet<ValueId>>::new();
-        let mut value2step = HashMap::<ValueId, HashSet<StepId>>::new();
+    /// Build the mappings between steps and the memory cells they access.
+    ///
+    /// The `hints` are keyed by their offset within the program (as in the program JSON), while
+    /// the trace contains relocated program counters. The `program_offset` is the relocated address
+    /// of the program segment, which is `1` when using the default cairo-vm relocation.
+    pub fn new(
+        memory: &Memory,
+        trace: &Trace,
+        hints: &HashMap<usize, Vec<Hint>>,
+        program_offset: usize,
+    ) -> Self {
+        Self::try_new(memory, trace, hints, program_offset).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        for (step, trace) in trace.iter().enumerate() {
-            let mut add_mapping = |value| {
-                step2value
-                    .entry(StepId(step))
-                    .or_default()
-                    .insert(ValueId(value));
-                value2step
-                    .entry(ValueId(value))
-                    .or_default()
-                    .insert(StepId(step));
-            };
+    pub fn try_new(
+        memory: &Memory,
+        trace: &Trace,
+        hints: &HashMap<usize, Vec<Hint>>,
+        program_offset: usize,
+    ) -> Result<Self, Error> {
+        let mut mappings = Self {
+            step2value: HashMap::new(),
+            value2step: HashMap::new(),
+            access_kinds: HashMap::new(),
+            hint_accesses: HashMap::new(),
+        };
 
-            Self::iter_memory_references(memory, trace, &mut add_mapping);
-            if let Some(hints) = hints.get(&step) {
+        for (step, trace) in trace.iter().enumerate() {
+            let step = StepId(step);
+            Self::iter_memory_references(memory, &trace, |value, kind| {
+                mappings.add_access(step, ValueId(value), kind);
+            })?;
+            if let Some(hints) = trace
+                .pc
+                .checked_sub(program_offset)
+                .and_then(|pc| hints.get(&pc))
+            {
                 for hint in hints {
-                    Self::iter_hint_references(memory, trace, hint, &mut add_mapping);
+                    Self::iter_hint_references(memory, &trace, hint, |value, kind| {
+                        mappings
+                            .hint_accesses
+                            .entry((step, ValueId(value)))
+                            .and_modify(|x: &mut AccessKind| *x = (*x).max(kind))
+                            .or_insert(kind);
+                        mappings.add_access(step, ValueId(value), kind);
+                    });
                 }
             }
         }
 
-        Self {
-            step2value,
-            value2step,
+        Ok(mappings)
+    }
+
+    /// Model the builtins which deduce some of their cells, so that paths flow through them.
+    ///
+    /// The VM deduces the outputs of a builtin instance when they're first accessed, so the step
+    /// which does so is considered to read the instance's inputs and write its outputs.
+    pub fn with_builtins(mut self, memory: &Memory, builtins: &BuiltinSegments) -> Self {
+        for segment in builtins.iter() {
+            let outputs = segment.builtin.outputs();
+            if outputs.is_empty() {
+                continue;
+            }
+
+            let end = segment.addrs.end.min(memory.len());
+            for base in (segment.addrs.start..end).step_by(segment.builtin.cells_per_instance()) {
+                let Some(step) = outputs
+                    .clone()
+                    .filter_map(|x| self.first_access(ValueId(base + x)))
+                    .min()
+                else {
+                    continue;
+                };
+
+                for offset in 0..outputs.end {
+                    let kind = match outputs.contains(&offset) {
+                        true => AccessKind::Write,
+                        false => AccessKind::Read,
+                    };
+                    self.add_access(step, ValueId(base + offset), kind);
+                }
+            }
         }
+
+        self
+    }
+
+    fn add_access(&mut self, step: StepId, value: ValueId, kind: AccessKind) {
+        self.step2value.entry(step).or_default().insert(value);
+        self.value2step.entry(value).or_default().insert(step);
+
+        // When a step accesses the same cell more than once, producing it takes precedence.
+        self.access_kinds
+            .entry((step, value))
+            .and_modify(|x| *x = (*x).max(kind))
+            .or_insert(kind);
     }
 
     pub fn step2value(&self) -> &HashMap<StepId, HashSet<ValueId>> {
@@ -55,164 +153,172 @@ impl GraphMappings {
         &self.value2step
     }
 
-    fn iter_memory_references(
+    /// Return how a step accesses a value, or `None` if it doesn't.
+    pub fn access_kind(&self, step: StepId, value: ValueId) -> Option<AccessKind> {
+        self.access_kinds.get(&(step, value)).copied()
+    }
+
+    /// Return the first step which accesses a value. Memory is write-once, so that's the step
+    /// which created it unless it existed beforehand.
+    pub fn first_access(&self, value: ValueId) -> Option<StepId> {
+        self.value2step.get(&value)?.iter().min().copied()
+    }
+
+    /// Whether a value is written by one of the hints of a step.
+    pub fn is_hint_output(&self, step: StepId, value: ValueId) -> bool {
+        self.hint_accesses.get(&(step, value)) == Some(&AccessKind::Write)
+    }
+
+    /// Iterate over the values read by the hints of a step.
+    pub fn hint_inputs(&self, step: StepId) -> impl Iterator<Item = ValueId> + '_ {
+        self.step2value
+            .get(&step)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |x| self.hint_accesses.get(&(step, *x)) == Some(&AccessKind::Read))
+    }
+
+    /// Iterate over the values read by a step.
+    pub fn inputs(&self, step: StepId) -> impl Iterator<Item = ValueId> + '_ {
+        self.step2value
+            .get(&step)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |x| self.access_kinds[&(step, *x)] == AccessKind::Read)
+    }
+
+    /// Iterate over the values written or asserted by a step.
+    pub fn outputs(&self, step: StepId) -> impl Iterator<Item = ValueId> + '_ {
+        self.step2value
+            .get(&step)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |x| self.access_kinds[&(step, *x)].is_producer())
+    }
+
+    /// Iterate over the steps which write or assert a value.
+    pub fn producers(&self, value: ValueId) -> impl Iterator<Item = StepId> + '_ {
+        self.value2step
+            .get(&value)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |x| self.access_kinds[&(*x, value)].is_producer())
+    }
+
+    /// Iterate over the steps which read a value.
+    pub fn consumers(&self, value: ValueId) -> impl Iterator<Item = StepId> + '_ {
+        self.value2step
+            .get(&value)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |x| self.access_kinds[&(*x, value)] == AccessKind::Read)
+    }
+
+    pub(crate) fn iter_memory_references(
         memory: &Memory,
         trace: &RelocatedTraceEntry,
-        mut callback: impl FnMut(usize),
-    ) {
-        let instr = decode_instruction(memory, trace.pc);
-
-        let mut process_cell_ref = |x: CellRef| {
-            let offset = match x.register {
-                Register::AP => trace.ap.wrapping_add_signed(x.offset as isize),
-                Register::FP => trace.fp.wrapping_add_signed(x.offset as isize),
-            };
-            callback(offset);
-            offset
+        callback: impl FnMut(usize, AccessKind),
+    ) -> Result<(), Error> {
+        let mut refs = References {
+            memory,
+            trace,
+            callback,
+        };
+        let instr = match try_decode_any_instruction(memory, trace.pc)? {
+            AnyInstruction::Casm(instr) => instr,
+            AnyInstruction::Generic(instr) => {
+                refs.generic(&instr);
+                return Ok(());
+            }
         };
 
         match instr.body {
-            InstructionBody::AddAp(add_ap_instruction) => match add_ap_instruction.operand {
-                ResOperand::Deref(cell_ref) => todo!(),
-                ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                ResOperand::Immediate(_) => {}
-                ResOperand::BinOp(bin_op_operand) => todo!(),
-            },
+            InstructionBody::AddAp(add_ap_instruction) => {
+                refs.res_operand(&add_ap_instruction.operand);
+            }
             InstructionBody::AssertEq(assert_eq_instruction) => {
-                process_cell_ref(assert_eq_instruction.a);
-                match assert_eq_instruction.b {
-                    ResOperand::Deref(cell_ref) => {
-                        process_cell_ref(cell_ref);
-                    }
-                    ResOperand::DoubleDeref(cell_ref, _) => {
-                        let offset = process_cell_ref(cell_ref);
-                        callback(memory[offset].unwrap().try_into().unwrap());
-                    }
-                    ResOperand::Immediate(_) => {}
-                    ResOperand::BinOp(bin_op_operand) => {
-                        process_cell_ref(bin_op_operand.a);
-                        match bin_op_operand.b {
-                            DerefOrImmediate::Deref(cell_ref) => {
-                                process_cell_ref(cell_ref);
-                            }
-                            DerefOrImmediate::Immediate(_) => {}
-                        }
+                refs.cell_ref(assert_eq_instruction.a, AccessKind::Assert);
+                refs.res_operand(&assert_eq_instruction.b);
+            }
+            InstructionBody::Call(call_instruction) => {
+                match call_instruction.target {
+                    DerefOrImmediate::Deref(cell_ref) => {
+                        refs.cell_ref(cell_ref, AccessKind::Read);
                     }
+                    DerefOrImmediate::Immediate(_) => {}
                 }
+
+                // The caller's frame pointer and the return address are pushed at `[ap]` and
+                // `[ap + 1]`.
+                refs.cell(trace.ap, AccessKind::Write);
+                refs.cell(trace.ap + 1, AccessKind::Write);
             }
-            InstructionBody::Call(call_instruction) => match call_instruction.target {
-                DerefOrImmediate::Deref(cell_ref) => todo!(),
-                DerefOrImmediate::Immediate(_) => {}
-            },
             InstructionBody::Jnz(jnz_instruction) => {
-                process_cell_ref(jnz_instruction.condition);
+                refs.cell_ref(jnz_instruction.condition, AccessKind::Read);
                 match jnz_instruction.jump_offset {
-                    DerefOrImmediate::Deref(cell_ref) => todo!(),
+                    DerefOrImmediate::Deref(cell_ref) => {
+                        refs.cell_ref(cell_ref, AccessKind::Read);
+                    }
                     DerefOrImmediate::Immediate(_) => {}
                 }
             }
             InstructionBody::Jump(jump_instruction) => match jump_instruction.target {
                 DerefOrImmediate::Deref(cell_ref) => {
-                    process_cell_ref(cell_ref);
+                    refs.cell_ref(cell_ref, AccessKind::Read);
                 }
                 DerefOrImmediate::Immediate(_) => {}
             },
-            InstructionBody::Ret(_) => {}
+            InstructionBody::Ret(_) => {
+                // The caller's frame pointer and the return address are restored from `[fp - 2]`
+                // and `[fp - 1]`.
+                refs.cell(trace.fp.wrapping_sub(2), AccessKind::Read);
+                refs.cell(trace.fp.wrapping_sub(1), AccessKind::Read);
+            }
         }
+
+        Ok(())
     }
 
     fn iter_hint_references(
         memory: &Memory,
         trace: &RelocatedTraceEntry,
         hint: &Hint,
-        mut callback: impl FnMut(usize),
+        callback: impl FnMut(usize, AccessKind),
     ) {
-        let mut process_cell_ref = |x: CellRef| {
-            let offset = match x.register {
-                Register::AP => trace.ap.wrapping_add_signed(x.offset as isize),
-                Register::FP => trace.fp.wrapping_add_signed(x.offset as isize),
-            };
-            callback(offset);
-            offset
+        let mut refs = References {
+            memory,
+            trace,
+            callback,
         };
 
         match hint {
             Hint::Core(core_hint_base) => match core_hint_base {
                 CoreHintBase::Core(core_hint) => match core_hint {
                     CoreHint::AllocSegment { dst } => {
-                        process_cell_ref(*dst);
+                        refs.cell_ref(*dst, AccessKind::Write);
                     }
-                    CoreHint::TestLessThan { lhs, rhs, dst } => {
-                        match lhs {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => {
-                                process_cell_ref(bin_op_operand.a);
-                                match bin_op_operand.b {
-                                    DerefOrImmediate::Deref(cell_ref) => {
-                                        process_cell_ref(cell_ref);
-                                    }
-                                    DerefOrImmediate::Immediate(_) => {}
-                                }
-                            }
-                        }
-                        match rhs {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        process_cell_ref(*dst);
-                    }
-                    CoreHint::TestLessThanOrEqual { lhs, rhs, dst } => {
-                        match lhs {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        match rhs {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        process_cell_ref(*dst);
+                    CoreHint::TestLessThan { lhs, rhs, dst }
+                    | CoreHint::TestLessThanOrEqual { lhs, rhs, dst }
+                    | CoreHint::TestLessThanOrEqualAddress { lhs, rhs, dst } => {
+                        refs.res_operand(lhs);
+                        refs.res_operand(rhs);
+                        refs.cell_ref(*dst, AccessKind::Write);
                     }
-                    CoreHint::TestLessThanOrEqualAddress { lhs, rhs, dst } => todo!(),
                     CoreHint::WideMul128 {
                         lhs,
                         rhs,
                         high,
                         low,
                     } => {
-                        match lhs {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        match rhs {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        process_cell_ref(*high);
-                        process_cell_ref(*low);
+                        refs.res_operand(lhs);
+                        refs.res_operand(rhs);
+                        refs.cell_ref(*high, AccessKind::Write);
+                        refs.cell_ref(*low, AccessKind::Write);
                     }
                     CoreHint::DivMod {
                         lhs,
@@ -220,24 +326,10 @@ impl GraphMappings {
                         quotient,
                         remainder,
                     } => {
-                        match lhs {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        match rhs {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        process_cell_ref(*quotient);
-                        process_cell_ref(*remainder);
+                        refs.res_operand(lhs);
+                        refs.res_operand(rhs);
+                        refs.cell_ref(*quotient, AccessKind::Write);
+                        refs.cell_ref(*remainder, AccessKind::Write);
                     }
                     CoreHint::Uint256DivMod {
                         dividend0,
@@ -249,42 +341,12 @@ impl GraphMappings {
                         remainder0,
                         remainder1,
                     } => {
-                        match dividend0 {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        match dividend1 {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
+                        for x in [dividend0, dividend1, divisor0, divisor1] {
+                            refs.res_operand(x);
                         }
-                        match divisor0 {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
-                        }
-                        match divisor1 {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
-                            }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
+                        for x in [quotient0, quotient1, remainder0, remainder1] {
+                            refs.cell_ref(*x, AccessKind::Write);
                         }
-                        process_cell_ref(*quotient0);
-                        process_cell_ref(*quotient1);
-                        process_cell_ref(*remainder0);
-                        process_cell_ref(*remainder1);
                     }
                     CoreHint::Uint512DivModByUint256 {
                         dividend0,
@@ -299,8 +361,22 @@ impl GraphMappings {
                         quotient3,
                         remainder0,
                         remainder1,
-                    } => todo!(),
-                    CoreHint::SquareRoot { value, dst } => todo!(),
+                    } => {
+                        for x in [
+                            dividend0, dividend1, dividend2, dividend3, divisor0, divisor1,
+                        ] {
+                            refs.res_operand(x);
+                        }
+                        for x in [
+                            quotient0, quotient1, quotient2, quotient3, remainder0, remainder1,
+                        ] {
+                            refs.cell_ref(*x, AccessKind::Write);
+                        }
+                    }
+                    CoreHint::SquareRoot { value, dst } => {
+                        refs.res_operand(value);
+                        refs.cell_ref(*dst, AccessKind::Write);
+                    }
                     CoreHint::Uint256SquareRoot {
                         value_low,
                         value_high,
@@ -309,7 +385,19 @@ impl GraphMappings {
                         remainder_low,
                         remainder_high,
                         sqrt_mul_2_minus_remainder_ge_u128,
-                    } => todo!(),
+                    } => {
+                        refs.res_operand(value_low);
+                        refs.res_operand(value_high);
+                        for x in [
+                            sqrt0,
+                            sqrt1,
+                            remainder_low,
+                            remainder_high,
+                            sqrt_mul_2_minus_remainder_ge_u128,
+                        ] {
+                            refs.cell_ref(*x, AccessKind::Write);
+                        }
+                    }
                     CoreHint::LinearSplit {
                         value,
                         scalar,
@@ -317,63 +405,130 @@ impl GraphMappings {
                         x,
                         y,
                     } => {
-                        match value {
-                            ResOperand::Deref(cell_ref) => {
-                                process_cell_ref(*cell_ref);
+                        refs.res_operand(value);
+                        refs.res_operand(scalar);
+                        refs.res_operand(max_x);
+                        refs.cell_ref(*x, AccessKind::Write);
+                        refs.cell_ref(*y, AccessKind::Write);
+                    }
+                    CoreHint::AllocFelt252Dict { segment_arena_ptr } => {
+                        // The arena keeps the number of allocated dictionaries at `ptr - 2` and the
+                        // pointer to the infos segment at `ptr - 3`. The new dictionary's segment
+                        // start is written into its `SegmentInfo`.
+                        if let Some(ptr) = refs.res_operand_address(segment_arena_ptr) {
+                            let index = refs.cell(ptr.wrapping_sub(2), AccessKind::Read);
+                            let infos = refs.cell_address(ptr.wrapping_sub(3), AccessKind::Read);
+                            if let (Some(index), Some(infos)) = (index.and_then(to_usize), infos) {
+                                refs.cell(infos + 3 * index, AccessKind::Write);
                             }
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
                         }
-                        match scalar {
-                            ResOperand::Deref(cell_ref) => todo!(),
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
+                    }
+                    CoreHint::Felt252DictEntryInit { dict_ptr, key } => {
+                        // The previous value of the entry is written at `dict_ptr + 1`.
+                        let dict_ptr = refs.res_operand_address(dict_ptr);
+                        refs.res_operand(key);
+                        if let Some(dict_ptr) = dict_ptr {
+                            refs.cell(dict_ptr + 1, AccessKind::Write);
                         }
-                        match max_x {
-                            ResOperand::Deref(cell_ref) => todo!(),
-                            ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                            ResOperand::Immediate(_) => {}
-                            ResOperand::BinOp(bin_op_operand) => todo!(),
+                    }
+                    CoreHint::Felt252DictEntryUpdate { dict_ptr, value } => {
+                        // The updated key is read from `dict_ptr - 3`.
+                        let dict_ptr = refs.res_operand_address(dict_ptr);
+                        refs.res_operand(value);
+                        if let Some(dict_ptr) = dict_ptr {
+                            refs.cell(dict_ptr.wrapping_sub(3), AccessKind::Read);
                         }
-                        process_cell_ref(*x);
-                        process_cell_ref(*y);
                     }
-                    CoreHint::AllocFelt252Dict { segment_arena_ptr } => todo!(),
-                    CoreHint::Felt252DictEntryInit { dict_ptr, key } => todo!(),
-                    CoreHint::Felt252DictEntryUpdate { dict_ptr, value } => todo!(),
                     CoreHint::GetSegmentArenaIndex {
                         dict_end_ptr,
                         dict_index,
-                    } => todo!(),
+                    } => {
+                        refs.res_operand(dict_end_ptr);
+                        refs.cell_ref(*dict_index, AccessKind::Write);
+                    }
                     CoreHint::InitSquashData {
                         dict_accesses,
                         ptr_diff,
                         n_accesses,
                         big_keys,
                         first_key,
-                    } => todo!(),
-                    CoreHint::GetCurrentAccessIndex { range_check_ptr } => todo!(),
-                    CoreHint::ShouldSkipSquashLoop { should_skip_loop } => todo!(),
-                    CoreHint::GetCurrentAccessDelta { index_delta_minus1 } => todo!(),
-                    CoreHint::ShouldContinueSquashLoop { should_continue } => todo!(),
-                    CoreHint::GetNextDictKey { next_key } => todo!(),
+                    } => {
+                        // The keys of every `DictAccess` (3 cells each) are read.
+                        let dict_accesses = refs.res_operand_address(dict_accesses);
+                        refs.res_operand(ptr_diff);
+                        let n_accesses = refs.res_operand(n_accesses).and_then(to_usize);
+                        if let (Some(dict_accesses), Some(n_accesses)) = (dict_accesses, n_accesses)
+                        {
+                            for i in 0..n_accesses {
+                                refs.cell(dict_accesses + 3 * i, AccessKind::Read);
+                            }
+                        }
+                        refs.cell_ref(*big_keys, AccessKind::Write);
+                        refs.cell_ref(*first_key, AccessKind::Write);
+                    }
+                    CoreHint::GetCurrentAccessIndex { range_check_ptr } => {
+                        if let Some(range_check_ptr) = refs.res_operand_address(range_check_ptr) {
+                            refs.cell(range_check_ptr, AccessKind::Write);
+                        }
+                    }
+                    CoreHint::ShouldSkipSquashLoop { should_skip_loop } => {
+                        refs.cell_ref(*should_skip_loop, AccessKind::Write);
+                    }
+                    CoreHint::GetCurrentAccessDelta { index_delta_minus1 } => {
+                        refs.cell_ref(*index_delta_minus1, AccessKind::Write);
+                    }
+                    CoreHint::ShouldContinueSquashLoop { should_continue } => {
+                        refs.cell_ref(*should_continue, AccessKind::Write);
+                    }
+                    CoreHint::GetNextDictKey { next_key } => {
+                        refs.cell_ref(*next_key, AccessKind::Write);
+                    }
                     CoreHint::AssertLeFindSmallArcs {
                         range_check_ptr,
                         a,
                         b,
-                    } => todo!(),
+                    } => {
+                        // The two smallest arcs are split into 4 range checked limbs.
+                        let range_check_ptr = refs.res_operand_address(range_check_ptr);
+                        refs.res_operand(a);
+                        refs.res_operand(b);
+                        if let Some(range_check_ptr) = range_check_ptr {
+                            for i in 0..4 {
+                                refs.cell(range_check_ptr + i, AccessKind::Write);
+                            }
+                        }
+                    }
                     CoreHint::AssertLeIsFirstArcExcluded {
                         skip_exclude_a_flag,
-                    } => todo!(),
+                    } => {
+                        refs.cell_ref(*skip_exclude_a_flag, AccessKind::Write);
+                    }
                     CoreHint::AssertLeIsSecondArcExcluded {
                         skip_exclude_b_minus_a,
-                    } => todo!(),
-                    CoreHint::RandomEcPoint { x, y } => todo!(),
-                    CoreHint::FieldSqrt { val, sqrt } => todo!(),
-                    CoreHint::DebugPrint { start, end } => todo!(),
-                    CoreHint::AllocConstantSize { size, dst } => todo!(),
+                    } => {
+                        refs.cell_ref(*skip_exclude_b_minus_a, AccessKind::Write);
+                    }
+                    CoreHint::RandomEcPoint { x, y } => {
+                        refs.cell_ref(*x, AccessKind::Write);
+                        refs.cell_ref(*y, AccessKind::Write);
+                    }
+                    CoreHint::FieldSqrt { val, sqrt } => {
+                        refs.res_operand(val);
+                        refs.cell_ref(*sqrt, AccessKind::Write);
+                    }
+                    CoreHint::DebugPrint { start, end } => {
+                        let start = refs.res_operand_address(start);
+                        let end = refs.res_operand_address(end);
+                        if let (Some(start), Some(end)) = (start, end) {
+                            for offset in start..end {
+                                refs.cell(offset, AccessKind::Read);
+                            }
+                        }
+                    }
+                    CoreHint::AllocConstantSize { size, dst } => {
+                        refs.res_operand(size);
+                        refs.cell_ref(*dst, AccessKind::Write);
+                    }
                     CoreHint::U256InvModN {
                         b0,
                         b1,
@@ -385,65 +540,293 @@ impl GraphMappings {
                         s_or_r1,
                         t_or_k0,
                         t_or_k1,
-                    } => todo!(),
+                    } => {
+                        for x in [b0, b1, n0, n1] {
+                            refs.res_operand(x);
+                        }
+                        for x in [g0_or_no_inv, g1_option, s_or_r0, s_or_r1, t_or_k0, t_or_k1] {
+                            refs.cell_ref(*x, AccessKind::Write);
+                        }
+                    }
                     CoreHint::EvalCircuit {
                         n_add_mods,
                         add_mod_builtin,
                         n_mul_mods,
                         mul_mod_builtin,
-                    } => todo!(),
+                    } => {
+                        let n_add_mods = refs.res_operand(n_add_mods).and_then(to_usize);
+                        let add_mod_builtin = refs.res_operand_address(add_mod_builtin);
+                        let n_mul_mods = refs.res_operand(n_mul_mods).and_then(to_usize);
+                        let mul_mod_builtin = refs.res_operand_address(mul_mod_builtin);
+
+                        if let (Some(n), Some(ptr)) = (n_add_mods, add_mod_builtin) {
+                            refs.mod_builtin(ptr, n);
+                        }
+                        if let (Some(n), Some(ptr)) = (n_mul_mods, mul_mod_builtin) {
+                            refs.mod_builtin(ptr, n);
+                        }
+                    }
                 },
                 CoreHintBase::Deprecated(deprecated_hint) => match deprecated_hint {
-                    DeprecatedHint::AssertCurrentAccessIndicesIsEmpty => todo!(),
+                    DeprecatedHint::AssertCurrentAccessIndicesIsEmpty
+                    | DeprecatedHint::AssertAllKeysUsed
+                    | DeprecatedHint::AssertLeAssertThirdArcExcluded => {}
                     DeprecatedHint::AssertAllAccessesUsed { n_used_accesses } => {
-                        todo!()
+                        refs.cell_ref(*n_used_accesses, AccessKind::Read);
+                    }
+                    DeprecatedHint::AssertLtAssertValidInput { a, b } => {
+                        refs.res_operand(a);
+                        refs.res_operand(b);
                     }
-                    DeprecatedHint::AssertAllKeysUsed => todo!(),
-                    DeprecatedHint::AssertLeAssertThirdArcExcluded => todo!(),
-                    DeprecatedHint::AssertLtAssertValidInput { a, b } => todo!(),
                     DeprecatedHint::Felt252DictRead {
                         dict_ptr,
                         key,
                         value_dst,
-                    } => todo!(),
+                    } => {
+                        refs.res_operand(dict_ptr);
+                        refs.res_operand(key);
+                        refs.cell_ref(*value_dst, AccessKind::Write);
+                    }
                     DeprecatedHint::Felt252DictWrite {
                         dict_ptr,
                         key,
                         value,
-                    } => todo!(),
+                    } => {
+                        refs.res_operand(dict_ptr);
+                        refs.res_operand(key);
+                        refs.res_operand(value);
+                    }
                 },
             },
             Hint::Starknet(starknet_hint) => match starknet_hint {
-                StarknetHint::SystemCall { system } => match system {
-                    ResOperand::Deref(cell_ref) => {
-                        process_cell_ref(*cell_ref);
-                    }
-                    ResOperand::DoubleDeref(cell_ref, _) => todo!(),
-                    ResOperand::Immediate(_) => {}
-                    ResOperand::BinOp(bin_op_operand) => {
-                        process_cell_ref(bin_op_operand.a);
-                        match bin_op_operand.b {
-                            DerefOrImmediate::Deref(cell_ref) => todo!(),
-                            DerefOrImmediate::Immediate(_) => {}
+                StarknetHint::SystemCall { system } => {
+                    // The handler reads the request and writes the response right after it.
+                    let layout = refs
+                        .res_operand_address(system)
+                        .and_then(|x| SyscallLayout::new(memory, x));
+                    if let Some(layout) = layout {
+                        for offset in layout.request {
+                            refs.cell(offset, AccessKind::Read);
+                        }
+                        for offset in layout.response {
+                            refs.cell(offset, AccessKind::Write);
                         }
                     }
-                },
+                }
                 StarknetHint::Cheatcode {
-                    selector,
+                    selector: _,
                     input_start,
                     input_end,
                     output_start,
                     output_end,
-                } => todo!(),
+                } => {
+                    let input_start = refs.res_operand_address(input_start);
+                    let input_end = refs.res_operand_address(input_end);
+                    if let (Some(start), Some(end)) = (input_start, input_end) {
+                        for offset in start..end {
+                            refs.cell(offset, AccessKind::Read);
+                        }
+                    }
+
+                    let output_start = refs.cell_ref_address(*output_start, AccessKind::Write);
+                    let output_end = refs.cell_ref_address(*output_end, AccessKind::Write);
+                    if let (Some(start), Some(end)) = (output_start, output_end) {
+                        for offset in start..end {
+                            refs.cell(offset, AccessKind::Write);
+                        }
+                    }
+                }
             },
             Hint::External(external_hint) => match external_hint {
-                ExternalHint::AddRelocationRule { src, dst } => todo!(),
-                ExternalHint::WriteRunParam { index, dst } => todo!(),
+                ExternalHint::AddRelocationRule { src, dst } => {
+                    refs.res_operand(src);
+                    refs.res_operand(dst);
+                }
+                ExternalHint::WriteRunParam { index, dst } => {
+                    refs.res_operand(index);
+                    refs.cell_ref(*dst, AccessKind::Write);
+                }
             },
         }
     }
 }
 
+/// Helper to report the memory cells accessed while evaluating operands.
+struct References<'a, F> {
+    memory: &'a Memory,
+    trace: &'a RelocatedTraceEntry,
+    callback: F,
+}
+
+impl<F> References<'_, F>
+where
+    F: FnMut(usize, AccessKind),
+{
+    /// Report an access to a cell and return its value.
+    fn cell(&mut self, offset: usize, kind: AccessKind) -> Option<Felt> {
+        (self.callback)(offset, kind);
+        self.memory.get(offset)
+    }
+
+    /// Report an access to a cell and return its value as an address.
+    fn cell_address(&mut self, offset: usize, kind: AccessKind) -> Option<usize> {
+        self.cell(offset, kind).and_then(to_usize)
+    }
+
+    /// Report an access to a cell reference and return its value.
+    fn cell_ref(&mut self, x: CellRef, kind: AccessKind) -> Option<Felt> {
+        self.cell(cell_ref_address(self.trace, x), kind)
+    }
+
+    /// Report an access to a cell reference and return its value as an address.
+    fn cell_ref_address(&mut self, x: CellRef, kind: AccessKind) -> Option<usize> {
+        self.cell_ref(x, kind).and_then(to_usize)
+    }
+
+    /// Report the cells read by an operand and return its value, if known.
+    fn res_operand(&mut self, x: &ResOperand) -> Option<Felt> {
+        match x {
+            ResOperand::Deref(cell_ref) => self.cell_ref(*cell_ref, AccessKind::Read),
+            ResOperand::DoubleDeref(cell_ref, offset) => {
+                let base = self.cell_ref_address(*cell_ref, AccessKind::Read)?;
+                self.cell(base.wrapping_add_signed(*offset as isize), AccessKind::Read)
+            }
+            ResOperand::Immediate(value) => Some(Felt::from(&value.value)),
+            ResOperand::BinOp(bin_op_operand) => {
+                let lhs = self.cell_ref(bin_op_operand.a, AccessKind::Read);
+                let rhs = match &bin_op_operand.b {
+                    DerefOrImmediate::Deref(cell_ref) => self.cell_ref(*cell_ref, AccessKind::Read),
+                    DerefOrImmediate::Immediate(value) => Some(Felt::from(&value.value)),
+                };
+
+                let (lhs, rhs) = (lhs?, rhs?);
+                Some(match bin_op_operand.op {
+                    Operation::Add => lhs + rhs,
+                    Operation::Mul => lhs * rhs,
+                })
+            }
+        }
+    }
+
+    /// Report the cells read by an operand and return its value as an address.
+    fn res_operand_address(&mut self, x: &ResOperand) -> Option<usize> {
+        self.res_operand(x).and_then(to_usize)
+    }
+
+    /// Report the cells accessed by an instruction in its generic representation, following the
+    /// VM's semantics: only the operands the instruction uses are reported.
+    fn generic(&mut self, instr: &GenericInstruction) {
+        let repr = &instr.repr;
+        let register = |x| match x {
+            VmRegister::AP => self.trace.ap,
+            VmRegister::FP => self.trace.fp,
+        };
+        let dst = register(repr.dst_register).wrapping_add_signed(repr.off0);
+        let op0 = register(repr.op0_register).wrapping_add_signed(repr.off1);
+
+        // A call writes the caller's frame pointer at dst and the return address at op0.
+        let op0_value = match (repr.opcode, repr.res, repr.op1_addr) {
+            (Opcode::Call, _, _) => self.cell(op0, AccessKind::Write),
+            (_, Res::Add | Res::Mul, _) | (_, _, Op1Addr::Op0) => self.cell(op0, AccessKind::Read),
+            _ => None,
+        };
+        match repr.op1_addr {
+            Op1Addr::Imm => {}
+            Op1Addr::AP => {
+                self.cell(
+                    self.trace.ap.wrapping_add_signed(repr.off2),
+                    AccessKind::Read,
+                );
+            }
+            Op1Addr::FP => {
+                self.cell(
+                    self.trace.fp.wrapping_add_signed(repr.off2),
+                    AccessKind::Read,
+                );
+            }
+            Op1Addr::Op0 => {
+                if let Some(base) = op0_value.and_then(to_usize) {
+                    self.cell(base.wrapping_add_signed(repr.off2), AccessKind::Read);
+                }
+            }
+        }
+
+        let dst_kind = match repr.opcode {
+            Opcode::AssertEq => Some(AccessKind::Assert),
+            Opcode::Call => Some(AccessKind::Write),
+            Opcode::Ret => Some(AccessKind::Read),
+            Opcode::NOp => (repr.pc_update == PcUpdate::Jnz || repr.fp_update == FpUpdate::Dst)
+                .then_some(AccessKind::Read),
+        };
+        if let Some(kind) = dst_kind {
+            self.cell(dst, kind);
+        }
+    }
+
+    /// Report the cells accessed when filling `n` instances of an `AddMod` or `MulMod` builtin.
+    ///
+    /// Every instance has 7 cells: `p0`, `p1`, `p2`, `p3`, `values_ptr`, `offsets_ptr` and `n`. Only
+    /// the first instance is written by the program, the others are filled from it. The offsets
+    /// table contains the `(a, b, c)` offsets of every operation within the values table, where
+    /// each value is made of 4 limbs. The inputs `a` and `b` are read while the result `c` is
+    /// written.
+    fn mod_builtin(&mut self, ptr: usize, n: usize) {
+        for offset in ptr..ptr + 7 * n {
+            let kind = if offset < ptr + 7 {
+                AccessKind::Read
+            } else {
+                AccessKind::Write
+            };
+            self.cell(offset, kind);
+        }
+
+        let values_ptr = self.memory.get(ptr + 4).and_then(to_usize);
+        let offsets_ptr = self.memory.get(ptr + 5).and_then(to_usize);
+        if let (Some(values_ptr), Some(offsets_ptr)) = (values_ptr, offsets_ptr) {
+            for i in 0..n {
+                for (j, kind) in [AccessKind::Read, AccessKind::Read, AccessKind::Write]
+                    .into_iter()
+                    .enumerate()
+                {
+                    if let Some(offset) =
+                        self.cell_address(offsets_ptr + 3 * i + j, AccessKind::Read)
+                    {
+                        for limb in 0..4 {
+                            self.cell(values_ptr + offset + limb, kind);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Evaluate an operand at a step, if all the cells it reads are known.
+pub(crate) fn eval_res_operand(
+    memory: &Memory,
+    trace: &RelocatedTraceEntry,
+    x: &ResOperand,
+) -> Option<Felt> {
+    References {
+        memory,
+        trace,
+        callback: |_, _| {},
+    }
+    .res_operand(x)
+}
+
+/// Return the address of a cell reference at a step.
+pub(crate) fn cell_ref_address(trace: &RelocatedTraceEntry, x: CellRef) -> usize {
+    match x.register {
+        Register::AP => trace.ap.wrapping_add_signed(x.offset as isize),
+        Register::FP => trace.fp.wrapping_add_signed(x.offset as isize),
+    }
+}
+
+fn to_usize(value: Felt) -> Option<usize> {
+    value.try_into().ok()
+}
+
 impl Index<StepId> for GraphMappings {
     type Output = HashSet<ValueId>;
 
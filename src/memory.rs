@@ -1,45 +1,279 @@
+use crate::{Error, RelocationTable};
 use bincode::{de::read::Reader, error::DecodeError};
+use memmap2::Mmap;
 use starknet_types_core::felt::Felt;
-use std::ops::Deref;
+use std::{fs::File, path::Path};
+
+/// Size of a memory record: an 8-byte address followed by a 32-byte value.
+const RECORD_SIZE: usize = 40;
+/// Size of a segment-relative memory record: an 8-byte segment index, an 8-byte offset, an 8-byte
+/// tag and a 32-byte value.
+const SEGMENT_RECORD_SIZE: usize = 56;
+
+/// Tag of a segment-relative value which is a felt.
+const TAG_FELT: u64 = 0;
+/// Tag of a segment-relative value which is a pointer. The first 16 bytes of the value contain its
+/// segment index and offset.
+const TAG_RELOCATABLE: u64 = 1;
+
+/// The program's memory.
+///
+/// Cells are stored as runs of consecutive addresses, so holes don't take any space. The values
+/// are either decoded into memory or read lazily from a memory-mapped dump.
+#[derive(Debug)]
+pub struct Memory {
+    runs: Vec<Run>,
+    values: Values,
+}
+
+/// A range of consecutive initialized cells.
+#[derive(Clone, Copy, Debug)]
+struct Run {
+    /// Address of the first cell.
+    addr: usize,
+    /// Number of cells.
+    len: usize,
+    /// Index of the first cell's value within the values.
+    index: usize,
+}
 
 #[derive(Debug)]
-pub struct Memory(Vec<Option<Felt>>);
+enum Values {
+    Owned(Vec<Felt>),
+    Mapped(Mmap),
+}
 
 impl Memory {
-    pub fn decode(mut data: impl Reader) -> Self {
-        let mut memory = Vec::new();
+    pub fn decode(data: impl Reader) -> Self {
+        Self::try_decode(data).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_decode(mut data: impl Reader) -> Result<Self, Error> {
+        let mut runs = RunsBuilder::default();
+        let mut values = Vec::new();
 
         let mut addr_data = [0u8; 8];
         let mut value_data = [0u8; 32];
-        loop {
+        for offset in (0..).step_by(RECORD_SIZE) {
             match data.read(&mut addr_data) {
                 Ok(_) => {}
                 Err(DecodeError::UnexpectedEnd { additional: 8 }) => break,
-                e @ Err(_) => e.unwrap(),
+                Err(DecodeError::UnexpectedEnd { .. }) => {
+                    return Err(Error::TruncatedRecord { offset })
+                }
+                Err(e) => return Err(Error::Decode(e)),
+            }
+            match data.read(&mut value_data) {
+                Ok(_) => {}
+                Err(DecodeError::UnexpectedEnd { .. }) => {
+                    return Err(Error::TruncatedRecord { offset })
+                }
+                Err(e) => return Err(Error::Decode(e)),
             }
-            data.read(&mut value_data).unwrap();
 
-            let addr = u64::from_le_bytes(addr_data);
-            let value = Felt::from_bytes_le(&value_data);
+            runs.push(u64::from_le_bytes(addr_data) as usize);
+            values.push(Felt::from_bytes_le(&value_data));
+        }
+
+        Ok(Self {
+            runs: runs.finish()?,
+            values: Values::Owned(values),
+        })
+    }
+
+    pub fn decode_segments(
+        data: impl Reader,
+        table: Option<RelocationTable>,
+    ) -> (Self, RelocationTable) {
+        Self::try_decode_segments(data, table).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Decode a segment-relative memory dump, where both the addresses and the pointers stored in
+    /// memory are `(segment, offset)` pairs.
+    ///
+    /// When no relocation table is provided, it's computed from the segment sizes in the dump the
+    /// same way cairo-vm does.
+    pub fn try_decode_segments(
+        mut data: impl Reader,
+        table: Option<RelocationTable>,
+    ) -> Result<(Self, RelocationTable), Error> {
+        enum Value {
+            Felt(Felt),
+            Relocatable(usize, usize),
+        }
+
+        let mut records = Vec::new();
 
-            if addr >= memory.len() as u64 {
-                memory.resize(addr as usize + 1, None);
+        let mut buf = [0u8; 8];
+        let mut value_data = [0u8; 32];
+        for offset in (0..).step_by(SEGMENT_RECORD_SIZE) {
+            match data.read(&mut buf) {
+                Ok(_) => {}
+                Err(DecodeError::UnexpectedEnd { additional: 8 }) => break,
+                Err(DecodeError::UnexpectedEnd { .. }) => {
+                    return Err(Error::TruncatedRecord { offset })
+                }
+                Err(e) => return Err(Error::Decode(e)),
             }
+            let segment = to_segment(i64::from_le_bytes(buf))?;
 
-            match &mut memory[addr as usize] {
-                Some(_) => panic!("duplicated memory cell"),
-                x @ None => *x = Some(value),
+            let mut read = |buf: &mut [u8]| match data.read(buf) {
+                Ok(_) => Ok(()),
+                Err(DecodeError::UnexpectedEnd { .. }) => Err(Error::TruncatedRecord { offset }),
+                Err(e) => Err(Error::Decode(e)),
+            };
+            read(&mut buf)?;
+            let cell_offset = u64::from_le_bytes(buf) as usize;
+            read(&mut buf)?;
+            let tag = u64::from_le_bytes(buf);
+            read(&mut value_data)?;
+
+            let value = match tag {
+                TAG_FELT => Value::Felt(Felt::from_bytes_le(&value_data)),
+                TAG_RELOCATABLE => Value::Relocatable(
+                    to_segment(i64::from_le_bytes(value_data[..8].try_into().unwrap()))?,
+                    u64::from_le_bytes(value_data[8..16].try_into().unwrap()) as usize,
+                ),
+                _ => return Err(Error::InvalidValueTag { offset, tag }),
+            };
+
+            records.push((segment, cell_offset, value));
+        }
+
+        let table = table.unwrap_or_else(|| {
+            let mut sizes = Vec::new();
+            for (segment, offset, _) in &records {
+                if *segment >= sizes.len() {
+                    sizes.resize(segment + 1, 0);
+                }
+                sizes[*segment] = sizes[*segment].max(offset + 1);
             }
+            RelocationTable::from_segment_sizes(sizes)
+        });
+        let relocate = |segment, offset| {
+            table
+                .relocate(segment, offset)
+                .ok_or(Error::UnknownSegment { segment })
+        };
+
+        let mut runs = RunsBuilder::default();
+        let mut values = Vec::with_capacity(records.len());
+        for (segment, offset, value) in records {
+            runs.push(relocate(segment, offset)?);
+            values.push(match value {
+                Value::Felt(value) => value,
+                Value::Relocatable(segment, offset) => Felt::from(relocate(segment, offset)?),
+            });
         }
 
-        Self(memory)
+        let memory = Self {
+            runs: runs.finish()?,
+            values: Values::Owned(values),
+        };
+        Ok((memory, table))
+    }
+
+    /// Memory-map a memory dump. Only the addresses are read upfront to build the index, while the
+    /// values are decoded on demand.
+    pub fn map_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = File::open(path).map_err(Error::Io)?;
+        // SAFETY: The file is not expected to be modified while it's being analyzed.
+        let mmap = unsafe { Mmap::map(&file) }.map_err(Error::Io)?;
+
+        if mmap.len() % RECORD_SIZE != 0 {
+            return Err(Error::TruncatedRecord {
+                offset: mmap.len() - mmap.len() % RECORD_SIZE,
+            });
+        }
+
+        let mut runs = RunsBuilder::default();
+        for record in mmap.chunks_exact(RECORD_SIZE) {
+            runs.push(u64::from_le_bytes(record[..8].try_into().unwrap()) as usize);
+        }
+
+        Ok(Self {
+            runs: runs.finish()?,
+            values: Values::Mapped(mmap),
+        })
+    }
+
+    /// Return the value of a cell, or `None` if it's not initialized.
+    pub fn get(&self, addr: usize) -> Option<Felt> {
+        let run = self.runs[..self.runs.partition_point(|x| x.addr <= addr)].last()?;
+        (addr - run.addr < run.len).then(|| self.value(run.index + (addr - run.addr)))
+    }
+
+    /// Return the value of a cell, or an error if it's not initialized.
+    pub fn try_get(&self, addr: usize) -> Result<Felt, Error> {
+        self.get(addr).ok_or(Error::UninitializedCell { addr })
+    }
+
+    /// Return one past the highest initialized address.
+    pub fn len(&self) -> usize {
+        self.runs.last().map_or(0, |x| x.addr + x.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Iterate over the initialized cells in address order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Felt)> + '_ {
+        self.runs.iter().flat_map(move |run| {
+            (0..run.len).map(move |i| (run.addr + i, self.value(run.index + i)))
+        })
+    }
+
+    fn value(&self, index: usize) -> Felt {
+        match &self.values {
+            Values::Owned(values) => values[index],
+            Values::Mapped(mmap) => {
+                let offset = index * RECORD_SIZE;
+                Felt::from_bytes_le(mmap[offset + 8..offset + RECORD_SIZE].try_into().unwrap())
+            }
+        }
     }
 }
 
-impl Deref for Memory {
-    type Target = [Option<Felt>];
+/// Build the runs from the addresses of the records, in the order they appear.
+#[derive(Default)]
+struct RunsBuilder {
+    runs: Vec<Run>,
+    count: usize,
+}
+
+impl RunsBuilder {
+    fn push(&mut self, addr: usize) {
+        match self.runs.last_mut() {
+            Some(run) if run.addr + run.len == addr => run.len += 1,
+            _ => self.runs.push(Run {
+                addr,
+                len: 1,
+                index: self.count,
+            }),
+        }
+        self.count += 1;
+    }
+
+    fn finish(mut self) -> Result<Vec<Run>, Error> {
+        // Dumps are usually sorted already, which makes this a no-op.
+        self.runs.sort_by_key(|x| x.addr);
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+        for window in self.runs.windows(2) {
+            if window[0].addr + window[0].len > window[1].addr {
+                return Err(Error::DuplicateCell {
+                    addr: window[1].addr,
+                });
+            }
+        }
+
+        Ok(self.runs)
     }
 }
+
+/// Convert a segment index, rejecting temporary segments.
+pub(crate) fn to_segment(segment: i64) -> Result<usize, Error> {
+    usize::try_from(segment).map_err(|_| Error::TemporarySegment {
+        segment: segment as isize,
+    })
+}
@@ -0,0 +1,384 @@
+//! Aggregate the cost of an execution per function and per pc, and export it as folded stacks or
+//! pprof profiles.
+
+use crate::{
+    calls::{CallTree, Frame, FrameId},
+    Memory, SierraMapping, StepId, Trace, ValueId,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Write as _,
+};
+
+/// The resources used by a part of the execution.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cost {
+    pub steps: usize,
+    /// Gas withdrawn from the gas counter, net of the redeposits.
+    pub gas: i128,
+    /// Instances used of every builtin, by the name of its Sierra type.
+    pub builtins: BTreeMap<String, usize>,
+}
+
+impl Cost {
+    fn add(&mut self, other: &Cost) {
+        self.steps += other.steps;
+        self.gas += other.gas;
+        for (name, count) in &other.builtins {
+            *self.builtins.entry(name.clone()).or_default() += count;
+        }
+    }
+}
+
+/// The cost of a part of the execution, excluding and including the functions it calls.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Costs {
+    pub exclusive: Cost,
+    pub inclusive: Cost,
+    /// The number of frames of a function, or the number of times a pc was executed.
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    /// The costs of every frame, indexed by their [`FrameId`].
+    pub frames: Vec<Costs>,
+    /// The costs of every function, by the pc of its first instruction.
+    pub functions: BTreeMap<usize, Costs>,
+    /// The costs of every pc. The inclusive cost of a `call` includes the callee's.
+    pub pcs: BTreeMap<usize, Costs>,
+}
+
+impl Profile {
+    /// Aggregate the cost of every frame of a call tree.
+    ///
+    /// Gas and builtins are only measured at function boundaries, from the leading builtin
+    /// arguments and return values of Sierra functions, so they require a Sierra mapping. The
+    /// exclusive cost of a pc only includes its steps. The inclusive costs of recursive functions
+    /// and calls only count the outermost frame.
+    pub fn new(
+        memory: &Memory,
+        trace: &Trace,
+        tree: &CallTree,
+        sierra: Option<&SierraMapping>,
+        program_offset: usize,
+    ) -> Self {
+        let mut frames = vec![Costs::default(); tree.frames.len()];
+        let mut pcs = BTreeMap::<usize, Costs>::new();
+        for (step, entry) in trace.iter().enumerate() {
+            let Some(frame) = tree.step_frame(StepId(step)) else {
+                continue;
+            };
+            frames[frame.0].exclusive.steps += 1;
+
+            let costs = pcs.entry(entry.pc).or_default();
+            costs.exclusive.steps += 1;
+            costs.count += 1;
+        }
+
+        // Children are always entered after their parent, so they're visited first in reverse.
+        for (index, frame) in tree.frames.iter().enumerate().rev() {
+            let mut children = Cost::default();
+            for child in &frame.children {
+                children.add(&frames[child.0].inclusive);
+            }
+
+            let costs = &mut frames[index];
+            costs.count = 1;
+            costs.inclusive.steps = costs.exclusive.steps + children.steps;
+            // Builtins which the frame doesn't take are only used by its children.
+            costs.inclusive.gas = children.gas;
+            costs.inclusive.builtins = children.builtins.clone();
+            if let Some(sierra) = sierra {
+                measure(
+                    memory,
+                    trace,
+                    sierra,
+                    program_offset,
+                    frame,
+                    &mut costs.inclusive,
+                );
+            }
+            costs.exclusive.gas = costs.inclusive.gas - children.gas;
+            for (name, count) in &costs.inclusive.builtins {
+                let exclusive =
+                    count.saturating_sub(children.builtins.get(name).copied().unwrap_or_default());
+                if exclusive != 0 {
+                    costs.exclusive.builtins.insert(name.clone(), exclusive);
+                }
+            }
+        }
+
+        // Walk the tree in order, keeping track of the functions and calls on the stack.
+        let mut functions = BTreeMap::<usize, Costs>::new();
+        let mut active = HashMap::<(usize, bool), usize>::new();
+        let mut stack = Vec::<FrameId>::new();
+        let keys = |frame: &Frame| {
+            [
+                Some((frame.entry_pc, true)),
+                frame
+                    .call_step
+                    .and_then(|x| trace.get(x.0))
+                    .map(|x| (x.pc, false)),
+            ]
+        };
+        for (index, frame) in tree.frames.iter().enumerate() {
+            while stack.last().copied() != frame.parent {
+                let Some(id) = stack.pop() else {
+                    break;
+                };
+                for key in keys(tree.frame(id)).into_iter().flatten() {
+                    *active.get_mut(&key).unwrap() -= 1;
+                }
+            }
+            stack.push(FrameId(index));
+
+            let costs = &frames[index];
+            let function = functions.entry(frame.entry_pc).or_default();
+            function.exclusive.add(&costs.exclusive);
+            function.count += 1;
+
+            for (key, is_function) in keys(frame).into_iter().flatten() {
+                let depth = active.entry((key, is_function)).or_default();
+                if *depth == 0 {
+                    let target = match is_function {
+                        true => &mut *function,
+                        false => pcs.entry(key).or_default(),
+                    };
+                    target.inclusive.add(&costs.inclusive);
+                }
+                *depth += 1;
+            }
+        }
+        for costs in pcs.values_mut() {
+            costs.inclusive.steps += costs.exclusive.steps;
+        }
+
+        Self {
+            frames,
+            functions,
+            pcs,
+        }
+    }
+
+    /// Render the exclusive steps of every call stack in the folded format used by flamegraph
+    /// tools: one `root;...;leaf <steps>` line per stack.
+    pub fn folded_stacks(&self, tree: &CallTree, name: impl Fn(usize) -> String) -> String {
+        let mut names = HashMap::new();
+        let mut stacks = BTreeMap::<String, usize>::new();
+        for (index, costs) in self.frames.iter().enumerate() {
+            if costs.exclusive.steps == 0 {
+                continue;
+            }
+
+            let mut stack = tree
+                .backtrace(FrameId(index))
+                .map(|x| {
+                    let pc = tree.frame(x).entry_pc;
+                    names.entry(pc).or_insert_with(|| name(pc)).clone()
+                })
+                .collect::<Vec<_>>();
+            stack.reverse();
+            *stacks.entry(stack.join(";")).or_default() += costs.exclusive.steps;
+        }
+
+        let mut folded = String::new();
+        for (stack, steps) in stacks {
+            writeln!(folded, "{stack} {steps}").unwrap();
+        }
+        folded
+    }
+
+    /// Encode the exclusive cost of every call stack as an uncompressed pprof profile.
+    ///
+    /// Every function is a location whose address is its entry pc. The samples have the steps,
+    /// the gas and then every builtin used as values.
+    pub fn to_pprof(&self, tree: &CallTree, name: impl Fn(usize) -> String) -> Vec<u8> {
+        let builtins = self
+            .frames
+            .iter()
+            .flat_map(|x| x.exclusive.builtins.keys())
+            .collect::<BTreeSet<_>>();
+
+        let mut strings = StringTable::default();
+        let mut profile = Proto::default();
+        let mut sample_types = vec![("steps", "count"), ("gas", "count")];
+        sample_types.extend(builtins.iter().map(|x| (x.as_str(), "instances")));
+        for (kind, unit) in sample_types {
+            let mut value_type = Proto::default();
+            value_type.uint(1, strings.index(kind));
+            value_type.uint(2, strings.index(unit));
+            profile.message(1, value_type);
+        }
+
+        // Locations and functions share their ids, starting from 1.
+        let mut locations = BTreeMap::<usize, u64>::new();
+        for frame in &tree.frames {
+            let next_id = locations.len() as u64 + 1;
+            locations.entry(frame.entry_pc).or_insert(next_id);
+        }
+
+        for (index, costs) in self.frames.iter().enumerate() {
+            let cost = &costs.exclusive;
+            if cost.steps == 0 && cost.gas == 0 && cost.builtins.is_empty() {
+                continue;
+            }
+
+            let mut values = vec![cost.steps as u64, cost.gas as u64];
+            values.extend(
+                builtins
+                    .iter()
+                    .map(|x| cost.builtins.get(*x).copied().unwrap_or_default() as u64),
+            );
+
+            let mut sample = Proto::default();
+            sample.packed(
+                1,
+                tree.backtrace(FrameId(index))
+                    .map(|x| locations[&tree.frame(x).entry_pc]),
+            );
+            sample.packed(2, values);
+            profile.message(2, sample);
+        }
+
+        for (pc, id) in &locations {
+            let mut line = Proto::default();
+            line.uint(1, *id);
+
+            let mut location = Proto::default();
+            location.uint(1, *id);
+            location.uint(3, *pc as u64);
+            location.message(4, line);
+            profile.message(4, location);
+        }
+        for (pc, id) in &locations {
+            let name = strings.index(&name(*pc));
+
+            let mut function = Proto::default();
+            function.uint(1, *id);
+            function.uint(2, name);
+            function.uint(3, name);
+            profile.message(5, function);
+        }
+
+        for string in &strings.strings {
+            profile.bytes(6, string.as_bytes());
+        }
+        profile.0
+    }
+}
+
+/// Measure the gas and builtins used by a frame from its builtin arguments and return values, and
+/// overwrite them in `cost`.
+///
+/// The arguments are located from the function's Sierra signature, since the frame may not access
+/// all of them. They end right below the saved `fp` and return `pc`.
+///
+/// The return values of a frame are only known when they're used after it returns, which isn't
+/// the case for the outermost frame. Then they're the cells right below `ap` at the `ret`, as many
+/// as the function's Sierra signature returns.
+fn measure(
+    memory: &Memory,
+    trace: &Trace,
+    sierra: &SierraMapping,
+    program_offset: usize,
+    frame: &Frame,
+    cost: &mut Cost,
+) -> Option<()> {
+    let exit_step = frame.exit_step?;
+    let offset = frame.entry_pc.checked_sub(program_offset)?;
+    let signature = sierra.builtin_signature(offset)?;
+    let arguments = frame.fp.checked_sub(2 + sierra.param_size(offset)?)?;
+    let return_values = match frame.return_values.is_empty() {
+        true => {
+            let ap = trace.get(exit_step.0)?.ap;
+            (ap.checked_sub(sierra.return_size(offset)?)?..ap)
+                .map(ValueId)
+                .collect()
+        }
+        false => frame.return_values.clone(),
+    };
+
+    for (index, name) in signature.params.iter().enumerate() {
+        let Some(ret_index) = signature.returns.iter().position(|x| x == name) else {
+            continue;
+        };
+        let arg = memory.get(arguments + index)?;
+        let ret = memory.get(return_values.get(ret_index)?.0)?;
+
+        match *name {
+            "GasBuiltin" => cost.gas = i128::try_from(arg - ret).ok()?,
+            _ => match usize::try_from(ret - arg).ok()? {
+                0 => {
+                    cost.builtins.remove(*name);
+                }
+                count => {
+                    cost.builtins.insert(name.to_string(), count);
+                }
+            },
+        }
+    }
+
+    Some(())
+}
+
+#[derive(Default)]
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, u64>,
+}
+
+impl StringTable {
+    fn index(&mut self, value: &str) -> u64 {
+        // The first string of a pprof profile must be empty.
+        if self.strings.is_empty() {
+            self.strings.push(String::new());
+            self.indices.insert(String::new(), 0);
+        }
+        if let Some(index) = self.indices.get(value) {
+            return *index;
+        }
+
+        let index = self.strings.len() as u64;
+        self.strings.push(value.to_string());
+        self.indices.insert(value.to_string(), index);
+        index
+    }
+}
+
+/// A protobuf message encoder, with only what the pprof format needs.
+#[derive(Default)]
+struct Proto(Vec<u8>);
+
+impl Proto {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn uint(&mut self, field: u64, value: u64) {
+        self.varint(field << 3);
+        self.varint(value);
+    }
+
+    fn bytes(&mut self, field: u64, value: &[u8]) {
+        self.varint((field << 3) | 2);
+        self.varint(value.len() as u64);
+        self.0.extend_from_slice(value);
+    }
+
+    fn message(&mut self, field: u64, value: Proto) {
+        self.bytes(field, &value.0);
+    }
+
+    fn packed(&mut self, field: u64, values: impl IntoIterator<Item = u64>) {
+        let mut data = Proto::default();
+        for value in values {
+            data.varint(value);
+        }
+        self.bytes(field, &data.0);
+    }
+}
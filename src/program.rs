@@ -1,4 +1,4 @@
-use crate::Memory;
+use crate::{Error, Memory};
 use cairo_lang_casm::{
     instructions::{
         AddApInstruction, AssertEqInstruction, CallInstruction, Instruction, InstructionBody,
@@ -15,13 +15,26 @@ use cairo_vm::{
     vm::decoding::decoder,
 };
 use starknet_types_core::felt::Felt;
+use std::{fmt, ops::Range};
 
-/// Source: https://github.com/starkware-libs/cairo/blob/main/crates/cairo-lang-casm/src/assembler.rs
 pub fn decode_instruction(memory: &Memory, offset: usize) -> Instruction {
-    let instr_repr =
-        decoder::decode_instruction(memory[offset].unwrap().try_into().unwrap()).unwrap();
+    try_decode_instruction(memory, offset).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Source: https://github.com/starkware-libs/cairo/blob/main/crates/cairo-lang-casm/src/assembler.rs
+pub fn try_decode_instruction(memory: &Memory, offset: usize) -> Result<Instruction, Error> {
+    let raw = memory.try_get(offset)?;
+    let unknown_encoding = || Error::UnknownInstructionEncoding { pc: offset, raw };
+    let imm = memory.get(offset + 1);
+    let read_imm = |off2| match (off2, imm) {
+        (1, Some(imm)) => Ok(DerefOrImmediate::Immediate(signed_immediate(imm))),
+        (1, None) => Err(Error::MissingImmediate { pc: offset }),
+        _ => Err(unknown_encoding()),
+    };
+
+    let instr_repr = decode_repr(raw).ok_or_else(unknown_encoding)?;
 
-    match instr_repr {
+    Ok(match instr_repr {
         InstructionRepr {
             off0: -1,
             off1,
@@ -39,11 +52,12 @@ pub fn decode_instruction(memory: &Memory, offset: usize) -> Instruction {
                 operand: decode_res_operand(ResDescription {
                     off1,
                     off2,
-                    imm: memory.get(offset + 1).copied().flatten(),
+                    imm,
                     op0_register,
                     op1_addr,
                     res,
-                }),
+                })
+                .map_err(|e| e.into_error(offset, raw))?,
             }),
             inc_ap: false,
             hints: Vec::new(),
@@ -72,11 +86,12 @@ pub fn decode_instruction(memory: &Memory, offset: usize) -> Instruction {
                 b: decode_res_operand(ResDescription {
                     off1,
                     off2,
-                    imm: memory.get(offset + 1).copied().flatten(),
+                    imm,
                     op0_register,
                     op1_addr,
                     res,
-                }),
+                })
+                .map_err(|e| e.into_error(offset, raw))?,
             }),
             inc_ap: match ap_update {
                 ApUpdate::Regular => false,
@@ -100,12 +115,7 @@ pub fn decode_instruction(memory: &Memory, offset: usize) -> Instruction {
         } => Instruction {
             body: InstructionBody::Call(CallInstruction {
                 target: match op1_addr {
-                    Op1Addr::Imm => {
-                        assert_eq!(off2, 1);
-                        DerefOrImmediate::Immediate(BigIntAsHex {
-                            value: memory[offset + 1].unwrap().to_bigint(),
-                        })
-                    }
+                    Op1Addr::Imm => read_imm(off2)?,
                     Op1Addr::AP => DerefOrImmediate::Deref(CellRef {
                         register: cairo_lang_casm::operand::Register::AP,
                         offset: off2 as i16,
@@ -140,12 +150,7 @@ pub fn decode_instruction(memory: &Memory, offset: usize) -> Instruction {
         } => Instruction {
             body: InstructionBody::Jump(JumpInstruction {
                 target: match op1_addr {
-                    Op1Addr::Imm => {
-                        assert_eq!(off2, 1);
-                        DerefOrImmediate::Immediate(BigIntAsHex {
-                            value: memory[offset + 1].unwrap().to_bigint(),
-                        })
-                    }
+                    Op1Addr::Imm => read_imm(off2)?,
                     Op1Addr::AP => DerefOrImmediate::Deref(CellRef {
                         register: cairo_lang_casm::operand::Register::AP,
                         offset: off2 as i16,
@@ -184,12 +189,7 @@ pub fn decode_instruction(memory: &Memory, offset: usize) -> Instruction {
         } => Instruction {
             body: InstructionBody::Jnz(JnzInstruction {
                 jump_offset: match op1_addr {
-                    Op1Addr::Imm => {
-                        assert_eq!(off2, 1);
-                        DerefOrImmediate::Immediate(BigIntAsHex {
-                            value: memory[offset + 1].unwrap().to_bigint(),
-                        })
-                    }
+                    Op1Addr::Imm => read_imm(off2)?,
                     Op1Addr::AP => DerefOrImmediate::Deref(CellRef {
                         register: cairo_lang_casm::operand::Register::AP,
                         offset: off2 as i16,
@@ -232,7 +232,273 @@ pub fn decode_instruction(memory: &Memory, offset: usize) -> Instruction {
             inc_ap: false,
             hints: Vec::new(),
         },
-        _ => panic!(),
+        _ => return Err(unknown_encoding()),
+    })
+}
+
+/// A decoded instruction.
+///
+/// Instructions that `cairo-lang-casm` can't represent, like the ones emitted by the Cairo 0
+/// compiler or written by hand, are kept in the VM's generic representation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AnyInstruction {
+    Casm(Instruction),
+    Generic(GenericInstruction),
+}
+
+impl AnyInstruction {
+    /// Return the number of cells taken by the instruction and its immediate.
+    pub fn size(&self) -> usize {
+        match self {
+            AnyInstruction::Casm(x) => x.body.op_size(),
+            AnyInstruction::Generic(x) => x.repr.size(),
+        }
+    }
+}
+
+impl fmt::Display for AnyInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnyInstruction::Casm(x) => write!(f, "{x}"),
+            AnyInstruction::Generic(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+/// Any valid instruction, along with its immediate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GenericInstruction {
+    pub repr: InstructionRepr,
+    /// The value of the op1 cell when it's relative to the pc.
+    pub imm: Option<Felt>,
+}
+
+impl GenericInstruction {
+    fn fmt_dst(&self) -> String {
+        fmt_cell(self.repr.dst_register, self.repr.off0)
+    }
+
+    fn fmt_op0(&self) -> String {
+        fmt_cell(self.repr.op0_register, self.repr.off1)
+    }
+
+    fn fmt_op1(&self) -> String {
+        match self.repr.op1_addr {
+            Op1Addr::Imm => match self.imm {
+                Some(imm) => signed_immediate(imm).value.to_string(),
+                None => format!("[pc + {}]", self.repr.off2),
+            },
+            Op1Addr::AP => fmt_cell(Register::AP, self.repr.off2),
+            Op1Addr::FP => fmt_cell(Register::FP, self.repr.off2),
+            Op1Addr::Op0 => format!("[{} + {}]", self.fmt_op0(), self.repr.off2),
+        }
+    }
+
+    fn fmt_res(&self) -> String {
+        match self.repr.res {
+            Res::Op1 | Res::Unconstrained => self.fmt_op1(),
+            Res::Add => format!("{} + {}", self.fmt_op0(), self.fmt_op1()),
+            Res::Mul => format!("{} * {}", self.fmt_op0(), self.fmt_op1()),
+        }
+    }
+}
+
+fn fmt_cell(register: Register, offset: isize) -> String {
+    match register {
+        Register::AP => format!("[ap + {offset}]"),
+        Register::FP => format!("[fp + {offset}]"),
+    }
+}
+
+/// Format the instruction with a syntax close to Cairo 0's. Register updates which don't match the
+/// opcode are appended after the main operation.
+impl fmt::Display for GenericInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = &self.repr;
+        let jump = |kind: &str| format!("jmp {kind} {}", self.fmt_res());
+        let mut ap_update_shown = false;
+
+        let body = match (repr.opcode, repr.pc_update) {
+            (Opcode::Call, PcUpdate::Jump) => format!("call abs {}", self.fmt_res()),
+            (Opcode::Call, _) => format!("call rel {}", self.fmt_res()),
+            (Opcode::Ret, _) => "ret".to_string(),
+            (Opcode::AssertEq, _) => format!("{} = {}", self.fmt_dst(), self.fmt_res()),
+            (Opcode::NOp, PcUpdate::Jnz) => {
+                format!("jmp rel {} if {} != 0", self.fmt_op1(), self.fmt_dst())
+            }
+            (Opcode::NOp, PcUpdate::Jump) => jump("abs"),
+            (Opcode::NOp, PcUpdate::JumpRel) => jump("rel"),
+            (Opcode::NOp, PcUpdate::Regular) if repr.ap_update == ApUpdate::Add => {
+                ap_update_shown = true;
+                format!("ap += {}", self.fmt_res())
+            }
+            (Opcode::NOp, PcUpdate::Regular) => "nop".to_string(),
+        };
+        write!(f, "{body}")?;
+
+        // Jumps which aren't part of the body.
+        if repr.opcode == Opcode::AssertEq {
+            match repr.pc_update {
+                PcUpdate::Regular => {}
+                PcUpdate::Jump => write!(f, ", jmp abs {}", self.fmt_res())?,
+                PcUpdate::JumpRel => write!(f, ", jmp rel {}", self.fmt_res())?,
+                PcUpdate::Jnz => {
+                    write!(f, ", jmp rel {} if {} != 0", self.fmt_op1(), self.fmt_dst())?
+                }
+            }
+        }
+
+        let (canonical_ap, canonical_fp) = match repr.opcode {
+            Opcode::Call => (ApUpdate::Add2, FpUpdate::APPlus2),
+            Opcode::Ret => (ApUpdate::Regular, FpUpdate::Dst),
+            Opcode::NOp | Opcode::AssertEq => (ApUpdate::Regular, FpUpdate::Regular),
+        };
+        if repr.ap_update != canonical_ap && !ap_update_shown {
+            match repr.ap_update {
+                ApUpdate::Regular => write!(f, ", ap unchanged")?,
+                ApUpdate::Add => write!(f, ", ap += {}", self.fmt_res())?,
+                ApUpdate::Add1 => write!(f, ", ap++")?,
+                ApUpdate::Add2 => write!(f, ", ap += 2")?,
+            }
+        }
+        if repr.fp_update != canonical_fp {
+            match repr.fp_update {
+                FpUpdate::Regular => write!(f, ", fp unchanged")?,
+                FpUpdate::APPlus2 => write!(f, ", fp = ap + 2")?,
+                FpUpdate::Dst => write!(f, ", fp = {}", self.fmt_dst())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn decode_any_instruction(memory: &Memory, offset: usize) -> AnyInstruction {
+    try_decode_any_instruction(memory, offset).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Decode any valid instruction, falling back to the generic representation when
+/// [`try_decode_instruction`] doesn't support its encoding.
+pub fn try_decode_any_instruction(memory: &Memory, offset: usize) -> Result<AnyInstruction, Error> {
+    match try_decode_instruction(memory, offset) {
+        Ok(instr) => Ok(AnyInstruction::Casm(instr)),
+        Err(Error::UnknownInstructionEncoding { pc, raw }) => {
+            let repr = decode_repr(raw).ok_or(Error::UnknownInstructionEncoding { pc, raw })?;
+            let imm = match repr.op1_addr {
+                Op1Addr::Imm => Some(
+                    memory
+                        .get(offset.wrapping_add_signed(repr.off2))
+                        .ok_or(Error::MissingImmediate { pc })?,
+                ),
+                _ => None,
+            };
+
+            Ok(AnyInstruction::Generic(GenericInstruction { repr, imm }))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Decode the instruction at `pc` into the VM's representation, which supports any valid encoding.
+pub(crate) fn try_decode_repr(memory: &Memory, pc: usize) -> Result<InstructionRepr, Error> {
+    let raw = memory.try_get(pc)?;
+    decode_repr(raw).ok_or(Error::UnknownInstructionEncoding { pc, raw })
+}
+
+fn decode_repr(raw: Felt) -> Option<InstructionRepr> {
+    decoder::decode_instruction(raw.try_into().ok()?).ok()
+}
+
+/// An entry of a program listing: either a decoded instruction or a data cell.
+#[derive(Clone, Debug)]
+pub struct ListingEntry {
+    pub pc: usize,
+    /// The encoded instruction followed by its immediate, if any.
+    pub raw: Vec<Felt>,
+    /// The decoded instruction, or `None` if the cell is not a valid instruction.
+    pub instruction: Option<AnyInstruction>,
+}
+
+impl ListingEntry {
+    /// Return the address a jump or call with an immediate target goes to.
+    pub fn target(&self) -> Option<usize> {
+        let (target, relative) = match self.instruction.as_ref()? {
+            AnyInstruction::Casm(instr) => {
+                let (target, relative) = match &instr.body {
+                    InstructionBody::Call(x) => (&x.target, x.relative),
+                    InstructionBody::Jump(x) => (&x.target, x.relative),
+                    InstructionBody::Jnz(x) => (&x.jump_offset, true),
+                    _ => return None,
+                };
+                let DerefOrImmediate::Immediate(target) = target else {
+                    return None;
+                };
+                (Felt::from(&target.value), relative)
+            }
+            AnyInstruction::Generic(instr) => {
+                let relative = match instr.repr.pc_update {
+                    PcUpdate::Jump => false,
+                    PcUpdate::JumpRel | PcUpdate::Jnz => true,
+                    PcUpdate::Regular => return None,
+                };
+                let is_imm = instr.repr.op1_addr == Op1Addr::Imm
+                    && matches!(instr.repr.res, Res::Op1 | Res::Unconstrained);
+                (instr.imm.filter(|_| is_imm)?, relative)
+            }
+        };
+
+        match relative {
+            true => Felt::from(self.pc) + target,
+            false => target,
+        }
+        .try_into()
+        .ok()
+    }
+}
+
+/// Decode every instruction within `range`, skipping their immediates. Cells which are not valid
+/// instructions are returned as data. The listing stops early at the first uninitialized cell.
+pub fn disassemble(
+    memory: &Memory,
+    range: Range<usize>,
+) -> impl Iterator<Item = ListingEntry> + '_ {
+    let mut pc = range.start;
+    std::iter::from_fn(move || {
+        if pc >= range.end {
+            return None;
+        }
+        let raw = memory.get(pc)?;
+
+        let entry = match try_decode_any_instruction(memory, pc) {
+            Ok(instr) => {
+                let size = instr.size();
+                ListingEntry {
+                    pc,
+                    raw: (pc..pc + size).filter_map(|x| memory.get(x)).collect(),
+                    instruction: Some(instr),
+                }
+            }
+            Err(_) => ListingEntry {
+                pc,
+                raw: vec![raw],
+                instruction: None,
+            },
+        };
+        pc += entry.raw.len();
+        Some(entry)
+    })
+}
+
+/// Represent an immediate as a signed integer, like the assembler's input: values above `P / 2`
+/// are negative.
+fn signed_immediate(imm: Felt) -> BigIntAsHex {
+    let value = imm.to_bigint();
+    let max = Felt::MAX.to_bigint();
+    BigIntAsHex {
+        value: match value > &max / 2 {
+            true => value - max - 1,
+            false => value,
+        },
     }
 }
 
@@ -245,8 +511,24 @@ struct ResDescription {
     res: Res,
 }
 
-fn decode_res_operand(desc: ResDescription) -> ResOperand {
-    match desc {
+/// Errors produced when decoding a `ResOperand`. They're converted into an [`Error`] by the caller,
+/// which knows the instruction's location.
+enum ResOperandError {
+    MissingImmediate,
+    UnknownEncoding,
+}
+
+impl ResOperandError {
+    fn into_error(self, pc: usize, raw: Felt) -> Error {
+        match self {
+            ResOperandError::MissingImmediate => Error::MissingImmediate { pc },
+            ResOperandError::UnknownEncoding => Error::UnknownInstructionEncoding { pc, raw },
+        }
+    }
+}
+
+fn decode_res_operand(desc: ResDescription) -> Result<ResOperand, ResOperandError> {
+    Ok(match desc {
         ResDescription {
             off1: -1,
             off2,
@@ -286,9 +568,7 @@ fn decode_res_operand(desc: ResDescription) -> ResOperand {
             op0_register: Register::FP,
             op1_addr: Op1Addr::Imm,
             res: Res::Op1,
-        } => ResOperand::Immediate(BigIntAsHex {
-            value: imm.to_bigint(),
-        }),
+        } => ResOperand::Immediate(signed_immediate(imm)),
         ResDescription {
             off1,
             off2,
@@ -310,12 +590,11 @@ fn decode_res_operand(desc: ResDescription) -> ResOperand {
                 offset: off1 as i16,
             },
             b: match op1_addr {
-                Op1Addr::Imm => {
-                    assert_eq!(off2, 1);
-                    DerefOrImmediate::Immediate(BigIntAsHex {
-                        value: imm.unwrap().to_bigint(),
-                    })
-                }
+                Op1Addr::Imm => match (off2, imm) {
+                    (1, Some(imm)) => DerefOrImmediate::Immediate(signed_immediate(imm)),
+                    (1, None) => return Err(ResOperandError::MissingImmediate),
+                    _ => return Err(ResOperandError::UnknownEncoding),
+                },
                 Op1Addr::AP => DerefOrImmediate::Deref(CellRef {
                     register: cairo_lang_casm::operand::Register::AP,
                     offset: off2 as i16,
@@ -327,6 +606,14 @@ fn decode_res_operand(desc: ResDescription) -> ResOperand {
                 _ => unreachable!(),
             },
         }),
-        _ => panic!(),
-    }
+        ResDescription {
+            off1: -1,
+            off2: 1,
+            imm: None,
+            op0_register: Register::FP,
+            op1_addr: Op1Addr::Imm,
+            res: Res::Op1,
+        } => return Err(ResOperandError::MissingImmediate),
+        _ => return Err(ResOperandError::UnknownEncoding),
+    })
 }
@@ -0,0 +1,154 @@
+use crate::{Error, ValueId};
+use std::{fmt, str::FromStr};
+
+/// Index of the program segment.
+pub const PROGRAM_SEGMENT: usize = 0;
+/// Index of the execution segment.
+pub const EXECUTION_SEGMENT: usize = 1;
+
+/// An address relative to the start of a segment.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SegmentAddr {
+    pub segment: usize,
+    pub offset: usize,
+}
+
+impl fmt::Display for SegmentAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.segment, self.offset)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SegmentKind<'a> {
+    Program,
+    Execution,
+    /// A builtin segment, with the builtin's name.
+    Builtin(&'a str),
+    /// Any other segment (ex. allocated by a hint).
+    Other,
+}
+
+/// Maps segments to their relocated base address.
+///
+/// The segments are laid out in order, with the program segment starting at address `1`, the
+/// same way cairo-vm relocates its memory.
+#[derive(Clone, Debug, Default)]
+pub struct RelocationTable {
+    bases: Vec<usize>,
+    /// One past the last address of the last segment, if known.
+    end: Option<usize>,
+    builtins: Vec<String>,
+}
+
+impl RelocationTable {
+    /// Build the table from the base address of every segment.
+    pub fn from_bases(bases: Vec<usize>) -> Self {
+        Self {
+            bases,
+            end: None,
+            builtins: Vec::new(),
+        }
+    }
+
+    /// Build the table from the size of every segment.
+    pub fn from_segment_sizes(sizes: impl IntoIterator<Item = usize>) -> Self {
+        let mut bases = vec![1];
+        for size in sizes {
+            bases.push(bases.last().unwrap() + size);
+        }
+        let end = bases.pop();
+
+        Self {
+            end,
+            ..Self::from_bases(bases)
+        }
+    }
+
+    /// Bound the last segment, which otherwise extends indefinitely. Usually the end of the
+    /// memory.
+    pub fn with_end(mut self, end: usize) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Name the builtin segments, which follow the execution segment in order.
+    pub fn with_builtins(mut self, builtins: Vec<String>) -> Self {
+        self.builtins = builtins;
+        self
+    }
+
+    pub fn bases(&self) -> &[usize] {
+        &self.bases
+    }
+
+    pub fn num_segments(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// Return the relocated address of a segment-relative address.
+    pub fn relocate(&self, segment: usize, offset: usize) -> Option<usize> {
+        self.bases.get(segment).map(|base| base + offset)
+    }
+
+    /// Return one past the last address of a segment: the base of the next one, or the end of the
+    /// table for the last one.
+    pub fn segment_end(&self, segment: usize) -> Option<usize> {
+        self.bases.get(segment + 1).copied().or(self.end)
+    }
+
+    /// Return the segment-relative address of a relocated address, or `None` if it's outside of
+    /// every segment.
+    pub fn unrelocate(&self, addr: usize) -> Option<SegmentAddr> {
+        let segment = self.bases.partition_point(|x| *x <= addr).checked_sub(1)?;
+        if self.segment_end(segment).is_some_and(|end| addr >= end) {
+            return None;
+        }
+
+        Some(SegmentAddr {
+            segment,
+            offset: addr - self.bases[segment],
+        })
+    }
+
+    pub fn segment_kind(&self, segment: usize) -> SegmentKind<'_> {
+        match segment {
+            PROGRAM_SEGMENT => SegmentKind::Program,
+            EXECUTION_SEGMENT => SegmentKind::Execution,
+            _ => match self.builtins.get(segment - 2) {
+                Some(name) => SegmentKind::Builtin(name),
+                None => SegmentKind::Other,
+            },
+        }
+    }
+
+    /// Format a value's address as `segment:offset`, falling back to the relocated address.
+    pub fn display(&self, value: ValueId) -> String {
+        match self.unrelocate(value.0) {
+            Some(addr) => addr.to_string(),
+            None => value.0.to_string(),
+        }
+    }
+}
+
+/// Parse a relocation table as the base addresses of every segment, separated by whitespace.
+impl FromStr for RelocationTable {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bases = s
+            .split_whitespace()
+            .enumerate()
+            .map(|(segment, x)| {
+                x.parse()
+                    .map_err(|_| Error::InvalidRelocationTable { segment })
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        if let Some(segment) = (1..bases.len()).find(|&i| bases[i] < bases[i - 1]) {
+            return Err(Error::InvalidRelocationTable { segment });
+        }
+
+        Ok(Self::from_bases(bases))
+    }
+}
@@ -1,7 +1,14 @@
-use crate::{GraphMappings, Memory, StepId, ValueId};
-use std::collections::VecDeque;
+use crate::{GraphMappings, Memory, StepId, Trace, ValueId};
+use std::collections::{HashSet, VecDeque};
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub mod bidirectional;
+pub mod gas;
+pub mod predicates;
+pub mod provenance;
+pub mod shortest;
+pub mod taint;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum NodeId {
     Step(StepId),
     Value(ValueId),
@@ -78,14 +85,74 @@ impl<T> QueueContainer<T> for DfsQueue<T> {
     }
 }
 
+/// A filter applied to every value before it's appended to a path.
+pub trait ValuePredicate {
+    fn test(&self, memory: &Memory, path: &[NodeId], value: ValueId) -> bool;
+}
+
+impl<F> ValuePredicate for F
+where
+    F: Fn(&Memory, &[NodeId], ValueId) -> bool,
+{
+    fn test(&self, memory: &Memory, path: &[NodeId], value: ValueId) -> bool {
+        self(memory, path, value)
+    }
+}
+
+/// A filter applied to every step before it's appended to a path.
+pub trait StepPredicate {
+    fn test(&self, trace: &Trace, path: &[NodeId], step: StepId) -> bool;
+}
+
+impl<F> StepPredicate for F
+where
+    F: Fn(&Trace, &[NodeId], StepId) -> bool,
+{
+    fn test(&self, trace: &Trace, path: &[NodeId], step: StepId) -> bool {
+        self(trace, path, step)
+    }
+}
+
+/// The set of filters a path has to satisfy. All of them must accept a node for it to be visited.
+#[derive(Default)]
+pub struct Predicates<'a> {
+    values: Vec<Box<dyn ValuePredicate + 'a>>,
+    steps: Vec<Box<dyn StepPredicate + 'a>>,
+}
+
+impl<'a> Predicates<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_value(mut self, predicate: impl ValuePredicate + 'a) -> Self {
+        self.values.push(Box::new(predicate));
+        self
+    }
+
+    pub fn with_step(mut self, predicate: impl StepPredicate + 'a) -> Self {
+        self.steps.push(Box::new(predicate));
+        self
+    }
+
+    pub fn test_value(&self, memory: &Memory, path: &[NodeId], value: ValueId) -> bool {
+        self.values.iter().all(|x| x.test(memory, path, value))
+    }
+
+    pub fn test_step(&self, trace: &Trace, path: &[NodeId], step: StepId) -> bool {
+        self.steps.iter().all(|x| x.test(trace, path, step))
+    }
+}
+
 pub struct SearchAlgorithmIter<'a, Q>
 where
     Q: QueueContainer<Vec<NodeId>>,
 {
     memory: &'a Memory,
+    trace: &'a Trace,
     mappings: &'a GraphMappings,
+    predicates: Predicates<'a>,
 
-    // visited: HashSet<NodeId>,
     queue: Q,
 
     target: ValueId,
@@ -122,35 +189,10 @@ where
                         self.mappings[id]
                             .iter()
                             .copied()
-                            .filter(|x| {
-                                // self.visited.insert(NodeId::Value(*x))
-                                !path.contains(&NodeId::Value(*x))
-                            })
+                            .filter(|x| !path.contains(&NodeId::Value(*x)))
                             .filter(|curr_id| {
-                                // // When searching for gas paths, the gas should not increase.
-                                // let prev_id = path
-                                //     .split_last()
-                                //     .unwrap()
-                                //     .1
-                                //     .iter()
-                                //     .rev()
-                                //     .find_map(|x| match x {
-                                //         NodeId::Step(_) => None,
-                                //         NodeId::Value(id) => Some(id),
-                                //     })
-                                //     .unwrap();
-                                // self.memory[curr_id.0]
-                                //     .is_some_and(|value| self.memory[prev_id.0].unwrap() >= value)
-
-                                self.memory[curr_id.0].is_some_and(|value| {
-                                    let min_value = 9919468708u64;
-                                    let max_value = 9997035710u64;
-
-                                    // Use half the min value to give some leeway for redeposited
-                                    // gas.
-                                    (value >= (min_value >> 1).into())
-                                    // && (value <= (max_value << 1).into())
-                                })
+                                self.memory.get(curr_id.0).is_some()
+                                    && self.predicates.test_value(self.memory, &path, *curr_id)
                             })
                             .map(|x| {
                                 let mut new_path = path.clone();
@@ -164,10 +206,7 @@ where
                         self.mappings[id]
                             .iter()
                             .copied()
-                            .filter(|x| {
-                                // self.visited.insert(NodeId::Step(*x))
-                                !path.contains(&NodeId::Step(*x))
-                            })
+                            .filter(|x| !path.contains(&NodeId::Step(*x)))
                             .filter(|curr_id| {
                                 // StepId should only increase.
                                 let prev_id = path.iter().rev().find_map(|x| match x {
@@ -180,6 +219,9 @@ where
                                     None => true,
                                 }
                             })
+                            .filter(|curr_id| {
+                                self.predicates.test_step(self.trace, &path, *curr_id)
+                            })
                             .map(|x| {
                                 let mut new_path = path.clone();
                                 new_path.push(NodeId::Step(x));
@@ -196,18 +238,69 @@ where
 
 pub fn run_search_algorithm<'a, Q>(
     memory: &'a Memory,
+    trace: &'a Trace,
     mappings: &'a GraphMappings,
     source: ValueId,
     target: ValueId,
+    predicates: Predicates<'a>,
 ) -> SearchAlgorithmIter<'a, Q>
 where
     Q: QueueContainer<Vec<NodeId>>,
 {
     SearchAlgorithmIter {
         memory,
+        trace,
         mappings,
-        // visited: HashSet::from([NodeId::Value(source)]),
+        predicates,
         queue: Q::new(vec![NodeId::Value(source)]),
         target,
     }
 }
+
+/// Skip the paths that share more than `max_overlap` (a fraction between `0` and `1`) of their
+/// nodes with any path returned before. The source and target are not taken into account, since
+/// every path shares them.
+pub fn diverse<I>(paths: I, max_overlap: f64) -> Diverse<I::IntoIter>
+where
+    I: IntoIterator<Item = Vec<NodeId>>,
+{
+    Diverse {
+        paths: paths.into_iter(),
+        max_overlap,
+        reported: Vec::new(),
+    }
+}
+
+pub struct Diverse<I> {
+    paths: I,
+    max_overlap: f64,
+    reported: Vec<HashSet<NodeId>>,
+}
+
+impl<I> Iterator for Diverse<I>
+where
+    I: Iterator<Item = Vec<NodeId>>,
+{
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let path = self.paths.next()?;
+            let inner = path
+                .get(1..path.len().saturating_sub(1))
+                .unwrap_or_default()
+                .iter()
+                .copied()
+                .collect::<HashSet<_>>();
+
+            let is_diverse = inner.is_empty()
+                || self.reported.iter().all(|x| {
+                    inner.intersection(x).count() as f64 / inner.len() as f64 <= self.max_overlap
+                });
+            if is_diverse {
+                self.reported.push(inner);
+                return Some(path);
+            }
+        }
+    }
+}
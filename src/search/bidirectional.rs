@@ -0,0 +1,211 @@
+use super::{
+    shortest::{tail, Search, State},
+    NodeId, Predicates,
+};
+use crate::{GraphMappings, Memory, Trace, ValueId};
+use std::collections::{hash_map::Entry, HashMap};
+
+/// Find a shortest path between two values by searching forward from the source and backward from
+/// the target at the same time, until both searches meet.
+///
+/// The forward search works like [`find_shortest_path`](super::shortest::find_shortest_path),
+/// visiting every node once per predecessor. The backward search visits every node once per
+/// successor and only keeps the steps in order, since the predicates depend on the nodes leading
+/// to a node. Every time both searches reach a node, the paths they found to it are joined and
+/// checked against the predicates. A join which fails is discarded, but the node is joined again
+/// whenever either search reaches it through another neighbor. Since the forward search alone
+/// finds every valid path, the search only gives up once it's exhausted.
+pub fn find_path_bidirectional(
+    memory: &Memory,
+    trace: &Trace,
+    mappings: &GraphMappings,
+    source: ValueId,
+    target: ValueId,
+    predicates: Predicates<'_>,
+) -> Option<Vec<NodeId>> {
+    let source = NodeId::Value(source);
+    let target = NodeId::Value(target);
+    if source == target {
+        return Some(vec![source]);
+    }
+
+    let mut search = Bidirectional {
+        forward: Search::new(memory, trace, mappings, predicates),
+        predecessors: HashMap::new(),
+        successors: HashMap::new(),
+        forward_states: HashMap::new(),
+        backward_states: HashMap::new(),
+    };
+
+    let mut forward_frontier = vec![(None, source)];
+    let mut backward_frontier = vec![(target, None)];
+    search.forward_states.insert(source, vec![(None, source)]);
+    search.backward_states.insert(target, vec![(target, None)]);
+
+    let mut buffer = Vec::new();
+    while !forward_frontier.is_empty() {
+        // Expand a whole level of the smallest frontier, then keep the shortest of the paths
+        // found while doing so.
+        let mut paths = Vec::new();
+        if backward_frontier.is_empty() || forward_frontier.len() <= backward_frontier.len() {
+            let mut next_frontier = Vec::new();
+            for state in forward_frontier {
+                search
+                    .forward
+                    .neighbors(tail(state).as_slice(), &mut buffer);
+                for &next in &buffer {
+                    let next_state = (Some(state.1), next);
+                    if next == source {
+                        continue;
+                    }
+                    if let Entry::Vacant(entry) = search.predecessors.entry(next_state) {
+                        entry.insert(state);
+                        search
+                            .forward_states
+                            .entry(next)
+                            .or_default()
+                            .push(next_state);
+                        for &other in search.backward_states.get(&next).into_iter().flatten() {
+                            paths.push(search.join(next_state, other));
+                        }
+                        next_frontier.push(next_state);
+                    }
+                }
+            }
+            forward_frontier = next_frontier;
+        } else {
+            let mut next_frontier = Vec::new();
+            for state in backward_frontier {
+                search.backward_neighbors(state, &mut buffer);
+                for &prev in &buffer {
+                    let prev_state = (prev, Some(state.0));
+                    if prev == target {
+                        continue;
+                    }
+                    if let Entry::Vacant(entry) = search.successors.entry(prev_state) {
+                        entry.insert(state);
+                        search
+                            .backward_states
+                            .entry(prev)
+                            .or_default()
+                            .push(prev_state);
+                        for &other in search.forward_states.get(&prev).into_iter().flatten() {
+                            paths.push(search.join(other, prev_state));
+                        }
+                        next_frontier.push(prev_state);
+                    }
+                }
+            }
+            backward_frontier = next_frontier;
+        }
+
+        if let Some(path) = paths
+            .into_iter()
+            .filter(|path| search.is_valid(path))
+            .min_by_key(Vec::len)
+        {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+/// A node along with its successor in the path, as visited by the backward search.
+type BackwardState = (NodeId, Option<NodeId>);
+
+struct Bidirectional<'a, 'b> {
+    forward: Search<'a, 'b>,
+    /// The previous state towards the source of every state visited by the forward search.
+    predecessors: HashMap<State, State>,
+    /// The next state towards the target of every state visited by the backward search.
+    successors: HashMap<BackwardState, BackwardState>,
+    /// The states visited by each search, by node.
+    forward_states: HashMap<NodeId, Vec<State>>,
+    backward_states: HashMap<NodeId, Vec<BackwardState>>,
+}
+
+impl Bidirectional<'_, '_> {
+    /// Append the nodes that may precede a state to `buffer`, ignoring whether they have been
+    /// visited.
+    fn backward_neighbors(&self, state: BackwardState, buffer: &mut Vec<NodeId>) {
+        let Search {
+            memory, mappings, ..
+        } = &self.forward;
+
+        buffer.clear();
+        match state {
+            (NodeId::Step(id), _) => buffer.extend(
+                mappings
+                    .step2value()
+                    .get(&id)
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(|x| memory.get(x.0).is_some())
+                    .map(NodeId::Value),
+            ),
+            (NodeId::Value(id), next) => {
+                // StepId should only decrease when going backward.
+                let next_id = match next {
+                    Some(NodeId::Step(id)) => Some(id),
+                    _ => None,
+                };
+
+                buffer.extend(
+                    mappings
+                        .value2step()
+                        .get(&id)
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .filter(|x| next_id.is_none_or(|next_id| x.0 < next_id.0))
+                        .map(NodeId::Step),
+                );
+            }
+        }
+    }
+
+    /// Join the path from the source to a state of the forward search and the path from a state
+    /// of the backward search to the target, which end and start at the same node.
+    fn join(&self, forward: State, backward: BackwardState) -> Vec<NodeId> {
+        let mut path = vec![forward.1];
+        let mut state = forward;
+        while let Some(prev) = self.predecessors.get(&state) {
+            path.push(prev.1);
+            state = *prev;
+        }
+        path.reverse();
+
+        let mut state = backward;
+        while let Some(next) = self.successors.get(&state) {
+            path.push(next.0);
+            state = *next;
+        }
+
+        path
+    }
+
+    /// Check that the steps in a joined path only increase and that it satisfies the predicates.
+    fn is_valid(&self, path: &[NodeId]) -> bool {
+        let Search {
+            memory,
+            trace,
+            predicates,
+            ..
+        } = &self.forward;
+
+        let mut prev_step = None;
+        (1..path.len()).all(|i| {
+            let tail = &path[i.saturating_sub(2)..i];
+            match path[i] {
+                NodeId::Step(id) => {
+                    let is_increasing = prev_step.is_none_or(|prev: usize| id.0 > prev);
+                    prev_step = Some(id.0);
+                    is_increasing && predicates.test_step(trace, tail, id)
+                }
+                NodeId::Value(id) => predicates.test_value(memory, tail, id),
+            }
+        })
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{
+    calls::CallTree, mappings::eval_res_operand, program::try_decode_repr, GraphMappings, Memory,
+    SierraMapping, StepId, SyscallLayout, Trace, ValueId,
+};
+use cairo_lang_casm::hints::{Hint, StarknetHint};
+use cairo_vm::{
+    types::instruction::{Op1Addr, Opcode, Register, Res},
+    vm::trace::trace_entry::RelocatedTraceEntry,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::{HashMap, HashSet};
+
+/// Changes larger than this are not gas operations, like the range checks of a failed
+/// `withdraw_gas` which offset the gas counter by `2 ** 128`.
+const MAX_DELTA: u128 = 1 << 64;
+
+/// A new cell holding the gas counter.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GasChange {
+    pub step: StepId,
+    pub value: ValueId,
+    pub gas: Felt,
+    /// The difference with the previous gas counter: negative for withdrawals, positive for
+    /// redeposits and zero for copies.
+    pub delta: i128,
+}
+
+/// The gas counter throughout an execution.
+#[derive(Clone, Debug)]
+pub struct GasTimeline {
+    pub initial: ValueId,
+    pub changes: Vec<GasChange>,
+}
+
+impl GasTimeline {
+    /// Return the gas counter right before a step is executed.
+    pub fn gas_at(&self, memory: &Memory, step: StepId) -> Option<Felt> {
+        let index = self.changes.partition_point(|x| x.step < step);
+        match index.checked_sub(1) {
+            Some(index) => Some(self.changes[index].gas),
+            None => memory.get(self.initial.0),
+        }
+    }
+
+    /// Iterate over the changes which withdraw or redeposit gas, skipping copies.
+    pub fn updates(&self) -> impl Iterator<Item = &GasChange> {
+        self.changes.iter().filter(|x| x.delta != 0)
+    }
+}
+
+/// Find the gas counter passed to the outermost Sierra function which takes one.
+pub fn find_gas_argument(
+    tree: &CallTree,
+    sierra: &SierraMapping,
+    program_offset: usize,
+) -> Option<ValueId> {
+    tree.frames.iter().find_map(|frame| {
+        let signature = sierra.builtin_signature(frame.entry_pc.checked_sub(program_offset)?)?;
+        let index = signature.params.iter().position(|x| *x == "GasBuiltin")?;
+        frame.arguments.get(index).copied()
+    })
+}
+
+/// Follow the gas counter forward from its initial cell.
+///
+/// Every `assert_eq` which relates the gas counter to a new cell close enough to it moves
+/// the counter to that cell: `withdraw_gas` subtracts from it, `redeposit_gas` adds to it and
+/// copies leave it as is. Every copy is followed, since the counter may be copied into a range
+/// check cell before the copy that's passed on. Function calls don't need any special handling,
+/// since arguments and return values are passed in place. A syscall whose request holds the
+/// counter moves it to the remaining gas of its response, withdrawing the cost of the syscall.
+pub fn track_gas(
+    memory: &Memory,
+    trace: &Trace,
+    mappings: &GraphMappings,
+    hints: &HashMap<usize, Vec<Hint>>,
+    program_offset: usize,
+    initial: ValueId,
+) -> GasTimeline {
+    let mut timeline = GasTimeline {
+        initial,
+        changes: Vec::new(),
+    };
+    let (Some(mut gas), Some(start)) = (memory.get(initial.0), mappings.first_access(initial))
+    else {
+        return timeline;
+    };
+
+    let mut aliases = HashSet::from([initial]);
+    let mut outputs = Vec::new();
+    for (step, entry) in trace.iter().enumerate().skip(start.0) {
+        let step = StepId(step);
+        if !mappings
+            .step2value()
+            .get(&step)
+            .is_some_and(|values| values.iter().any(|x| aliases.contains(x)))
+        {
+            continue;
+        }
+
+        // Hints run before the instruction of their step.
+        let step_hints = entry
+            .pc
+            .checked_sub(program_offset)
+            .and_then(|pc| hints.get(&pc))
+            .into_iter()
+            .flatten();
+        for hint in step_hints {
+            let Hint::Starknet(StarknetHint::SystemCall { system }) = hint else {
+                continue;
+            };
+            let Some(layout) = eval_res_operand(memory, &entry, system)
+                .and_then(|x| usize::try_from(x).ok())
+                .and_then(|x| SyscallLayout::new(memory, x))
+            else {
+                continue;
+            };
+            let Some(value) = layout
+                .remaining_gas()
+                .filter(|_| aliases.contains(&layout.gas()))
+            else {
+                continue;
+            };
+            let Some((new_gas, delta)) = memory
+                .get(value.0)
+                .and_then(|x| Some((x, i128::try_from(x - gas).ok()?)))
+            else {
+                continue;
+            };
+
+            aliases.clear();
+            aliases.insert(value);
+            gas = new_gas;
+            timeline.changes.push(GasChange {
+                step,
+                value,
+                gas,
+                delta,
+            });
+        }
+
+        let Some(values) = assertion_operands(memory, &entry) else {
+            continue;
+        };
+        if !values.iter().any(|x| aliases.contains(x)) {
+            continue;
+        }
+
+        outputs.clear();
+        outputs.extend(values.iter().copied().filter(|x| {
+            !aliases.contains(x)
+                && mappings.first_access(*x) == Some(step)
+                && !mappings.is_hint_output(step, *x)
+        }));
+        outputs.sort();
+        outputs.dedup();
+
+        for &value in &outputs {
+            let Some(new_gas) = memory.get(value.0) else {
+                continue;
+            };
+            let Some(delta) = i128::try_from(new_gas - gas)
+                .ok()
+                .filter(|x| x.unsigned_abs() < MAX_DELTA)
+            else {
+                continue;
+            };
+
+            if delta != 0 {
+                aliases.clear();
+                gas = new_gas;
+            }
+            aliases.insert(value);
+            timeline.changes.push(GasChange {
+                step,
+                value,
+                gas,
+                delta,
+            });
+        }
+    }
+
+    timeline
+}
+
+/// Return the cells related by an `assert_eq`: its destination and the operands of its result,
+/// excluding the pointer of a double dereference.
+fn assertion_operands(memory: &Memory, entry: &RelocatedTraceEntry) -> Option<Vec<ValueId>> {
+    let repr = try_decode_repr(memory, entry.pc).ok()?;
+    if repr.opcode != Opcode::AssertEq {
+        return None;
+    }
+
+    let register = |x| match x {
+        Register::AP => entry.ap,
+        Register::FP => entry.fp,
+    };
+    let dst = register(repr.dst_register).checked_add_signed(repr.off0)?;
+    let op0 = register(repr.op0_register).checked_add_signed(repr.off1)?;
+    let op1 = match repr.op1_addr {
+        Op1Addr::Imm => None,
+        Op1Addr::AP => Some(entry.ap),
+        Op1Addr::FP => Some(entry.fp),
+        Op1Addr::Op0 => Some(memory.get(op0)?.try_into().ok()?),
+    }
+    .and_then(|x: usize| x.checked_add_signed(repr.off2));
+
+    let mut operands = vec![ValueId(dst)];
+    if matches!(repr.res, Res::Add | Res::Mul) {
+        operands.push(ValueId(op0));
+    }
+    operands.extend(op1.map(ValueId));
+    Some(operands)
+}
@@ -0,0 +1,94 @@
+use super::{NodeId, StepPredicate, ValuePredicate};
+use crate::{AccessKind, GraphMappings, Memory, StepId, Trace, ValueId};
+use starknet_types_core::felt::Felt;
+use std::{collections::HashSet, ops::RangeInclusive};
+
+/// Accept only values within an inclusive range.
+#[derive(Clone, Debug)]
+pub struct ValueInRange(pub RangeInclusive<Felt>);
+
+impl ValuePredicate for ValueInRange {
+    fn test(&self, memory: &Memory, _path: &[NodeId], value: ValueId) -> bool {
+        memory
+            .get(value.0)
+            .is_some_and(|value| self.0.contains(&value))
+    }
+}
+
+/// Accept only values that are lower or equal than the previous value in the path, if there is
+/// one. Uninitialized values are never accepted, nor compared to.
+///
+/// Useful when following the gas counter, which should never increase.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NonIncreasing;
+
+impl ValuePredicate for NonIncreasing {
+    fn test(&self, memory: &Memory, path: &[NodeId], value: ValueId) -> bool {
+        let prev_id = path.iter().rev().find_map(|x| match x {
+            NodeId::Step(_) => None,
+            NodeId::Value(id) => Some(id),
+        });
+
+        let Some(curr_value) = memory.get(value.0) else {
+            return false;
+        };
+        match prev_id {
+            Some(prev_id) => memory
+                .get(prev_id.0)
+                .is_some_and(|prev_value| prev_value >= curr_value),
+            None => true,
+        }
+    }
+}
+
+/// Accept only values that are one of the provided set.
+#[derive(Clone, Debug)]
+pub struct ValueInSet(pub HashSet<Felt>);
+
+impl ValuePredicate for ValueInSet {
+    fn test(&self, memory: &Memory, _path: &[NodeId], value: ValueId) -> bool {
+        memory
+            .get(value.0)
+            .is_some_and(|value| self.0.contains(&value))
+    }
+}
+
+/// Accept only steps whose program counter is within an inclusive range.
+#[derive(Clone, Debug)]
+pub struct PcInRange(pub RangeInclusive<usize>);
+
+impl StepPredicate for PcInRange {
+    fn test(&self, trace: &Trace, _path: &[NodeId], step: StepId) -> bool {
+        trace.get(step.0).is_some_and(|x| self.0.contains(&x.pc))
+    }
+}
+
+/// Only follow producer to consumer dataflow: from a value to the steps that read it, and from a
+/// step to the values it writes or asserts.
+///
+/// It must be registered both as a value and a step predicate.
+#[derive(Clone, Copy, Debug)]
+pub struct Dataflow<'a>(pub &'a GraphMappings);
+
+impl ValuePredicate for Dataflow<'_> {
+    fn test(&self, _memory: &Memory, path: &[NodeId], value: ValueId) -> bool {
+        match path.last() {
+            Some(NodeId::Step(step)) => self
+                .0
+                .access_kind(*step, value)
+                .is_some_and(AccessKind::is_producer),
+            _ => true,
+        }
+    }
+}
+
+impl StepPredicate for Dataflow<'_> {
+    fn test(&self, _trace: &Trace, path: &[NodeId], step: StepId) -> bool {
+        match path.last() {
+            Some(NodeId::Value(value)) => {
+                self.0.access_kind(step, *value) == Some(AccessKind::Read)
+            }
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{AccessKind, GraphMappings, StepId, ValueId};
+use std::collections::{BTreeMap, VecDeque};
+
+/// How a value in a provenance slice came to be.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Origin {
+    /// Computed by a step from the values it read.
+    Step { step: StepId, inputs: Vec<ValueId> },
+    /// Written by one of the hints of a step. Hint results are not derived from the step's inputs,
+    /// so the slice stops here.
+    Hint { step: StepId },
+    /// Already in memory when first accessed: program constants, function arguments and builtin
+    /// cells.
+    Input,
+    /// Not expanded because it's deeper than the depth limit.
+    Truncated,
+}
+
+/// The dependency slice of a value: every value it was derived from, down to its inputs.
+///
+/// The slice is a DAG, since a value may be used to compute more than one other value.
+#[derive(Clone, Debug)]
+pub struct Provenance {
+    pub root: ValueId,
+    /// The origin of every value in the slice.
+    pub origins: BTreeMap<ValueId, Origin>,
+    /// The number of steps between the root and every value in the slice.
+    pub depths: BTreeMap<ValueId, usize>,
+}
+
+impl Provenance {
+    /// Iterate over the leaves of the slice along with their origin.
+    pub fn leaves(&self) -> impl Iterator<Item = (ValueId, &Origin)> {
+        self.origins
+            .iter()
+            .filter(|(_, x)| !matches!(x, Origin::Step { .. }))
+            .map(|(id, x)| (*id, x))
+    }
+}
+
+/// Walk backwards from a value through the steps that produced it and their inputs.
+///
+/// Memory is write-once, so a value's origin is the first step that accesses it: if that step
+/// writes or asserts it, or deduces it from an assertion whose destination already existed, the
+/// value was computed there, otherwise it already existed. Values more than `max_depth` steps
+/// away from `value` are not expanded.
+pub fn find_provenance(
+    mappings: &GraphMappings,
+    value: ValueId,
+    max_depth: Option<usize>,
+) -> Provenance {
+    let mut origins = BTreeMap::new();
+    let mut depths = BTreeMap::from([(value, 0)]);
+    let mut queue = VecDeque::from([value]);
+
+    while let Some(value) = queue.pop_front() {
+        let depth = depths[&value];
+        if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            origins.insert(value, Origin::Truncated);
+            continue;
+        }
+
+        let origin = origin_of(mappings, value);
+        if let Origin::Step { inputs, .. } = &origin {
+            for input in inputs {
+                if !depths.contains_key(input) {
+                    depths.insert(*input, depth + 1);
+                    queue.push_back(*input);
+                }
+            }
+        }
+        origins.insert(value, origin);
+    }
+
+    Provenance {
+        root: value,
+        origins,
+        depths,
+    }
+}
+
+fn origin_of(mappings: &GraphMappings, value: ValueId) -> Origin {
+    let Some(step) = mappings.first_access(value) else {
+        return Origin::Input;
+    };
+
+    if mappings.is_hint_output(step, value) {
+        Origin::Hint { step }
+    } else if mappings
+        .access_kind(step, value)
+        .is_some_and(|x| x.is_producer())
+    {
+        let mut inputs = mappings.inputs(step).collect::<Vec<_>>();
+        inputs.sort();
+        Origin::Step { step, inputs }
+    } else if is_deduced(mappings, step, value) {
+        // Every other cell of the assertion, including its destination, was used to deduce it.
+        let mut inputs = mappings
+            .step2value()
+            .get(&step)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|x| !is_deduced(mappings, step, *x) && !mappings.is_hint_output(step, *x))
+            .collect::<Vec<_>>();
+        inputs.sort();
+        Origin::Step { step, inputs }
+    } else {
+        Origin::Input
+    }
+}
+
+/// Whether a value read by an `AssertEq` was deduced by the VM from the assertion's destination.
+///
+/// When the destination already existed, the cell the step accesses for the first time is the
+/// operand the VM deduced, for example `[ap + 0]` in `[fp + -3] = [ap + 0] + [fp + -4]`.
+fn is_deduced(mappings: &GraphMappings, step: StepId, value: ValueId) -> bool {
+    if mappings.access_kind(step, value) != Some(AccessKind::Read)
+        || mappings.is_hint_output(step, value)
+        || mappings.first_access(value) != Some(step)
+    {
+        return false;
+    }
+
+    mappings.outputs(step).any(|x| {
+        mappings.access_kind(step, x) == Some(AccessKind::Assert)
+            && mappings.first_access(x).is_some_and(|x| x < step)
+    })
+}
@@ -0,0 +1,372 @@
+use super::{NodeId, Predicates};
+use crate::{GraphMappings, Memory, Trace, ValueId};
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+};
+
+/// The weight of the edges followed by [`find_shortest_path`].
+pub trait EdgeWeight {
+    /// Whether every edge weighs the same, in which case a BFS is used instead of Dijkstra.
+    const UNIT: bool = false;
+
+    /// Return the weight of the edge from the end of `tail` to `to`.
+    fn weight(&self, tail: &[NodeId], to: NodeId) -> u64;
+}
+
+/// Every edge weighs the same: find the path with the fewest nodes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unweighted;
+
+impl EdgeWeight for Unweighted {
+    const UNIT: bool = true;
+
+    fn weight(&self, _tail: &[NodeId], _to: NodeId) -> u64 {
+        1
+    }
+}
+
+/// Weigh every step by the number of steps executed since the previous one in the path: find the
+/// path that spans the fewest steps.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StepDistance;
+
+impl EdgeWeight for StepDistance {
+    fn weight(&self, tail: &[NodeId], to: NodeId) -> u64 {
+        let NodeId::Step(step) = to else {
+            return 0;
+        };
+
+        tail.iter()
+            .rev()
+            .find_map(|x| match x {
+                NodeId::Step(id) => Some(id),
+                NodeId::Value(_) => None,
+            })
+            .map_or(0, |prev| (step.0 - prev.0) as u64)
+    }
+}
+
+/// Find the shortest path between two values.
+///
+/// Unlike [`run_search_algorithm`](super::run_search_algorithm), paths are rebuilt from a
+/// predecessor map instead of being enumerated. Every node is visited at most once per
+/// predecessor, since the nodes that may follow it depend on the predecessor: a value reached
+/// through a late step is expanded again when it's reached through an earlier one. The search is
+/// therefore bounded by the sum, over every node, of its number of predecessors times its number
+/// of successors, rather than by the number of edges. As a consequence, the predicates only
+/// receive the tail of the path: the node being expanded and its predecessor.
+pub fn find_shortest_path<W>(
+    memory: &Memory,
+    trace: &Trace,
+    mappings: &GraphMappings,
+    source: ValueId,
+    target: ValueId,
+    predicates: Predicates<'_>,
+    weights: W,
+) -> Option<Vec<NodeId>>
+where
+    W: EdgeWeight,
+{
+    Search::new(memory, trace, mappings, predicates).run(
+        &[NodeId::Value(source)],
+        NodeId::Value(target),
+        &weights,
+    )
+}
+
+/// Find the paths between two values in increasing order of cost, using Yen's algorithm.
+///
+/// Every path is found with the same search as [`find_shortest_path`], so the same limitations
+/// apply.
+pub fn find_k_shortest_paths<'a, W>(
+    memory: &'a Memory,
+    trace: &'a Trace,
+    mappings: &'a GraphMappings,
+    source: ValueId,
+    target: ValueId,
+    predicates: Predicates<'a>,
+    weights: W,
+) -> KShortestPaths<'a, W>
+where
+    W: EdgeWeight,
+{
+    KShortestPaths {
+        search: Search::new(memory, trace, mappings, predicates),
+        weights,
+        source: NodeId::Value(source),
+        target: NodeId::Value(target),
+        started: false,
+        found: Vec::new(),
+        candidates: BinaryHeap::new(),
+        seen: HashSet::new(),
+    }
+}
+
+pub struct KShortestPaths<'a, W> {
+    search: Search<'a, 'a>,
+    weights: W,
+    source: NodeId,
+    target: NodeId,
+
+    started: bool,
+    found: Vec<Vec<NodeId>>,
+    candidates: BinaryHeap<Reverse<(u64, Vec<NodeId>)>>,
+    seen: HashSet<Vec<NodeId>>,
+}
+
+impl<W> Iterator for KShortestPaths<'_, W>
+where
+    W: EdgeWeight,
+{
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+
+            let path = self
+                .search
+                .run(&[self.source], self.target, &self.weights)?;
+            self.seen.insert(path.clone());
+            self.found.push(path.clone());
+            return Some(path);
+        }
+
+        // Find the deviations from the last path: for every node but the target, the shortest path
+        // which shares the nodes up to it but doesn't continue like any path found before.
+        let prev = self.found.last()?.clone();
+        for i in 0..prev.len() - 1 {
+            let root = &prev[..=i];
+            self.search.blocked_edges = self
+                .found
+                .iter()
+                .filter(|x| x.len() > i + 1 && x[..=i] == *root)
+                .map(|x| (x[i], x[i + 1]))
+                .collect();
+
+            if let Some(path) = self.search.run(root, self.target, &self.weights) {
+                if self.seen.insert(path.clone()) {
+                    let cost = path_cost(&path, &self.weights);
+                    self.candidates.push(Reverse((cost, path)));
+                }
+            }
+        }
+        self.search.blocked_edges.clear();
+
+        let Reverse((_, path)) = self.candidates.pop()?;
+        self.found.push(path.clone());
+        Some(path)
+    }
+}
+
+/// Return the total weight of a path.
+pub fn path_cost(path: &[NodeId], weights: &impl EdgeWeight) -> u64 {
+    (1..path.len())
+        .map(|i| weights.weight(&path[i.saturating_sub(2)..i], path[i]))
+        .sum()
+}
+
+/// A node along with its predecessor in the path. The nodes which may follow a node depend on its
+/// predecessor, since steps must increase and the predicates receive it.
+pub(super) type State = (Option<NodeId>, NodeId);
+
+pub(super) struct Search<'a, 'b> {
+    pub(super) memory: &'a Memory,
+    pub(super) trace: &'a Trace,
+    pub(super) mappings: &'a GraphMappings,
+    pub(super) predicates: Predicates<'b>,
+
+    predecessors: HashMap<State, State>,
+    blocked_edges: HashSet<(NodeId, NodeId)>,
+}
+
+impl<'a, 'b> Search<'a, 'b> {
+    pub(super) fn new(
+        memory: &'a Memory,
+        trace: &'a Trace,
+        mappings: &'a GraphMappings,
+        predicates: Predicates<'b>,
+    ) -> Self {
+        Self {
+            memory,
+            trace,
+            mappings,
+            predicates,
+            predecessors: HashMap::new(),
+            blocked_edges: HashSet::new(),
+        }
+    }
+
+    /// Find the shortest path to `target` which starts with `root`. The nodes in `root` are not
+    /// visited again.
+    fn run<W>(&mut self, root: &[NodeId], target: NodeId, weights: &W) -> Option<Vec<NodeId>>
+    where
+        W: EdgeWeight,
+    {
+        self.predecessors.clear();
+        let mut source = (None, root[0]);
+        for window in root.windows(2) {
+            let next = (Some(window[0]), window[1]);
+            self.predecessors.insert(next, source);
+            source = next;
+        }
+
+        let excluded = root.iter().copied().collect::<HashSet<_>>();
+        let found = if W::UNIT {
+            self.bfs(source, &excluded, target)
+        } else {
+            self.dijkstra(source, &excluded, target, weights)
+        };
+
+        found.map(|state| self.path_to(state))
+    }
+
+    fn bfs(&mut self, source: State, excluded: &HashSet<NodeId>, target: NodeId) -> Option<State> {
+        if source.1 == target {
+            return Some(source);
+        }
+
+        let mut visited = HashSet::from([source]);
+        let mut queue = VecDeque::from([source]);
+        let mut buffer = Vec::new();
+        while let Some(state) = queue.pop_front() {
+            self.neighbors(tail(state).as_slice(), &mut buffer);
+            for &next in &buffer {
+                let next_state = (Some(state.1), next);
+                if !excluded.contains(&next) && visited.insert(next_state) {
+                    self.predecessors.insert(next_state, state);
+                    if next == target {
+                        return Some(next_state);
+                    }
+                    queue.push_back(next_state);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn dijkstra(
+        &mut self,
+        source: State,
+        excluded: &HashSet<NodeId>,
+        target: NodeId,
+        weights: &impl EdgeWeight,
+    ) -> Option<State> {
+        // Ties are broken by the number of nodes, so that a path never loops back to a value
+        // through a later step when it costs the same.
+        let mut distances = HashMap::from([(source, (0, 0))]);
+        let mut settled = HashSet::new();
+        let mut queue = BinaryHeap::from([Reverse((0, 0, source))]);
+        let mut buffer = Vec::new();
+
+        while let Some(Reverse((distance, hops, state))) = queue.pop() {
+            if !settled.insert(state) {
+                continue;
+            }
+            if state.1 == target {
+                return Some(state);
+            }
+
+            let tail = tail(state);
+            self.neighbors(tail.as_slice(), &mut buffer);
+            for &next in &buffer {
+                let next_state = (Some(state.1), next);
+                if excluded.contains(&next) || settled.contains(&next_state) {
+                    continue;
+                }
+
+                let next_distance = (distance + weights.weight(tail.as_slice(), next), hops + 1);
+                match distances.entry(next_state) {
+                    Entry::Occupied(entry) if *entry.get() <= next_distance => continue,
+                    Entry::Occupied(mut entry) => {
+                        entry.insert(next_distance);
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(next_distance);
+                    }
+                }
+
+                self.predecessors.insert(next_state, state);
+                queue.push(Reverse((next_distance.0, next_distance.1, next_state)));
+            }
+        }
+
+        None
+    }
+
+    /// Append the nodes that may follow `tail` to `buffer`, ignoring whether they have been
+    /// visited.
+    pub(super) fn neighbors(&self, tail: &[NodeId], buffer: &mut Vec<NodeId>) {
+        buffer.clear();
+        match *tail.last().unwrap() {
+            NodeId::Step(id) => buffer.extend(
+                self.mappings
+                    .step2value()
+                    .get(&id)
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(|x| {
+                        self.memory.get(x.0).is_some()
+                            && self.predicates.test_value(self.memory, tail, *x)
+                    })
+                    .map(NodeId::Value),
+            ),
+            NodeId::Value(id) => {
+                // StepId should only increase.
+                let prev_id = tail.iter().rev().find_map(|x| match x {
+                    NodeId::Step(id) => Some(*id),
+                    NodeId::Value(_) => None,
+                });
+
+                buffer.extend(
+                    self.mappings
+                        .value2step()
+                        .get(&id)
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .filter(|x| prev_id.is_none_or(|prev_id| x.0 > prev_id.0))
+                        .filter(|x| self.predicates.test_step(self.trace, tail, *x))
+                        .map(NodeId::Step),
+                );
+            }
+        }
+
+        if !self.blocked_edges.is_empty() {
+            let node = *tail.last().unwrap();
+            buffer.retain(|next| !self.blocked_edges.contains(&(node, *next)));
+        }
+    }
+
+    /// Rebuild the path to a state by following the predecessors back to the source.
+    fn path_to(&self, mut state: State) -> Vec<NodeId> {
+        let mut path = vec![state.1];
+        while let Some(prev) = self.predecessors.get(&state) {
+            path.push(prev.1);
+            state = *prev;
+        }
+
+        path.reverse();
+        path
+    }
+}
+
+/// Return the end of the path to a state: its predecessor (if any) and its node.
+pub(super) fn tail(state: State) -> Tail {
+    match state.0 {
+        Some(prev) => Tail([prev, state.1], 0),
+        None => Tail([state.1, state.1], 1),
+    }
+}
+
+/// The last nodes of a path, without allocating.
+pub(super) struct Tail([NodeId; 2], usize);
+
+impl Tail {
+    pub(super) fn as_slice(&self) -> &[NodeId] {
+        &self.0[self.1..]
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{
+    mappings::{cell_ref_address, eval_res_operand},
+    try_decode_any_instruction, AccessKind, AnyInstruction, BuiltinSegments, GraphMappings, Memory,
+    StepId, SyscallLayout, Trace, ValueId,
+};
+use cairo_lang_casm::{
+    hints::{Hint, StarknetHint},
+    instructions::InstructionBody,
+    operand::DerefOrImmediate,
+};
+use cairo_vm::{
+    types::instruction::{Op1Addr, Opcode, PcUpdate, Register, Res},
+    vm::trace::trace_entry::RelocatedTraceEntry,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A sensitive operation reached by a tainted value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Sink {
+    /// A `jnz` whose condition is tainted.
+    Branch { step: StepId, condition: ValueId },
+    /// A `jmp`, `jnz` or `call` whose target is tainted.
+    Jump { step: StepId, target: ValueId },
+    /// A syscall whose request contains tainted cells.
+    Syscall {
+        step: StepId,
+        selector: Felt,
+        cells: Vec<ValueId>,
+    },
+}
+
+/// Every value and step which depends on a set of tainted values.
+#[derive(Clone, Debug, Default)]
+pub struct Taint {
+    /// The tainted values, along with the step which tainted them (`None` for the seeds).
+    pub values: BTreeMap<ValueId, Option<StepId>>,
+    /// The steps which read a tainted value.
+    pub steps: BTreeSet<StepId>,
+    pub sinks: Vec<Sink>,
+    /// The steps whose instruction couldn't be decoded, which the taint doesn't propagate
+    /// through.
+    pub undecodable: Vec<StepId>,
+}
+
+impl Taint {
+    pub fn is_tainted(&self, value: ValueId) -> bool {
+        self.values.contains_key(&value)
+    }
+}
+
+/// Propagate the taint of `seeds` forward through the execution.
+///
+/// Memory is write-once, so the values an `AssertEq` produces are the cells it accesses for the
+/// first time: its destination or, when the destination already existed, the operand the VM
+/// deduced from it. They are tainted when any of the other cells accessed by the step are, which
+/// includes the pointers of double derefs. Hint outputs are tainted when any of the hint's inputs
+/// are, and so are the outputs of a builtin instance when any of its inputs are. The cells written
+/// by a `call` are never tainted, since they only depend on the registers.
+pub fn find_taint(
+    memory: &Memory,
+    trace: &Trace,
+    mappings: &GraphMappings,
+    hints: &HashMap<usize, Vec<Hint>>,
+    builtins: &BuiltinSegments,
+    program_offset: usize,
+    seeds: &[ValueId],
+) -> Taint {
+    let mut taint = Taint {
+        values: seeds.iter().map(|x| (*x, None)).collect(),
+        ..Default::default()
+    };
+
+    let mut first_access = HashMap::<ValueId, StepId>::with_capacity(mappings.value2step().len());
+    for (value, steps) in mappings.value2step() {
+        if let Some(step) = steps.iter().min() {
+            first_access.insert(*value, *step);
+        }
+    }
+
+    // Seeds which are never accessed by an instruction may still reach a syscall.
+    let Some(start) = seeds
+        .iter()
+        .map(|x| first_access.get(x).map_or(0, |x| x.0))
+        .min()
+    else {
+        return taint;
+    };
+
+    let mut values = Vec::new();
+    let mut accessed = Vec::new();
+    let mut outputs = Vec::new();
+    for (step, entry) in trace.iter().enumerate().skip(start) {
+        let step = StepId(step);
+        let Ok(instr) = try_decode_any_instruction(memory, entry.pc) else {
+            taint.undecodable.push(step);
+            continue;
+        };
+
+        let step_hints = entry
+            .pc
+            .checked_sub(program_offset)
+            .and_then(|pc| hints.get(&pc))
+            .into_iter()
+            .flatten();
+        for hint in step_hints {
+            let Hint::Starknet(StarknetHint::SystemCall { system }) = hint else {
+                continue;
+            };
+            let Some(layout) = eval_res_operand(memory, &entry, system)
+                .and_then(|x| usize::try_from(x).ok())
+                .and_then(|x| SyscallLayout::new(memory, x))
+            else {
+                continue;
+            };
+
+            let cells = layout
+                .request
+                .map(ValueId)
+                .filter(|x| taint.is_tainted(*x))
+                .collect::<Vec<_>>();
+            if !cells.is_empty() {
+                taint.sinks.push(Sink::Syscall {
+                    step,
+                    selector: layout.selector,
+                    cells,
+                });
+            }
+        }
+
+        values.clear();
+        values.extend(
+            mappings
+                .step2value()
+                .get(&step)
+                .into_iter()
+                .flatten()
+                .copied(),
+        );
+        values.sort();
+
+        // Builtin outputs are deduced from the inputs of their instance when first accessed.
+        for &value in &values {
+            let Some(cell) = builtins.cell(value).filter(|x| x.is_output()) else {
+                continue;
+            };
+            if first_access[&value] != step || taint.is_tainted(value) {
+                continue;
+            }
+
+            let base = value.0 - cell.offset;
+            if (base..base + cell.builtin.outputs().start).any(|x| taint.is_tainted(ValueId(x))) {
+                taint.values.insert(value, Some(step));
+            }
+        }
+
+        if !values.iter().any(|x| taint.is_tainted(*x)) {
+            continue;
+        }
+        taint.steps.insert(step);
+
+        // Hint outputs only depend on the hint inputs.
+        if mappings.hint_inputs(step).any(|x| taint.is_tainted(x)) {
+            for &value in &values {
+                if mappings.is_hint_output(step, value) {
+                    taint.values.entry(value).or_insert(Some(step));
+                }
+            }
+        }
+
+        let operands = Operands::new(&instr, &entry);
+        if operands.is_assert_eq {
+            // Only the cells accessed by the instruction itself, not by its hints.
+            accessed.clear();
+            let _ = GraphMappings::iter_memory_references(memory, &entry, |x, _| {
+                accessed.push(ValueId(x))
+            });
+
+            outputs.clear();
+            outputs.extend(
+                accessed
+                    .iter()
+                    .copied()
+                    .filter(|x| first_access[x] == step && !mappings.is_hint_output(step, *x)),
+            );
+            if outputs
+                .iter()
+                .any(|x| mappings.access_kind(step, *x) == Some(AccessKind::Assert))
+            {
+                outputs.retain(|x| mappings.access_kind(step, *x) == Some(AccessKind::Assert));
+            }
+
+            let is_tainted = accessed.iter().any(|x| {
+                !outputs.contains(x) && !mappings.is_hint_output(step, *x) && taint.is_tainted(*x)
+            });
+            if is_tainted {
+                for &value in &outputs {
+                    taint.values.entry(value).or_insert(Some(step));
+                }
+            }
+        }
+
+        if let Some(condition) = operands.condition.filter(|x| taint.is_tainted(*x)) {
+            taint.sinks.push(Sink::Branch { step, condition });
+        }
+        if let Some(target) = operands.target.filter(|x| taint.is_tainted(*x)) {
+            taint.sinks.push(Sink::Jump { step, target });
+        }
+    }
+
+    taint
+}
+
+/// The parts of an instruction relevant to taint propagation.
+struct Operands {
+    is_assert_eq: bool,
+    /// The condition of a `jnz`.
+    condition: Option<ValueId>,
+    /// The cell containing the target of a jump or call.
+    target: Option<ValueId>,
+}
+
+impl Operands {
+    fn new(instr: &AnyInstruction, entry: &RelocatedTraceEntry) -> Self {
+        match instr {
+            AnyInstruction::Casm(instr) => {
+                let target = match &instr.body {
+                    InstructionBody::Call(x) => Some(&x.target),
+                    InstructionBody::Jnz(x) => Some(&x.jump_offset),
+                    InstructionBody::Jump(x) => Some(&x.target),
+                    _ => None,
+                };
+
+                Self {
+                    is_assert_eq: matches!(instr.body, InstructionBody::AssertEq(_)),
+                    condition: match &instr.body {
+                        InstructionBody::Jnz(x) => {
+                            Some(ValueId(cell_ref_address(entry, x.condition)))
+                        }
+                        _ => None,
+                    },
+                    target: match target {
+                        Some(DerefOrImmediate::Deref(x)) => {
+                            Some(ValueId(cell_ref_address(entry, *x)))
+                        }
+                        _ => None,
+                    },
+                }
+            }
+            AnyInstruction::Generic(instr) => {
+                let repr = &instr.repr;
+                let register = |x| match x {
+                    Register::AP => entry.ap,
+                    Register::FP => entry.fp,
+                };
+                let op1 = match repr.op1_addr {
+                    Op1Addr::AP => Some(entry.ap.wrapping_add_signed(repr.off2)),
+                    Op1Addr::FP => Some(entry.fp.wrapping_add_signed(repr.off2)),
+                    Op1Addr::Imm | Op1Addr::Op0 => None,
+                };
+                let is_jump = repr.opcode == Opcode::Call
+                    || (repr.pc_update != PcUpdate::Regular
+                        && matches!(repr.res, Res::Op1 | Res::Unconstrained));
+
+                Self {
+                    is_assert_eq: repr.opcode == Opcode::AssertEq,
+                    condition: (repr.pc_update == PcUpdate::Jnz).then(|| {
+                        ValueId(register(repr.dst_register).wrapping_add_signed(repr.off0))
+                    }),
+                    target: op1.filter(|_| is_jump).map(ValueId),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::Error;
+use cairo_lang_sierra::{
+    ids::{ConcreteTypeId, FunctionId},
+    program::{GenericArg, Program, Statement, StatementIdx, VersionedProgram},
+    ProgramParser,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, fmt};
+
+/// Generic types of the builtins, including the gas counter.
+const BUILTIN_TYPES: &[&str] = &[
+    "GasBuiltin",
+    "RangeCheck",
+    "RangeCheck96",
+    "Pedersen",
+    "Poseidon",
+    "Bitwise",
+    "EcOp",
+    "SegmentArena",
+    "AddMod",
+    "MulMod",
+];
+
+/// The CASM code generated for a Sierra statement, as found in the `sierra_statement_info` of the
+/// debug info produced by cairo-lang-sierra-to-casm.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct SierraStatementInfo {
+    /// Offset of the statement's first instruction, relative to the program segment.
+    pub start_offset: usize,
+    /// Offset one past the statement's last instruction, relative to the program segment.
+    pub end_offset: usize,
+}
+
+/// Maps CASM offsets to the Sierra statements, libfuncs and user functions they were generated
+/// from.
+#[derive(Clone, Debug)]
+pub struct SierraMapping {
+    program: Program,
+    statement_info: Vec<SierraStatementInfo>,
+    /// Entry points of the user functions sorted by statement, with the function's index.
+    entry_points: Vec<(StatementIdx, usize)>,
+    /// Fallback libfunc names for programs without debug names.
+    libfunc_names: HashMap<u64, String>,
+    /// Generic type of every concrete type which is a builtin.
+    builtin_types: HashMap<u64, String>,
+    /// Index of the declaration of every concrete type.
+    type_declarations: HashMap<u64, usize>,
+}
+
+/// The Sierra origin of a CASM instruction.
+#[derive(Clone, Debug)]
+pub struct SierraLocation<'a> {
+    pub statement: StatementIdx,
+    /// Name of the invoked libfunc, or `return`.
+    pub libfunc: &'a str,
+    /// The user function the statement belongs to.
+    pub function: Option<&'a FunctionId>,
+}
+
+/// The builtins a user function takes and returns.
+///
+/// Only the leading builtin parameters and return values are included, since they're the only ones
+/// whose position within the arguments doesn't depend on the size of other types. Implicit
+/// arguments always come first.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuiltinSignature<'a> {
+    pub params: Vec<&'a str>,
+    pub returns: Vec<&'a str>,
+}
+
+impl fmt::Display for SierraLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}", self.statement.0, self.libfunc)?;
+        if let Some(function) = self.function {
+            write!(f, " in {function}")?;
+        }
+        Ok(())
+    }
+}
+
+impl SierraMapping {
+    pub fn new(program: Program, statement_info: Vec<SierraStatementInfo>) -> Self {
+        let mut entry_points = program
+            .funcs
+            .iter()
+            .enumerate()
+            .map(|(index, function)| (function.entry_point, index))
+            .collect::<Vec<_>>();
+        entry_points.sort();
+
+        let libfunc_names = program
+            .libfunc_declarations
+            .iter()
+            .map(|decl| {
+                let name = match &decl.id.debug_name {
+                    Some(name) => name.to_string(),
+                    None => decl.long_id.to_string(),
+                };
+                (decl.id.id, name)
+            })
+            .collect();
+
+        let builtin_types = program
+            .type_declarations
+            .iter()
+            .filter(|decl| BUILTIN_TYPES.contains(&decl.long_id.generic_id.0.as_str()))
+            .map(|decl| (decl.id.id, decl.long_id.generic_id.to_string()))
+            .collect();
+
+        let type_declarations = program
+            .type_declarations
+            .iter()
+            .enumerate()
+            .map(|(index, decl)| (decl.id.id, index))
+            .collect();
+
+        Self {
+            program,
+            statement_info,
+            entry_points,
+            libfunc_names,
+            builtin_types,
+            type_declarations,
+        }
+    }
+
+    /// Parse a Sierra program, either as JSON (versioned or not) or in its textual form, along
+    /// with the CASM debug info as JSON.
+    ///
+    /// The debug info may be either the whole debug info object or just its
+    /// `sierra_statement_info` list.
+    pub fn parse(program: &str, debug_info: &str) -> Result<Self, Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum DebugInfo {
+            Full {
+                sierra_statement_info: Vec<SierraStatementInfo>,
+            },
+            StatementInfo(Vec<SierraStatementInfo>),
+        }
+
+        let program = parse_program(program)?;
+        let statement_info = match serde_json::from_str(debug_info) {
+            Ok(DebugInfo::Full {
+                sierra_statement_info,
+            }) => sierra_statement_info,
+            Ok(DebugInfo::StatementInfo(statement_info)) => statement_info,
+            Err(e) => return Err(Error::InvalidSierraDebugInfo(e.to_string())),
+        };
+
+        if statement_info.len() != program.statements.len() {
+            return Err(Error::InvalidSierraDebugInfo(format!(
+                "expected {} statements, found {}",
+                program.statements.len(),
+                statement_info.len(),
+            )));
+        }
+
+        Ok(Self::new(program, statement_info))
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Return the statement whose CASM code contains `offset`, relative to the program segment.
+    pub fn statement_at(&self, offset: usize) -> Option<StatementIdx> {
+        // Statements are laid out in order, so the last one starting at or before `offset` is the
+        // only candidate. Statements without any code are skipped naturally.
+        let index = self
+            .statement_info
+            .partition_point(|x| x.start_offset <= offset)
+            .checked_sub(1)?;
+        (offset < self.statement_info[index].end_offset).then_some(StatementIdx(index))
+    }
+
+    /// Return the user function a statement belongs to.
+    pub fn function_of(&self, statement: StatementIdx) -> Option<&FunctionId> {
+        Some(&self.program.funcs[self.function_index(statement)?].id)
+    }
+
+    fn function_index(&self, statement: StatementIdx) -> Option<usize> {
+        let index = self
+            .entry_points
+            .partition_point(|x| x.0 <= statement)
+            .checked_sub(1)?;
+        Some(self.entry_points[index].1)
+    }
+
+    /// Return the builtins of the user function whose code starts at `offset`, relative to the
+    /// program segment.
+    pub fn builtin_signature(&self, offset: usize) -> Option<BuiltinSignature<'_>> {
+        let index = self.function_index(self.statement_at(offset)?)?;
+        let signature = &self.program.funcs[index].signature;
+
+        let builtins = |types: &[ConcreteTypeId]| {
+            types
+                .iter()
+                .map_while(|x| self.builtin_types.get(&x.id).map(String::as_str))
+                .collect()
+        };
+        Some(BuiltinSignature {
+            params: builtins(&signature.param_types),
+            returns: builtins(&signature.ret_types),
+        })
+    }
+
+    /// Return the number of cells taken by the parameters of the user function whose code starts
+    /// at `offset`, relative to the program segment, or `None` if any of them has a type whose
+    /// size isn't known.
+    pub fn param_size(&self, offset: usize) -> Option<usize> {
+        let index = self.function_index(self.statement_at(offset)?)?;
+        self.types_size(&self.program.funcs[index].signature.param_types)
+    }
+
+    /// Return the number of cells taken by the return values of the user function whose code
+    /// starts at `offset`, relative to the program segment, or `None` if any of them has a type
+    /// whose size isn't known.
+    pub fn return_size(&self, offset: usize) -> Option<usize> {
+        let index = self.function_index(self.statement_at(offset)?)?;
+        self.types_size(&self.program.funcs[index].signature.ret_types)
+    }
+
+    fn types_size(&self, types: &[ConcreteTypeId]) -> Option<usize> {
+        types.iter().map(|x| self.type_size(x)).sum()
+    }
+
+    /// Return the number of cells taken by a value of a concrete type, following the layout of
+    /// cairo-lang-sierra-to-casm.
+    fn type_size(&self, id: &ConcreteTypeId) -> Option<usize> {
+        let long_id = &self.program.type_declarations[*self.type_declarations.get(&id.id)?].long_id;
+        let inner = || {
+            long_id.generic_args.iter().map(|x| match x {
+                GenericArg::Type(x) => self.type_size(x),
+                _ => Some(0),
+            })
+        };
+
+        match long_id.generic_id.0.as_str() {
+            "Array" | "EcPoint" => Some(2),
+            "EcState" => Some(3),
+            "NonZero" | "Snapshot" | "Uninitialized" | "Struct" => inner().sum(),
+            "Enum" => match inner()
+                .skip(1)
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .max()
+            {
+                Some(size) => Some(1 + size),
+                None => Some(0),
+            },
+            "AddMod" | "Bitwise" | "BoundedInt" | "Box" | "BuiltinCosts" | "bytes31"
+            | "ClassHash" | "ContractAddress" | "EcOp" | "Felt252Dict" | "felt252"
+            | "GasBuiltin" | "i128" | "i16" | "i32" | "i64" | "i8" | "MulMod" | "Nullable"
+            | "Pedersen" | "Poseidon" | "RangeCheck" | "RangeCheck96" | "Secp256K1Point"
+            | "Secp256R1Point" | "SegmentArena" | "Sha256StateHandle" | "StorageAddress"
+            | "StorageBaseAddress" | "System" | "u128" | "u16" | "u32" | "u64" | "u8" => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Return the Sierra origin of the instruction at `offset`, relative to the program segment.
+    pub fn locate(&self, offset: usize) -> Option<SierraLocation<'_>> {
+        let statement = self.statement_at(offset)?;
+        let libfunc = match &self.program.statements[statement.0] {
+            Statement::Invocation(invocation) => self
+                .libfunc_names
+                .get(&invocation.libfunc_id.id)
+                .map_or("<unknown libfunc>", String::as_str),
+            Statement::Return(_) => "return",
+        };
+
+        Some(SierraLocation {
+            statement,
+            libfunc,
+            function: self.function_of(statement),
+        })
+    }
+}
+
+fn parse_program(input: &str) -> Result<Program, Error> {
+    if let Ok(program) = serde_json::from_str::<VersionedProgram>(input) {
+        let artifact = program
+            .into_v1()
+            .map_err(|e| Error::InvalidSierraProgram(e.to_string()))?;
+        let mut program = artifact.program;
+        if let Some(debug_info) = artifact.debug_info {
+            debug_info.populate(&mut program);
+        }
+        return Ok(program);
+    }
+    if let Ok(program) = serde_json::from_str::<Program>(input) {
+        return Ok(program);
+    }
+
+    ProgramParser::new()
+        .parse(input)
+        .map_err(|e| Error::InvalidSierraProgram(e.to_string()))
+}
@@ -0,0 +1,228 @@
+//! The memory layout of the Starknet syscalls.
+//!
+//! A syscall request starts with the selector and the gas counter, followed by the arguments of
+//! the syscall. The response is written right after the request: the remaining gas, a failure
+//! flag, and either the results of the syscall or the revert reason.
+
+use crate::{Memory, ValueId};
+use starknet_types_core::felt::Felt;
+use std::{fmt, ops::Range};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Syscall {
+    CallContract,
+    Deploy,
+    EmitEvent,
+    GetBlockHash,
+    GetClassHashAt,
+    GetExecutionInfo,
+    Keccak,
+    LibraryCall,
+    MetaTxV0,
+    ReplaceClass,
+    Secp256k1Add,
+    Secp256k1GetPointFromX,
+    Secp256k1GetXy,
+    Secp256k1Mul,
+    Secp256k1New,
+    Secp256r1Add,
+    Secp256r1GetPointFromX,
+    Secp256r1GetXy,
+    Secp256r1Mul,
+    Secp256r1New,
+    SendMessageToL1,
+    Sha256ProcessBlock,
+    StorageRead,
+    StorageWrite,
+}
+
+impl Syscall {
+    const ALL: [Syscall; 24] = [
+        Syscall::CallContract,
+        Syscall::Deploy,
+        Syscall::EmitEvent,
+        Syscall::GetBlockHash,
+        Syscall::GetClassHashAt,
+        Syscall::GetExecutionInfo,
+        Syscall::Keccak,
+        Syscall::LibraryCall,
+        Syscall::MetaTxV0,
+        Syscall::ReplaceClass,
+        Syscall::Secp256k1Add,
+        Syscall::Secp256k1GetPointFromX,
+        Syscall::Secp256k1GetXy,
+        Syscall::Secp256k1Mul,
+        Syscall::Secp256k1New,
+        Syscall::Secp256r1Add,
+        Syscall::Secp256r1GetPointFromX,
+        Syscall::Secp256r1GetXy,
+        Syscall::Secp256r1Mul,
+        Syscall::Secp256r1New,
+        Syscall::SendMessageToL1,
+        Syscall::Sha256ProcessBlock,
+        Syscall::StorageRead,
+        Syscall::StorageWrite,
+    ];
+
+    /// Find the syscall of a selector, which is the short string of its name.
+    pub fn from_selector(selector: Felt) -> Option<Self> {
+        let selector = selector.to_bytes_be();
+        let name = selector
+            .iter()
+            .position(|x| *x != 0)
+            .map_or(&[][..], |x| &selector[x..]);
+
+        Self::ALL.into_iter().find(|x| x.name().as_bytes() == name)
+    }
+
+    /// The name of the syscall, which is also its selector.
+    pub fn name(self) -> &'static str {
+        match self {
+            Syscall::CallContract => "CallContract",
+            Syscall::Deploy => "Deploy",
+            Syscall::EmitEvent => "EmitEvent",
+            Syscall::GetBlockHash => "GetBlockHash",
+            Syscall::GetClassHashAt => "GetClassHashAt",
+            Syscall::GetExecutionInfo => "GetExecutionInfo",
+            Syscall::Keccak => "Keccak",
+            Syscall::LibraryCall => "LibraryCall",
+            Syscall::MetaTxV0 => "MetaTxV0",
+            Syscall::ReplaceClass => "ReplaceClass",
+            Syscall::Secp256k1Add => "Secp256k1Add",
+            Syscall::Secp256k1GetPointFromX => "Secp256k1GetPointFromX",
+            Syscall::Secp256k1GetXy => "Secp256k1GetXy",
+            Syscall::Secp256k1Mul => "Secp256k1Mul",
+            Syscall::Secp256k1New => "Secp256k1New",
+            Syscall::Secp256r1Add => "Secp256r1Add",
+            Syscall::Secp256r1GetPointFromX => "Secp256r1GetPointFromX",
+            Syscall::Secp256r1GetXy => "Secp256r1GetXy",
+            Syscall::Secp256r1Mul => "Secp256r1Mul",
+            Syscall::Secp256r1New => "Secp256r1New",
+            Syscall::SendMessageToL1 => "SendMessageToL1",
+            Syscall::Sha256ProcessBlock => "Sha256ProcessBlock",
+            Syscall::StorageRead => "StorageRead",
+            Syscall::StorageWrite => "StorageWrite",
+        }
+    }
+
+    /// The number of arguments of the request, after the selector and the gas counter. Arrays are
+    /// passed as their start and end pointers.
+    pub fn request_args(self) -> usize {
+        match self {
+            Syscall::GetExecutionInfo => 0,
+            Syscall::GetBlockHash
+            | Syscall::GetClassHashAt
+            | Syscall::ReplaceClass
+            | Syscall::Secp256k1GetXy
+            | Syscall::Secp256r1GetXy => 1,
+            Syscall::Keccak
+            | Syscall::Secp256k1Add
+            | Syscall::Secp256r1Add
+            | Syscall::Sha256ProcessBlock
+            | Syscall::StorageRead => 2,
+            Syscall::Secp256k1GetPointFromX
+            | Syscall::Secp256k1Mul
+            | Syscall::Secp256r1GetPointFromX
+            | Syscall::Secp256r1Mul
+            | Syscall::SendMessageToL1
+            | Syscall::StorageWrite => 3,
+            Syscall::CallContract
+            | Syscall::EmitEvent
+            | Syscall::LibraryCall
+            | Syscall::Secp256k1New
+            | Syscall::Secp256r1New => 4,
+            Syscall::Deploy => 5,
+            Syscall::MetaTxV0 => 6,
+        }
+    }
+
+    /// The number of results of a successful response, after the gas counter and the failure
+    /// flag.
+    pub fn response_results(self) -> usize {
+        match self {
+            Syscall::EmitEvent
+            | Syscall::ReplaceClass
+            | Syscall::SendMessageToL1
+            | Syscall::StorageWrite => 0,
+            Syscall::GetBlockHash
+            | Syscall::GetClassHashAt
+            | Syscall::GetExecutionInfo
+            | Syscall::Secp256k1Add
+            | Syscall::Secp256k1Mul
+            | Syscall::Secp256r1Add
+            | Syscall::Secp256r1Mul
+            | Syscall::Sha256ProcessBlock
+            | Syscall::StorageRead => 1,
+            Syscall::CallContract
+            | Syscall::Keccak
+            | Syscall::LibraryCall
+            | Syscall::MetaTxV0
+            | Syscall::Secp256k1GetPointFromX
+            | Syscall::Secp256k1New
+            | Syscall::Secp256r1GetPointFromX
+            | Syscall::Secp256r1New => 2,
+            Syscall::Deploy => 3,
+            Syscall::Secp256k1GetXy | Syscall::Secp256r1GetXy => 4,
+        }
+    }
+}
+
+impl fmt::Display for Syscall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The cells of a syscall's request and response.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyscallLayout {
+    pub selector: Felt,
+    /// The syscall, or `None` if the selector is unknown.
+    pub syscall: Option<Syscall>,
+    /// The request, including the selector and the gas counter.
+    pub request: Range<usize>,
+    /// The response, including the remaining gas and the failure flag. It's empty when the
+    /// selector is unknown, since its request may have any length.
+    pub response: Range<usize>,
+}
+
+impl SyscallLayout {
+    /// Find the layout of the syscall whose request starts at `system`, or `None` if its selector
+    /// is not initialized.
+    ///
+    /// A failed syscall responds with the start and end pointers of its revert reason instead of
+    /// its results.
+    pub fn new(memory: &Memory, system: usize) -> Option<Self> {
+        let selector = memory.get(system)?;
+        let Some(syscall) = Syscall::from_selector(selector) else {
+            return Some(Self {
+                selector,
+                syscall: None,
+                request: system..system + 2,
+                response: system + 2..system + 2,
+            });
+        };
+
+        let request = system..system + 2 + syscall.request_args();
+        let results = match memory.get(request.end + 1) {
+            Some(x) if x == Felt::ONE => 2,
+            _ => syscall.response_results(),
+        };
+        Some(Self {
+            selector,
+            syscall: Some(syscall),
+            response: request.end..request.end + 2 + results,
+            request,
+        })
+    }
+
+    /// The gas counter passed to the syscall.
+    pub fn gas(&self) -> ValueId {
+        ValueId(self.request.start + 1)
+    }
+
+    /// The gas left after the syscall, if the selector is known.
+    pub fn remaining_gas(&self) -> Option<ValueId> {
+        (!self.response.is_empty()).then_some(ValueId(self.response.start))
+    }
+}
@@ -1,40 +1,161 @@
+use crate::{memory::to_segment, relocation::EXECUTION_SEGMENT, Error, RelocationTable};
 use bincode::{de::read::Reader, error::DecodeError};
 use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
-use std::ops::Deref;
+use memmap2::Mmap;
+use std::{fs::File, path::Path};
+
+/// Size of a trace record: the `ap`, `fp` and `pc` registers, 8 bytes each.
+const RECORD_SIZE: usize = 24;
+/// Size of an unrelocated trace record: the `pc` register's segment index and offset, followed by
+/// the `ap` and `fp` offsets within the execution segment, 8 bytes each.
+const UNRELOCATED_RECORD_SIZE: usize = 32;
+
+/// The execution trace.
+///
+/// The entries are either decoded into memory or read lazily from a memory-mapped dump.
+#[derive(Debug)]
+pub struct Trace(Entries);
 
 #[derive(Debug)]
-pub struct Trace(Vec<RelocatedTraceEntry>);
+enum Entries {
+    Owned(Vec<RelocatedTraceEntry>),
+    Mapped(Mmap),
+}
 
 impl Trace {
-    pub fn decode(mut data: impl Reader) -> Self {
+    pub fn decode(data: impl Reader) -> Self {
+        Self::try_decode(data).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_decode(mut data: impl Reader) -> Result<Self, Error> {
         let mut trace = Vec::new();
 
         let mut buf = [0u8; 8];
-        loop {
+        for offset in (0..).step_by(RECORD_SIZE) {
             match data.read(&mut buf) {
                 Ok(_) => {}
                 Err(DecodeError::UnexpectedEnd { additional: 8 }) => break,
-                e @ Err(_) => e.unwrap(),
+                Err(DecodeError::UnexpectedEnd { .. }) => {
+                    return Err(Error::TruncatedRecord { offset })
+                }
+                Err(e) => return Err(Error::Decode(e)),
             }
             let ap = u64::from_le_bytes(buf) as usize;
 
-            data.read(&mut buf).unwrap();
-            let fp = u64::from_le_bytes(buf) as usize;
-
-            data.read(&mut buf).unwrap();
-            let pc = u64::from_le_bytes(buf) as usize;
+            let mut read_u64 = || match data.read(&mut buf) {
+                Ok(_) => Ok(u64::from_le_bytes(buf) as usize),
+                Err(DecodeError::UnexpectedEnd { .. }) => Err(Error::TruncatedRecord { offset }),
+                Err(e) => Err(Error::Decode(e)),
+            };
+            let fp = read_u64()?;
+            let pc = read_u64()?;
 
             trace.push(RelocatedTraceEntry { pc, ap, fp });
         }
 
-        Self(trace)
+        Ok(Self(Entries::Owned(trace)))
     }
-}
 
-impl Deref for Trace {
-    type Target = [RelocatedTraceEntry];
+    pub fn decode_unrelocated(data: impl Reader, table: &RelocationTable) -> Self {
+        Self::try_decode_unrelocated(data, table).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Decode an unrelocated trace dump (ex. from a failed execution) using a relocation table.
+    pub fn try_decode_unrelocated(
+        mut data: impl Reader,
+        table: &RelocationTable,
+    ) -> Result<Self, Error> {
+        let relocate = |segment, offset| {
+            table
+                .relocate(segment, offset)
+                .ok_or(Error::UnknownSegment { segment })
+        };
+
+        let mut trace = Vec::new();
+
+        let mut buf = [0u8; 8];
+        for offset in (0..).step_by(UNRELOCATED_RECORD_SIZE) {
+            match data.read(&mut buf) {
+                Ok(_) => {}
+                Err(DecodeError::UnexpectedEnd { additional: 8 }) => break,
+                Err(DecodeError::UnexpectedEnd { .. }) => {
+                    return Err(Error::TruncatedRecord { offset })
+                }
+                Err(e) => return Err(Error::Decode(e)),
+            }
+            let pc_segment = to_segment(i64::from_le_bytes(buf))?;
+
+            let mut read_u64 = || match data.read(&mut buf) {
+                Ok(_) => Ok(u64::from_le_bytes(buf) as usize),
+                Err(DecodeError::UnexpectedEnd { .. }) => Err(Error::TruncatedRecord { offset }),
+                Err(e) => Err(Error::Decode(e)),
+            };
+            let pc_offset = read_u64()?;
+            let ap = read_u64()?;
+            let fp = read_u64()?;
+
+            trace.push(RelocatedTraceEntry {
+                pc: relocate(pc_segment, pc_offset)?,
+                ap: relocate(EXECUTION_SEGMENT, ap)?,
+                fp: relocate(EXECUTION_SEGMENT, fp)?,
+            });
+        }
+
+        Ok(Self(Entries::Owned(trace)))
+    }
+
+    /// Memory-map a trace dump. The entries are decoded on demand.
+    pub fn map_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = File::open(path).map_err(Error::Io)?;
+        // SAFETY: The file is not expected to be modified while it's being analyzed.
+        let mmap = unsafe { Mmap::map(&file) }.map_err(Error::Io)?;
+
+        if mmap.len() % RECORD_SIZE != 0 {
+            return Err(Error::TruncatedRecord {
+                offset: mmap.len() - mmap.len() % RECORD_SIZE,
+            });
+        }
+
+        Ok(Self(Entries::Mapped(mmap)))
+    }
+
+    pub fn get(&self, index: usize) -> Option<RelocatedTraceEntry> {
+        match &self.0 {
+            Entries::Owned(entries) => entries.get(index).cloned(),
+            Entries::Mapped(mmap) => {
+                let record = mmap.get(index * RECORD_SIZE..(index + 1) * RECORD_SIZE)?;
+                let read_u64 =
+                    |i: usize| u64::from_le_bytes(record[i..i + 8].try_into().unwrap()) as usize;
+
+                Some(RelocatedTraceEntry {
+                    ap: read_u64(0),
+                    fp: read_u64(8),
+                    pc: read_u64(16),
+                })
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Entries::Owned(entries) => entries.len(),
+            Entries::Mapped(mmap) => mmap.len() / RECORD_SIZE,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn first(&self) -> Option<RelocatedTraceEntry> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<RelocatedTraceEntry> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    pub fn iter(&self) -> impl Iterator<Item = RelocatedTraceEntry> + '_ {
+        (0..self.len()).map(|index| self.get(index).unwrap())
     }
 }
@@ -0,0 +1,193 @@
+//! Replay an execution to check that its trace is consistent with its memory.
+
+use crate::{program::try_decode_repr, Error, Memory, StepId, Trace};
+use cairo_vm::{
+    types::instruction::{ApUpdate, FpUpdate, Op1Addr, Opcode, PcUpdate, Register, Res},
+    vm::trace::trace_entry::RelocatedTraceEntry,
+};
+use starknet_types_core::felt::Felt;
+use std::fmt;
+
+/// The first step at which the trace and the memory disagree.
+#[derive(Debug)]
+pub struct Divergence {
+    pub step: StepId,
+    pub kind: DivergenceKind,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step {} diverges: {}", self.step.0, self.kind)
+    }
+}
+
+#[derive(Debug)]
+pub enum DivergenceKind {
+    /// The instruction can't be decoded, or a cell it accesses is not initialized.
+    Error(Error),
+    /// A value used as an address, or an address computed from it, is out of range.
+    InvalidAddress { value: Felt },
+    /// The instruction uses its result, but its encoding leaves it unconstrained.
+    UnconstrainedRes,
+    /// A cell doesn't contain the value the instruction asserts or writes.
+    Assertion {
+        addr: usize,
+        expected: Felt,
+        actual: Felt,
+    },
+    /// A register of the next trace entry doesn't match the one computed by the step.
+    Register {
+        name: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl From<Error> for DivergenceKind {
+    fn from(value: Error) -> Self {
+        DivergenceKind::Error(value)
+    }
+}
+
+impl fmt::Display for DivergenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DivergenceKind::Error(e) => write!(f, "{e}"),
+            DivergenceKind::InvalidAddress { value } => write!(f, "invalid address {value}"),
+            DivergenceKind::UnconstrainedRes => write!(f, "the result is unconstrained"),
+            DivergenceKind::Assertion {
+                addr,
+                expected,
+                actual,
+            } => write!(f, "expected [{addr}] = {expected}, but it is {actual}"),
+            DivergenceKind::Register {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected the next {name} to be {expected}, but it is {actual}"
+            ),
+        }
+    }
+}
+
+/// Re-execute every step of the trace and check it against the memory.
+///
+/// Every `assert_eq` and `call` must hold in memory, and the registers computed by every step must
+/// match the next trace entry. Hints and builtins are not executed: the memory already contains
+/// every cell they wrote.
+pub fn verify_trace(memory: &Memory, trace: &Trace) -> Result<(), Divergence> {
+    let mut entries = trace.iter().enumerate().peekable();
+    while let Some((step, entry)) = entries.next() {
+        let divergence = |kind| Divergence {
+            step: StepId(step),
+            kind,
+        };
+
+        let next = replay_step(memory, &entry).map_err(divergence)?;
+        let Some((_, actual)) = entries.peek() else {
+            break;
+        };
+        for (name, expected, actual) in [
+            ("pc", next.pc, actual.pc),
+            ("ap", next.ap, actual.ap),
+            ("fp", next.fp, actual.fp),
+        ] {
+            if expected != actual {
+                return Err(divergence(DivergenceKind::Register {
+                    name,
+                    expected,
+                    actual,
+                }));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute a single step using the VM's semantics, checking its assertions against the memory,
+/// and return the registers of the next step.
+pub fn replay_step(
+    memory: &Memory,
+    entry: &RelocatedTraceEntry,
+) -> Result<RelocatedTraceEntry, DivergenceKind> {
+    let repr = try_decode_repr(memory, entry.pc)?;
+    let size = repr.size();
+    let register = |x| match x {
+        Register::AP => entry.ap,
+        Register::FP => entry.fp,
+    };
+
+    let dst_addr = register(repr.dst_register).wrapping_add_signed(repr.off0);
+    let op0_addr = register(repr.op0_register).wrapping_add_signed(repr.off1);
+    let dst = || memory.try_get(dst_addr);
+    let op0 = || memory.try_get(op0_addr);
+
+    let op1_addr = match repr.op1_addr {
+        Op1Addr::Imm => entry.pc,
+        Op1Addr::AP => entry.ap,
+        Op1Addr::FP => entry.fp,
+        Op1Addr::Op0 => to_address(op0()?)?,
+    }
+    .wrapping_add_signed(repr.off2);
+    let op1 = memory.try_get(op1_addr)?;
+
+    let res = match repr.res {
+        Res::Op1 => Some(op1),
+        Res::Add => Some(op0()? + op1),
+        Res::Mul => Some(op0()? * op1),
+        Res::Unconstrained => None,
+    };
+    let res = || res.ok_or(DivergenceKind::UnconstrainedRes);
+
+    let assert_cell = |addr, expected| {
+        let actual = memory.try_get(addr)?;
+        match actual == expected {
+            true => Ok(()),
+            false => Err(DivergenceKind::Assertion {
+                addr,
+                expected,
+                actual,
+            }),
+        }
+    };
+    match repr.opcode {
+        Opcode::AssertEq => assert_cell(dst_addr, res()?)?,
+        Opcode::Call => {
+            assert_cell(dst_addr, Felt::from(entry.fp))?;
+            assert_cell(op0_addr, Felt::from(entry.pc + size))?;
+        }
+        Opcode::NOp | Opcode::Ret => {}
+    }
+
+    let pc = match repr.pc_update {
+        PcUpdate::Regular => entry.pc + size,
+        PcUpdate::Jump => to_address(res()?)?,
+        PcUpdate::JumpRel => to_address(Felt::from(entry.pc) + res()?)?,
+        PcUpdate::Jnz => match dst()? == Felt::ZERO {
+            true => entry.pc + size,
+            false => to_address(Felt::from(entry.pc) + op1)?,
+        },
+    };
+    let ap = match repr.ap_update {
+        ApUpdate::Regular => entry.ap,
+        ApUpdate::Add => to_address(Felt::from(entry.ap) + res()?)?,
+        ApUpdate::Add1 => entry.ap + 1,
+        ApUpdate::Add2 => entry.ap + 2,
+    };
+    let fp = match repr.fp_update {
+        FpUpdate::Regular => entry.fp,
+        FpUpdate::APPlus2 => entry.ap + 2,
+        FpUpdate::Dst => to_address(dst()?)?,
+    };
+
+    Ok(RelocatedTraceEntry { pc, ap, fp })
+}
+
+fn to_address(value: Felt) -> Result<usize, DivergenceKind> {
+    value
+        .try_into()
+        .map_err(|_| DivergenceKind::InvalidAddress { value })
+}
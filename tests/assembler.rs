@@ -0,0 +1,227 @@
+use cairo_lang_casm::{
+    instructions::{
+        AddApInstruction, AssertEqInstruction, CallInstruction, Instruction, InstructionBody,
+        JnzInstruction, JumpInstruction, RetInstruction,
+    },
+    operand::{BinOpOperand, CellRef, DerefOrImmediate, Operation, Register, ResOperand},
+};
+use cairo_lang_utils::bigint::BigIntAsHex;
+use common::build_memory;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sierra2casm_dbg::decode_instruction;
+use starknet_types_core::felt::Felt;
+
+mod common;
+
+/// Assemble an instruction at address `1` and decode it back.
+fn round_trip(instr: &Instruction) -> Instruction {
+    let cells = instr
+        .assemble()
+        .encode()
+        .iter()
+        .enumerate()
+        .map(|(offset, value)| (offset + 1, Felt::from(value)))
+        .collect::<Vec<_>>();
+    decode_instruction(&build_memory(&cells), 1)
+}
+
+fn assert_round_trip(instr: Instruction) {
+    assert_eq!(round_trip(&instr), instr, "{instr}");
+}
+
+fn cell_ref(register: Register, offset: i16) -> CellRef {
+    CellRef { register, offset }
+}
+
+fn imm(value: i128) -> BigIntAsHex {
+    BigIntAsHex {
+        value: value.into(),
+    }
+}
+
+fn random_cell_ref(rng: &mut StdRng) -> CellRef {
+    let register = match rng.gen() {
+        true => Register::AP,
+        false => Register::FP,
+    };
+    cell_ref(register, rng.gen())
+}
+
+fn random_imm(rng: &mut StdRng) -> BigIntAsHex {
+    // Mostly small offsets and constants, but also values close to the field's prime.
+    match rng.gen_range(0..3) {
+        0 => imm(rng.gen_range(-1000..1000)),
+        1 => imm(rng.gen()),
+        _ => BigIntAsHex {
+            value: (Felt::MAX.to_bigint() / 2) - rng.gen_range(0..1000u32),
+        },
+    }
+}
+
+fn random_deref_or_imm(rng: &mut StdRng) -> DerefOrImmediate {
+    match rng.gen() {
+        true => DerefOrImmediate::Deref(random_cell_ref(rng)),
+        false => DerefOrImmediate::Immediate(random_imm(rng)),
+    }
+}
+
+fn random_res_operand(rng: &mut StdRng) -> ResOperand {
+    match rng.gen_range(0..4) {
+        0 => ResOperand::Deref(random_cell_ref(rng)),
+        1 => ResOperand::DoubleDeref(random_cell_ref(rng), rng.gen()),
+        2 => ResOperand::Immediate(random_imm(rng)),
+        _ => ResOperand::BinOp(BinOpOperand {
+            op: match rng.gen() {
+                true => Operation::Add,
+                false => Operation::Mul,
+            },
+            a: random_cell_ref(rng),
+            b: random_deref_or_imm(rng),
+        }),
+    }
+}
+
+fn random_instruction(rng: &mut StdRng) -> Instruction {
+    let (body, inc_ap) = match rng.gen_range(0..6) {
+        0 => (
+            InstructionBody::AddAp(AddApInstruction {
+                operand: random_res_operand(rng),
+            }),
+            false,
+        ),
+        1 => (
+            InstructionBody::AssertEq(AssertEqInstruction {
+                a: random_cell_ref(rng),
+                b: random_res_operand(rng),
+            }),
+            rng.gen(),
+        ),
+        2 => (
+            InstructionBody::Call(CallInstruction {
+                target: random_deref_or_imm(rng),
+                relative: rng.gen(),
+            }),
+            false,
+        ),
+        3 => (
+            InstructionBody::Jump(JumpInstruction {
+                target: random_deref_or_imm(rng),
+                relative: rng.gen(),
+            }),
+            rng.gen(),
+        ),
+        4 => (
+            InstructionBody::Jnz(JnzInstruction {
+                jump_offset: random_deref_or_imm(rng),
+                condition: random_cell_ref(rng),
+            }),
+            rng.gen(),
+        ),
+        _ => (InstructionBody::Ret(RetInstruction {}), false),
+    };
+
+    Instruction::new(body, inc_ap)
+}
+
+#[test]
+fn random_instructions() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..10_000 {
+        assert_round_trip(random_instruction(&mut rng));
+    }
+}
+
+#[test]
+fn every_operand_form() {
+    let registers = [Register::AP, Register::FP];
+    let offsets = [i16::MIN, -1, 0, 1, i16::MAX];
+
+    let mut cell_refs = Vec::new();
+    for register in registers {
+        for offset in offsets {
+            cell_refs.push(cell_ref(register, offset));
+        }
+    }
+
+    let mut targets = cell_refs
+        .iter()
+        .copied()
+        .map(DerefOrImmediate::Deref)
+        .collect::<Vec<_>>();
+    targets.extend([-1, 0, 1, 1 << 64].map(|x| DerefOrImmediate::Immediate(imm(x))));
+
+    let mut operands = Vec::new();
+    for a in &cell_refs {
+        operands.push(ResOperand::Deref(*a));
+        for offset in offsets {
+            operands.push(ResOperand::DoubleDeref(*a, offset));
+        }
+        for b in &targets {
+            for op in [Operation::Add, Operation::Mul] {
+                operands.push(ResOperand::BinOp(BinOpOperand {
+                    op,
+                    a: *a,
+                    b: b.clone(),
+                }));
+            }
+        }
+    }
+    operands.extend([-1, 0, 1, 1 << 64].map(|x| ResOperand::Immediate(imm(x))));
+
+    for operand in &operands {
+        assert_round_trip(Instruction::new(
+            InstructionBody::AddAp(AddApInstruction {
+                operand: operand.clone(),
+            }),
+            false,
+        ));
+        for a in &cell_refs {
+            for inc_ap in [false, true] {
+                assert_round_trip(Instruction::new(
+                    InstructionBody::AssertEq(AssertEqInstruction {
+                        a: *a,
+                        b: operand.clone(),
+                    }),
+                    inc_ap,
+                ));
+            }
+        }
+    }
+
+    for target in &targets {
+        for relative in [false, true] {
+            assert_round_trip(Instruction::new(
+                InstructionBody::Call(CallInstruction {
+                    target: target.clone(),
+                    relative,
+                }),
+                false,
+            ));
+            for inc_ap in [false, true] {
+                assert_round_trip(Instruction::new(
+                    InstructionBody::Jump(JumpInstruction {
+                        target: target.clone(),
+                        relative,
+                    }),
+                    inc_ap,
+                ));
+            }
+        }
+        for condition in &cell_refs {
+            for inc_ap in [false, true] {
+                assert_round_trip(Instruction::new(
+                    InstructionBody::Jnz(JnzInstruction {
+                        jump_offset: target.clone(),
+                        condition: *condition,
+                    }),
+                    inc_ap,
+                ));
+            }
+        }
+    }
+
+    assert_round_trip(Instruction::new(
+        InstructionBody::Ret(RetInstruction {}),
+        false,
+    ));
+}
@@ -0,0 +1,122 @@
+use cairo_lang_casm::casm;
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    search::{
+        shortest::{find_shortest_path, Unweighted},
+        NodeId, Predicates,
+    },
+    AccessKind, Builtin, BuiltinSegment, BuiltinSegments, Error, GraphMappings, RelocationTable,
+    StepId, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+mod common;
+
+const PUBLIC_INPUT: &str = r#"{
+    "layout": "all_cairo",
+    "n_steps": 3,
+    "memory_segments": {
+        "program": { "begin_addr": 1, "stop_ptr": 4 },
+        "execution": { "begin_addr": 30, "stop_ptr": 31 },
+        "pedersen": { "begin_addr": 50, "stop_ptr": 53 },
+        "range_check": { "begin_addr": 53, "stop_ptr": 60 }
+    },
+    "public_memory": []
+}"#;
+
+#[test]
+fn segments() {
+    let table = "1 10 50 53"
+        .parse::<RelocationTable>()
+        .unwrap()
+        .with_builtins(vec!["pedersen_builtin".into()]);
+    let builtins = BuiltinSegments::from_relocation_table(&table).unwrap();
+    assert_eq!(
+        builtins.iter().collect::<Vec<_>>(),
+        [&BuiltinSegment {
+            builtin: Builtin::Pedersen,
+            addrs: 50..53,
+        }]
+    );
+
+    let builtins = BuiltinSegments::from_public_input(PUBLIC_INPUT).unwrap();
+    let cell = |addr| builtins.cell(ValueId(addr)).map(|x| x.to_string());
+    assert_eq!(cell(49), None);
+    assert_eq!(cell(50).as_deref(), Some("pedersen #0 input 0"));
+    assert_eq!(cell(52).as_deref(), Some("pedersen #0 output 0"));
+    assert_eq!(cell(55).as_deref(), Some("range_check #2 cell 0"));
+    assert_eq!(cell(60), None);
+
+    assert!(matches!(
+        "1 10 50"
+            .parse::<RelocationTable>()
+            .map(|x| x.with_builtins(vec!["hash".into()]))
+            .map(|x| BuiltinSegments::from_relocation_table(&x)),
+        Ok(Err(Error::UnknownBuiltin(name))) if name == "hash"
+    ));
+}
+
+/// Steps:
+///   0. `[30] = [27] + 1`.
+///   1. `[30] = [50]`, which writes the first input of a pedersen instance.
+///   2. `[31] = [52]`, which reads its output, through a different pointer to the instance.
+#[test]
+fn pedersen_path() {
+    let program = assemble(casm! {
+        [ap + 0] = [fp + -3] + 1, ap++;
+        [ap + -1] = [[fp + -4]];
+        [ap + 0] = [[fp + -5] + 2], ap++;
+    });
+    let cells = [
+        (25, 50),
+        (26, 50),
+        (27, 4),
+        (30, 5),
+        (31, 99),
+        (50, 5),
+        (51, 7),
+        (52, 99),
+    ]
+    .map(|(addr, value)| (addr, Felt::from(value)))
+    .to_vec();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[entry(1, 30, 30), entry(3, 31, 30), entry(4, 31, 30)]);
+    let builtins = BuiltinSegments::new(vec![BuiltinSegment {
+        builtin: Builtin::Pedersen,
+        addrs: 50..53,
+    }]);
+
+    let find_path = |mappings: &GraphMappings| {
+        find_shortest_path(
+            &memory,
+            &trace,
+            mappings,
+            ValueId(27),
+            ValueId(31),
+            Predicates::new(),
+            Unweighted,
+        )
+    };
+
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+    assert_eq!(find_path(&mappings), None);
+
+    let mappings = mappings.with_builtins(&memory, &builtins);
+    let kind = |addr| mappings.access_kind(StepId(2), ValueId(addr));
+    assert_eq!(kind(50), Some(AccessKind::Read));
+    assert_eq!(kind(51), Some(AccessKind::Read));
+    assert_eq!(kind(52), Some(AccessKind::Write));
+    assert_eq!(
+        find_path(&mappings),
+        Some(vec![
+            NodeId::Value(ValueId(27)),
+            NodeId::Step(StepId(0)),
+            NodeId::Value(ValueId(30)),
+            NodeId::Step(StepId(1)),
+            NodeId::Value(ValueId(50)),
+            NodeId::Step(StepId(2)),
+            NodeId::Value(ValueId(31)),
+        ])
+    );
+}
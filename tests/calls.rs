@@ -0,0 +1,120 @@
+use cairo_lang_casm::casm;
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    calls::{CallTree, FrameId},
+    GraphMappings, StepId, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+mod common;
+
+/// Steps:
+///   0. `[30] = 3`.
+///   1. `[31] = 4`.
+///   2. `call rel 5`, which enters a frame at `fp = 34`.
+///   3. `[34] = [30] * [31]`.
+///   4. `ret`.
+///   5. `[35] = [34] + 1`.
+///   6. `ret`.
+fn run(steps: usize) -> (GraphMappings, CallTree) {
+    let program = assemble(casm! {
+        [ap + 0] = 3, ap++;
+        [ap + 0] = 4, ap++;
+        call rel 5;
+        [ap + 0] = [ap + -1] + 1, ap++;
+        ret;
+        [ap + 0] = [fp + -4] * [fp + -3], ap++;
+        ret;
+    });
+    let cells = [(30, 3), (31, 4), (32, 30), (33, 7), (34, 12), (35, 13)]
+        .map(|(addr, value)| (addr, Felt::from(value)));
+    let memory = build_memory(&[program, cells.to_vec()].concat());
+    let trace = build_trace(
+        &[
+            entry(1, 30, 30),
+            entry(3, 31, 30),
+            entry(5, 32, 30),
+            entry(10, 34, 34),
+            entry(11, 35, 34),
+            entry(7, 35, 30),
+            entry(9, 36, 30),
+        ][..steps],
+    );
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    let tree = CallTree::new(&memory, &trace, &mappings);
+    (mappings, tree)
+}
+
+#[test]
+fn frames() {
+    let (_, tree) = run(7);
+    assert_eq!(tree.frames.len(), 2);
+
+    let root = tree.frame(FrameId(0));
+    assert_eq!(root.parent, None);
+    assert_eq!(root.children, [FrameId(1)]);
+    assert_eq!((root.entry_pc, root.fp), (1, 30));
+    assert_eq!(root.call_step, None);
+    assert_eq!(
+        (root.entry_step, root.exit_step),
+        (StepId(0), Some(StepId(6)))
+    );
+    assert!(root.arguments.is_empty());
+    assert!(root.return_values.is_empty());
+
+    let callee = tree.frame(FrameId(1));
+    assert_eq!(callee.parent, Some(FrameId(0)));
+    assert!(callee.children.is_empty());
+    assert_eq!((callee.entry_pc, callee.fp), (10, 34));
+    assert_eq!(callee.call_step, Some(StepId(2)));
+    assert_eq!(
+        (callee.entry_step, callee.exit_step),
+        (StepId(3), Some(StepId(4)))
+    );
+    assert_eq!(callee.arguments, [ValueId(30), ValueId(31)]);
+    assert_eq!(callee.return_values, [ValueId(34)]);
+
+    assert_eq!(
+        tree.backtrace(FrameId(1)).collect::<Vec<_>>(),
+        [FrameId(1), FrameId(0)]
+    );
+}
+
+#[test]
+fn attribution() {
+    let (mappings, tree) = run(7);
+
+    assert_eq!(
+        (0..8)
+            .map(|x| tree.step_frame(StepId(x)))
+            .collect::<Vec<_>>(),
+        [
+            Some(FrameId(0)),
+            Some(FrameId(0)),
+            Some(FrameId(0)),
+            Some(FrameId(1)),
+            Some(FrameId(1)),
+            Some(FrameId(0)),
+            Some(FrameId(0)),
+            None,
+        ]
+    );
+    assert_eq!(tree.value_frame(&mappings, ValueId(30)), Some(FrameId(0)));
+    assert_eq!(tree.value_frame(&mappings, ValueId(34)), Some(FrameId(1)));
+    assert_eq!(tree.value_frame(&mappings, ValueId(35)), Some(FrameId(0)));
+    assert_eq!(tree.value_frame(&mappings, ValueId(40)), None);
+}
+
+#[test]
+fn unfinished_call() {
+    let (_, tree) = run(4);
+    assert_eq!(tree.frames.len(), 2);
+
+    let callee = tree.frame(FrameId(1));
+    assert_eq!(callee.exit_step, None);
+    assert_eq!(callee.arguments, [ValueId(30), ValueId(31)]);
+    assert!(callee.return_values.is_empty());
+    assert_eq!(tree.frame(FrameId(0)).exit_step, None);
+}
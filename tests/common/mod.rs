@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+use bincode::de::read::SliceReader;
+use cairo_lang_casm::inline::CasmContext;
+use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
+use sierra2casm_dbg::{Memory, Trace};
+use starknet_types_core::felt::Felt;
+
+/// Encode memory cells using the same format as the relocated memory dumps.
+pub fn encode_memory(cells: &[(usize, Felt)]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(cells.len() * 40);
+    for (addr, value) in cells {
+        data.extend((*addr as u64).to_le_bytes());
+        data.extend(value.to_bytes_le());
+    }
+    data
+}
+
+/// Encode trace entries using the same format as the relocated trace dumps.
+pub fn encode_trace(entries: &[RelocatedTraceEntry]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(entries.len() * 24);
+    for entry in entries {
+        data.extend((entry.ap as u64).to_le_bytes());
+        data.extend((entry.fp as u64).to_le_bytes());
+        data.extend((entry.pc as u64).to_le_bytes());
+    }
+    data
+}
+
+pub fn build_memory(cells: &[(usize, Felt)]) -> Memory {
+    Memory::decode(SliceReader::new(&encode_memory(cells)))
+}
+
+pub fn build_trace(entries: &[RelocatedTraceEntry]) -> Trace {
+    Trace::decode(SliceReader::new(&encode_trace(entries)))
+}
+
+pub fn entry(pc: usize, ap: usize, fp: usize) -> RelocatedTraceEntry {
+    RelocatedTraceEntry { pc, ap, fp }
+}
+
+/// Encoding of `ret`.
+pub const RET: u64 = 0x208b7fff7fff7ffe;
+
+/// Encoding of `jmp rel <imm>`, which doesn't access any memory cell.
+pub const JMP_REL: u64 = 0x010780017fff7fff;
+
+/// Assemble a program into memory cells, starting at address `1`.
+pub fn assemble(ctx: CasmContext) -> Vec<(usize, Felt)> {
+    ctx.instructions
+        .iter()
+        .flat_map(|x| x.assemble().encode())
+        .enumerate()
+        .map(|(offset, value)| (offset + 1, Felt::from(&value)))
+        .collect()
+}
@@ -0,0 +1,205 @@
+use bincode::de::read::SliceReader;
+use cairo_lang_casm::casm;
+use common::{assemble, build_memory, encode_memory, encode_trace, entry};
+use sierra2casm_dbg::{
+    disassemble, try_decode_any_instruction, try_decode_instruction, AnyInstruction, Error, Memory,
+    Trace,
+};
+use starknet_types_core::felt::Felt;
+use std::{env, fs, path::PathBuf};
+
+mod common;
+
+#[test]
+fn truncated_memory() {
+    let data = encode_memory(&[(1, Felt::ONE), (2, Felt::TWO)]);
+    let result = Memory::try_decode(SliceReader::new(&data[..data.len() - 3]));
+    assert!(matches!(result, Err(Error::TruncatedRecord { offset: 40 })));
+}
+
+#[test]
+fn truncated_trace() {
+    let data = encode_trace(&[entry(1, 2, 3), entry(4, 5, 6)]);
+    let result = Trace::try_decode(SliceReader::new(&data[..data.len() - 12]));
+    assert!(matches!(result, Err(Error::TruncatedRecord { offset: 24 })));
+}
+
+#[test]
+fn duplicate_cell() {
+    let data = encode_memory(&[(1, Felt::ONE), (3, Felt::TWO), (1, Felt::THREE)]);
+    let result = Memory::try_decode(SliceReader::new(&data));
+    assert!(matches!(result, Err(Error::DuplicateCell { addr: 1 })));
+}
+
+#[test]
+fn uninitialized_cell() {
+    let memory = build_memory(&[(1, Felt::ONE)]);
+    let result = try_decode_instruction(&memory, 5);
+    assert!(matches!(result, Err(Error::UninitializedCell { addr: 5 })));
+}
+
+#[test]
+fn unknown_instruction_encoding() {
+    // Not a valid instruction: the highest bit must be zero.
+    let raw = Felt::from(1u128 << 63);
+    let memory = build_memory(&[(1, raw)]);
+    let result = try_decode_instruction(&memory, 1);
+    assert!(matches!(result, Err(Error::UnknownInstructionEncoding { pc: 1, raw: x }) if x == raw));
+}
+
+#[test]
+fn missing_immediate() {
+    let program = assemble(casm! { [ap + 0] = 5, ap++; });
+    let memory = build_memory(&program[..1]);
+    let result = try_decode_instruction(&memory, 1);
+    assert!(matches!(result, Err(Error::MissingImmediate { pc: 1 })));
+}
+
+#[test]
+fn listing() {
+    let mut program = assemble(casm! {
+        [ap + 0] = 5, ap++;
+        jmp rel -2;
+        call abs 1;
+        ret;
+    });
+    // A data cell after the code, followed by a gap.
+    program.push((8, Felt::from(1u128 << 63)));
+    program.push((10, Felt::ONE));
+    let memory = build_memory(&program);
+
+    let listing = disassemble(&memory, 1..100).collect::<Vec<_>>();
+    assert_eq!(
+        listing
+            .iter()
+            .map(|x| (x.pc, x.raw.len()))
+            .collect::<Vec<_>>(),
+        [(1, 2), (3, 2), (5, 2), (7, 1), (8, 1)]
+    );
+    assert_eq!(
+        listing.iter().map(|x| x.target()).collect::<Vec<_>>(),
+        [None, Some(1), Some(1), None, None]
+    );
+    assert_eq!(
+        listing[1].instruction.as_ref().unwrap().to_string(),
+        "jmp rel -2"
+    );
+    assert!(listing[4].instruction.is_none());
+
+    assert_eq!(disassemble(&memory, 3..6).count(), 2);
+}
+
+/// Replace the `ap_update` flags of an encoded instruction.
+fn with_ap_update(raw: Felt, ap_update: u64) -> Felt {
+    let raw = u64::try_from(raw).unwrap();
+    Felt::from((raw & !(3 << 58)) | (ap_update << 58))
+}
+
+#[test]
+fn generic_instructions() {
+    let program = assemble(casm! {
+        [ap + 0] = [fp + -3], ap++;
+        ret;
+        [ap + 0] = 5;
+    });
+    let memory = build_memory(&[
+        (1, with_ap_update(program[0].1, 1)),
+        (2, with_ap_update(program[1].1, 2)),
+        (3, with_ap_update(program[2].1, 1)),
+        program[3],
+    ]);
+
+    // Not supported by the Sierra compiler's subset.
+    assert!(try_decode_instruction(&memory, 1).is_err());
+
+    let decoded = (1..4)
+        .map(|pc| try_decode_any_instruction(&memory, pc).unwrap())
+        .collect::<Vec<_>>();
+    assert!(decoded
+        .iter()
+        .all(|x| matches!(x, AnyInstruction::Generic(_))));
+    assert_eq!(
+        decoded.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+        [
+            "[ap + 0] = [fp + -3], ap += [fp + -3]",
+            "ret, ap++",
+            "[ap + 0] = 5, ap += 5",
+        ]
+    );
+    assert_eq!(decoded[2].size(), 2);
+
+    // Canonical instructions are still represented as CASM.
+    let memory = build_memory(&program);
+    assert!(matches!(
+        try_decode_any_instruction(&memory, 1),
+        Ok(AnyInstruction::Casm(_))
+    ));
+}
+
+fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+    let path = env::temp_dir().join(format!("sierra2casm-dbg-{}-{name}", std::process::id()));
+    fs::write(&path, data).unwrap();
+    path
+}
+
+#[test]
+fn mapped_memory_matches_decoded() {
+    // Unsorted records with holes between runs.
+    let data = encode_memory(&[
+        (10, Felt::from(10)),
+        (11, Felt::from(11)),
+        (1, Felt::ONE),
+        (2, Felt::TWO),
+        (5, Felt::from(5)),
+    ]);
+    let path = temp_file("memory.bin", &data);
+
+    let decoded = Memory::decode(SliceReader::new(&data));
+    let mapped = Memory::map_file(&path).unwrap();
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(mapped.len(), 12);
+    assert_eq!(
+        mapped.iter().collect::<Vec<_>>(),
+        decoded.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        decoded.iter().map(|x| x.0).collect::<Vec<_>>(),
+        [1, 2, 5, 10, 11]
+    );
+    for addr in 0..13 {
+        assert_eq!(mapped.get(addr), decoded.get(addr));
+    }
+    assert_eq!(mapped.get(3), None);
+    assert_eq!(mapped.get(11), Some(Felt::from(11)));
+}
+
+#[test]
+fn mapped_trace_matches_decoded() {
+    let data = encode_trace(&[entry(1, 2, 3), entry(4, 5, 6)]);
+    let path = temp_file("trace.bin", &data);
+
+    let mapped = Trace::map_file(&path).unwrap();
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(mapped.len(), 2);
+    assert_eq!(
+        mapped
+            .iter()
+            .map(|x| (x.pc, x.ap, x.fp))
+            .collect::<Vec<_>>(),
+        [(1, 2, 3), (4, 5, 6)]
+    );
+    assert!(mapped.get(2).is_none());
+}
+
+#[test]
+fn truncated_mapped_memory() {
+    let data = encode_memory(&[(1, Felt::ONE), (2, Felt::TWO)]);
+    let path = temp_file("truncated-memory.bin", &data[..data.len() - 3]);
+
+    let result = Memory::map_file(&path);
+    fs::remove_file(path).unwrap();
+
+    assert!(matches!(result, Err(Error::TruncatedRecord { offset: 40 })));
+}
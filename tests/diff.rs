@@ -0,0 +1,168 @@
+use cairo_lang_casm::casm;
+use common::{assemble, build_memory, build_trace, entry};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sierra2casm_dbg::{
+    diff::{align, diff_executions, DiffOp, Execution},
+    search::NodeId,
+    GraphMappings, Memory, StepId, Trace, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+mod common;
+
+/// Check that an alignment is valid and return the length of its common subsequence.
+fn check_alignment(left: &[u8], right: &[u8], ops: &[DiffOp]) -> usize {
+    let (mut x, mut y, mut common) = (0, 0, 0);
+    for op in ops {
+        match *op {
+            DiffOp::Equal(a, b) => {
+                assert_eq!((a.0, b.0), (x, y));
+                assert_eq!(left[x], right[y]);
+                (x, y, common) = (x + 1, y + 1, common + 1);
+            }
+            DiffOp::Removed(a) => {
+                assert_eq!(a.0, x);
+                x += 1;
+            }
+            DiffOp::Inserted(b) => {
+                assert_eq!(b.0, y);
+                y += 1;
+            }
+        }
+    }
+    assert_eq!((x, y), (left.len(), right.len()));
+    common
+}
+
+fn lcs_length(left: &[u8], right: &[u8]) -> usize {
+    let mut lengths = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for (i, a) in left.iter().enumerate() {
+        for (j, b) in right.iter().enumerate() {
+            lengths[i + 1][j + 1] = match a == b {
+                true => lengths[i][j] + 1,
+                false => lengths[i][j + 1].max(lengths[i + 1][j]),
+            };
+        }
+    }
+    lengths[left.len()][right.len()]
+}
+
+#[test]
+fn alignment() {
+    assert_eq!(
+        align(&[1, 2, 3, 4], &[1, 2, 9, 3, 4]),
+        [
+            DiffOp::Equal(StepId(0), StepId(0)),
+            DiffOp::Equal(StepId(1), StepId(1)),
+            DiffOp::Inserted(StepId(2)),
+            DiffOp::Equal(StepId(2), StepId(3)),
+            DiffOp::Equal(StepId(3), StepId(4)),
+        ]
+    );
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..1000 {
+        let sequence = |rng: &mut StdRng| {
+            let len = rng.gen_range(0..20);
+            (0..len).map(|_| rng.gen_range(0..4)).collect::<Vec<u8>>()
+        };
+        let (left, right) = (sequence(&mut rng), sequence(&mut rng));
+
+        let ops = align(&left, &right);
+        assert_eq!(
+            check_alignment(&left, &right, &ops),
+            lcs_length(&left, &right)
+        );
+    }
+}
+
+/// Steps:
+///   0. `[30] = [27] + 1`.
+///   1. `[31] = [30] * 2`.
+///   2. `[32] = [31] + 3`, only in the second execution.
+fn execution(argument: u64, steps: usize) -> (Memory, Trace) {
+    let program = assemble(casm! {
+        [ap + 0] = [fp + -3] + 1, ap++;
+        [ap + 0] = [ap + -1] * 2, ap++;
+        [ap + 0] = [ap + -1] + 3, ap++;
+    });
+    let cells = [
+        (27, argument),
+        (30, argument + 1),
+        (31, 2 * argument + 2),
+        (32, 2 * argument + 5),
+    ]
+    .map(|(addr, value)| (addr, Felt::from(value)))
+    .to_vec();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[entry(1, 30, 30), entry(3, 31, 30), entry(5, 32, 30)][..steps]);
+
+    (memory, trace)
+}
+
+#[test]
+fn divergence() {
+    let (left_memory, left_trace) = execution(4, 2);
+    let (right_memory, right_trace) = execution(6, 3);
+    let left = Execution {
+        memory: &left_memory,
+        trace: &left_trace,
+        program_offset: 1,
+    };
+    let right = Execution {
+        memory: &right_memory,
+        trace: &right_trace,
+        program_offset: 1,
+    };
+
+    let diff = diff_executions(&left, &right);
+    assert_eq!(
+        diff.ops,
+        [
+            DiffOp::Equal(StepId(0), StepId(0)),
+            DiffOp::Equal(StepId(1), StepId(1)),
+            DiffOp::Inserted(StepId(2)),
+        ]
+    );
+    assert_eq!(
+        diff.first_divergence,
+        Some(DiffOp::Equal(StepId(0), StepId(0)))
+    );
+    assert_eq!(
+        diff.cells
+            .iter()
+            .map(|x| (x.left_step.0, x.left.0, x.left_value, x.right_value))
+            .collect::<Vec<_>>(),
+        [
+            (0, 30, Some(Felt::from(5)), Some(Felt::from(7))),
+            (0, 27, Some(Felt::from(4)), Some(Felt::from(6))),
+            (1, 31, Some(Felt::from(10)), Some(Felt::from(14))),
+        ]
+    );
+    assert_eq!(diff.counterpart(ValueId(31)), Some(ValueId(31)));
+
+    let mappings = GraphMappings::new(&left_memory, &left_trace, &HashMap::new(), 1);
+    assert_eq!(
+        diff.divergence_path(&mappings),
+        Some(vec![
+            NodeId::Value(ValueId(27)),
+            NodeId::Step(StepId(0)),
+            NodeId::Value(ValueId(30)),
+        ])
+    );
+}
+
+#[test]
+fn identical() {
+    let (memory, trace) = execution(4, 3);
+    let execution = Execution {
+        memory: &memory,
+        trace: &trace,
+        program_offset: 1,
+    };
+
+    let diff = diff_executions(&execution, &execution);
+    assert!(diff.is_empty());
+    assert!(diff.cells.is_empty());
+}
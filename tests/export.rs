@@ -0,0 +1,132 @@
+use cairo_lang_casm::casm;
+use common::{assemble, build_memory, build_trace, entry};
+use serde_json::json;
+use sierra2casm_dbg::{
+    export::{to_dot, JsonGraph, JsonNode, Subgraph},
+    search::NodeId,
+    GraphMappings, Memory, StepId, Trace, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+mod common;
+
+/// Step 0 computes `[10] = [9] + [21]` and step 1 computes `[11] = [10] + [21]`.
+fn build_graph() -> (Memory, Trace, GraphMappings) {
+    let program = assemble(casm! { [ap + 0] = [ap + -1] + [fp + -3], ap++; });
+    let cells = [9, 10, 11, 21]
+        .map(|addr| (addr, Felt::from(addr)))
+        .to_vec();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[entry(1, 10, 24), entry(1, 11, 24)]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    (memory, trace, mappings)
+}
+
+#[test]
+fn neighborhood() {
+    let (_, _, mappings) = build_graph();
+
+    let graph = Subgraph::neighborhood(&mappings, NodeId::Value(ValueId(9)), 1);
+    assert_eq!(
+        graph.nodes.into_iter().collect::<Vec<_>>(),
+        [NodeId::Step(StepId(0)), NodeId::Value(ValueId(9))]
+    );
+
+    // `[11]` is 4 edges away from `[9]`.
+    let graph = Subgraph::neighborhood(&mappings, NodeId::Value(ValueId(9)), 3);
+    assert!(!graph.nodes.contains(&NodeId::Value(ValueId(11))));
+    assert_eq!(graph.nodes.len(), 5);
+}
+
+#[test]
+fn step_window() {
+    let (_, _, mappings) = build_graph();
+
+    let graph = Subgraph::step_window(&mappings, 1..5);
+    assert_eq!(
+        graph.nodes.into_iter().collect::<Vec<_>>(),
+        [
+            NodeId::Step(StepId(1)),
+            NodeId::Value(ValueId(10)),
+            NodeId::Value(ValueId(11)),
+            NodeId::Value(ValueId(21)),
+        ]
+    );
+}
+
+#[test]
+fn dot() {
+    let (memory, trace, mappings) = build_graph();
+    let path = vec![
+        NodeId::Value(ValueId(10)),
+        NodeId::Step(StepId(1)),
+        NodeId::Value(ValueId(11)),
+    ];
+
+    let graph = Subgraph::step_window(&mappings, 1..2);
+    let dot = to_dot(&memory, &trace, &mappings, &graph, &[path]);
+    assert!(dot.starts_with("digraph {\n"));
+    assert!(dot.contains(
+        "  s1 [shape=box, label=\"step 1 (pc = 1)\\n[ap + 0] = [ap + -1] + [fp + -3], ap++\"];\n"
+    ));
+    assert!(dot.contains("  v21 [shape=ellipse, label=\"[21] = 21\"];\n"));
+    assert!(dot.contains("  v10 -> s1 [color=red, penwidth=2];\n"));
+    assert!(dot.contains("  s1 -> v11 [style=dashed, color=red, penwidth=2];\n"));
+    assert!(dot.contains("  v21 -> s1;\n"));
+}
+
+#[test]
+fn json() {
+    let (memory, trace, mappings) = build_graph();
+    let path = vec![
+        NodeId::Value(ValueId(10)),
+        NodeId::Step(StepId(1)),
+        NodeId::Value(ValueId(11)),
+    ];
+
+    let graph = Subgraph::from_paths([path.as_slice()]);
+    let json =
+        serde_json::to_value(JsonGraph::new(&memory, &trace, &mappings, &graph, &[path])).unwrap();
+    assert_eq!(
+        json,
+        json!({
+            "nodes": [
+                {
+                    "kind": "step",
+                    "id": 1,
+                    "pc": 1,
+                    "ap": 11,
+                    "fp": 24,
+                    "instruction": "[ap + 0] = [ap + -1] + [fp + -3], ap++",
+                },
+                { "kind": "value", "address": 10, "value": "10" },
+                { "kind": "value", "address": 11, "value": "11" },
+            ],
+            "edges": [
+                { "step": 0, "value": 1, "access": "read" },
+                { "step": 0, "value": 2, "access": "assert" },
+            ],
+            "paths": [[1, 0, 2]],
+        })
+    );
+}
+
+#[test]
+fn json_missing_step() {
+    let (memory, trace, _) = build_graph();
+
+    let node = JsonNode::new(&memory, &trace, NodeId::Step(StepId(5)));
+    assert_eq!(
+        serde_json::to_value(node).unwrap(),
+        json!({
+            "kind": "step",
+            "id": 5,
+            "pc": null,
+            "ap": null,
+            "fp": null,
+            "instruction": null,
+        })
+    );
+}
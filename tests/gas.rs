@@ -0,0 +1,178 @@
+use cairo_lang_casm::{
+    casm,
+    hints::{Hint, StarknetHint},
+    operand::{CellRef, Register, ResOperand},
+};
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    calls::CallTree,
+    search::gas::{find_gas_argument, track_gas, GasChange},
+    GraphMappings, Memory, SierraMapping, StepId, Trace, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+mod common;
+
+const PROGRAM: &str = "
+    type RangeCheck = RangeCheck;
+    type GasBuiltin = GasBuiltin;
+
+    libfunc withdraw_gas = withdraw_gas;
+
+    withdraw_gas(rc, gas) -> (rc, gas);
+    return(rc, gas);
+
+    foo::main@0(rc: RangeCheck, gas: GasBuiltin) -> (RangeCheck, GasBuiltin);
+";
+
+const DEBUG_INFO: &str = r#"[
+    { "start_offset": 0, "end_offset": 8 },
+    { "start_offset": 9, "end_offset": 8 }
+]"#;
+
+/// The range check pointer is at `[26] = 100` and the gas counter at `[27] = 1000`.
+///
+/// Steps:
+///   0. `[27] = [30] + 10`, which withdraws 10 gas.
+///   1. `[30] = [[26]]`, which copies the gas counter into a range check cell.
+///   2. `[31] = [30] + 5`, which redeposits 5 gas.
+///   3. `[32] = [31] + [40]`, which offsets the gas counter by `2 ** 128`.
+///   4. `[33] = [31]`, which copies the gas counter.
+fn run() -> (Memory, Trace, GraphMappings) {
+    let program = assemble(casm! {
+        [fp + -3] = [ap + 0] + 10, ap++;
+        [ap + -1] = [[fp + -4]];
+        [ap + 0] = [ap + -1] + 5, ap++;
+        [ap + 0] = [ap + -1] + [fp + 10], ap++;
+        [ap + 0] = [ap + -2], ap++;
+    });
+    let offset = Felt::TWO.pow(128u32);
+    let mut cells = [
+        (26, 100),
+        (27, 1000),
+        (30, 990),
+        (31, 995),
+        (33, 995),
+        (100, 990),
+    ]
+    .map(|(addr, value)| (addr, Felt::from(value)))
+    .to_vec();
+    cells.extend([(32, Felt::from(995) + offset), (40, offset)]);
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[
+        entry(1, 30, 30),
+        entry(3, 31, 30),
+        entry(4, 31, 30),
+        entry(6, 32, 30),
+        entry(7, 33, 30),
+    ]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    (memory, trace, mappings)
+}
+
+#[test]
+fn timeline() {
+    let (memory, trace, mappings) = run();
+    let timeline = track_gas(&memory, &trace, &mappings, &HashMap::new(), 1, ValueId(27));
+
+    let change = |step, value, gas: u64, delta| GasChange {
+        step: StepId(step),
+        value: ValueId(value),
+        gas: Felt::from(gas),
+        delta,
+    };
+    assert_eq!(
+        timeline.changes,
+        [
+            change(0, 30, 990, -10),
+            change(1, 100, 990, 0),
+            change(2, 31, 995, 5),
+            change(4, 33, 995, 0),
+        ]
+    );
+    assert_eq!(
+        timeline.updates().map(|x| x.delta).collect::<Vec<_>>(),
+        [-10, 5]
+    );
+
+    assert_eq!(timeline.gas_at(&memory, StepId(0)), Some(Felt::from(1000)));
+    assert_eq!(timeline.gas_at(&memory, StepId(1)), Some(Felt::from(990)));
+    assert_eq!(timeline.gas_at(&memory, StepId(3)), Some(Felt::from(995)));
+}
+
+/// The system pointer is at `[26] = 50` and the gas counter at `[27] = 1000`.
+///
+/// Steps:
+///   0. `[27] = [30] + 10`, which withdraws 10 gas.
+///   1. `[30] = [[26] + 1]`, which copies the gas counter into the request of a syscall.
+///   2. `ap += 1`, whose `StorageRead` syscall responds with 900 gas left at `[54]`.
+///   3. `[32] = [[26] + 4]`, which copies the remaining gas.
+#[test]
+fn syscall() {
+    let program = assemble(casm! {
+        [fp + -3] = [ap + 0] + 10, ap++;
+        [ap + -1] = [[fp + -4] + 1];
+        ap += 1;
+        [ap + 0] = [[fp + -4] + 4], ap++;
+    });
+    let mut cells = [
+        (26, 50),
+        (27, 1000),
+        (30, 990),
+        (32, 900),
+        (51, 990),
+        (52, 0),
+        (53, 7),
+        (54, 900),
+        (55, 0),
+        (56, 42),
+    ]
+    .map(|(addr, value)| (addr, Felt::from(value)))
+    .to_vec();
+    cells.push((50, Felt::from_bytes_be_slice(b"StorageRead")));
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[
+        entry(1, 30, 30),
+        entry(3, 31, 30),
+        entry(4, 31, 30),
+        entry(6, 32, 30),
+    ]);
+    let hints = HashMap::from([(
+        3,
+        vec![Hint::Starknet(StarknetHint::SystemCall {
+            system: ResOperand::Deref(CellRef {
+                register: Register::FP,
+                offset: -4,
+            }),
+        })],
+    )]);
+    let mappings = GraphMappings::new(&memory, &trace, &hints, 1);
+    let timeline = track_gas(&memory, &trace, &mappings, &hints, 1, ValueId(27));
+
+    let change = |step, value, gas: u64, delta| GasChange {
+        step: StepId(step),
+        value: ValueId(value),
+        gas: Felt::from(gas),
+        delta,
+    };
+    assert_eq!(
+        timeline.changes,
+        [
+            change(0, 30, 990, -10),
+            change(1, 51, 990, 0),
+            change(2, 54, 900, -90),
+            change(3, 32, 900, 0),
+        ]
+    );
+}
+
+#[test]
+fn gas_argument() {
+    let (memory, trace, mappings) = run();
+    let tree = CallTree::new(&memory, &trace, &mappings);
+    let sierra = SierraMapping::parse(PROGRAM, DEBUG_INFO).unwrap();
+
+    assert_eq!(find_gas_argument(&tree, &sierra, 1), Some(ValueId(27)));
+}
@@ -0,0 +1,779 @@
+use cairo_lang_casm::{
+    hints::{CoreHint, CoreHintBase, DeprecatedHint, ExternalHint, Hint, StarknetHint},
+    operand::{BinOpOperand, CellRef, DerefOrImmediate, Operation, Register, ResOperand},
+};
+use cairo_lang_utils::bigint::BigIntAsHex;
+use common::{build_memory, build_trace, entry, JMP_REL};
+use sierra2casm_dbg::{
+    AccessKind::{self, Read, Write},
+    GraphMappings, StepId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::{BTreeMap, HashMap};
+
+mod common;
+
+const AP: usize = 100;
+const FP: usize = 200;
+
+/// Run a single `jmp rel 0` step with `ap = 100` and `fp = 200` with the hint attached, and return
+/// the cells it accesses.
+fn hint_accesses(cells: &[(usize, u64)], hint: impl Into<Hint>) -> BTreeMap<usize, AccessKind> {
+    let cells = cells
+        .iter()
+        .map(|(addr, value)| (*addr, Felt::from(*value)))
+        .collect::<Vec<_>>();
+    felt_hint_accesses(&cells, hint)
+}
+
+fn felt_hint_accesses(
+    cells: &[(usize, Felt)],
+    hint: impl Into<Hint>,
+) -> BTreeMap<usize, AccessKind> {
+    let mut memory = vec![(1, Felt::from(JMP_REL)), (2, Felt::ZERO)];
+    memory.extend_from_slice(cells);
+
+    let memory = build_memory(&memory);
+    let trace = build_trace(&[entry(1, AP, FP)]);
+    let hints = HashMap::from([(0, vec![hint.into()])]);
+
+    let mappings = GraphMappings::new(&memory, &trace, &hints, 1);
+    mappings
+        .step2value()
+        .get(&StepId(0))
+        .into_iter()
+        .flatten()
+        .map(|x| (x.0, mappings.access_kind(StepId(0), *x).unwrap()))
+        .collect()
+}
+
+fn deprecated(hint: DeprecatedHint) -> Hint {
+    Hint::Core(CoreHintBase::Deprecated(hint))
+}
+
+fn ap(offset: i16) -> CellRef {
+    CellRef {
+        register: Register::AP,
+        offset,
+    }
+}
+
+fn fp(offset: i16) -> CellRef {
+    CellRef {
+        register: Register::FP,
+        offset,
+    }
+}
+
+fn deref(cell_ref: CellRef) -> ResOperand {
+    ResOperand::Deref(cell_ref)
+}
+
+fn imm(value: i64) -> ResOperand {
+    ResOperand::Immediate(BigIntAsHex {
+        value: value.into(),
+    })
+}
+
+fn accesses<const N: usize>(x: [(usize, AccessKind); N]) -> BTreeMap<usize, AccessKind> {
+    BTreeMap::from(x)
+}
+
+#[test]
+fn double_deref_operand() {
+    let hint = CoreHint::SquareRoot {
+        value: ResOperand::DoubleDeref(fp(-3), 2),
+        dst: ap(0),
+    };
+    assert_eq!(
+        hint_accesses(&[(197, 300)], hint),
+        accesses([(100, Write), (197, Read), (302, Read)]),
+    );
+}
+
+#[test]
+fn bin_op_operand() {
+    let hint = CoreHint::SquareRoot {
+        value: ResOperand::BinOp(BinOpOperand {
+            op: Operation::Add,
+            a: fp(-3),
+            b: DerefOrImmediate::Deref(ap(-1)),
+        }),
+        dst: ap(0),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(99, Read), (100, Write), (197, Read)]),
+    );
+}
+
+#[test]
+fn bin_op_operand_as_address() {
+    let hint = CoreHint::GetCurrentAccessIndex {
+        range_check_ptr: ResOperand::BinOp(BinOpOperand {
+            op: Operation::Add,
+            a: fp(-3),
+            b: DerefOrImmediate::Immediate(BigIntAsHex { value: 2.into() }),
+        }),
+    };
+    assert_eq!(
+        hint_accesses(&[(197, 300)], hint),
+        accesses([(197, Read), (302, Write)]),
+    );
+}
+
+#[test]
+fn alloc_segment() {
+    let hint = CoreHint::AllocSegment { dst: ap(0) };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
+
+#[test]
+fn test_less_than() {
+    let hint = CoreHint::TestLessThan {
+        lhs: deref(fp(-3)),
+        rhs: imm(5),
+        dst: ap(0),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (197, Read)]),
+    );
+}
+
+#[test]
+fn test_less_than_or_equal() {
+    let hint = CoreHint::TestLessThanOrEqual {
+        lhs: deref(fp(-3)),
+        rhs: deref(fp(-4)),
+        dst: ap(0),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (196, Read), (197, Read)]),
+    );
+}
+
+#[test]
+fn test_less_than_or_equal_address() {
+    let hint = CoreHint::TestLessThanOrEqualAddress {
+        lhs: deref(fp(-3)),
+        rhs: deref(fp(-4)),
+        dst: ap(0),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (196, Read), (197, Read)]),
+    );
+}
+
+#[test]
+fn wide_mul_128() {
+    let hint = CoreHint::WideMul128 {
+        lhs: deref(fp(-3)),
+        rhs: deref(fp(-4)),
+        high: ap(0),
+        low: ap(1),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (101, Write), (196, Read), (197, Read)]),
+    );
+}
+
+#[test]
+fn div_mod() {
+    let hint = CoreHint::DivMod {
+        lhs: deref(fp(-3)),
+        rhs: imm(10),
+        quotient: ap(0),
+        remainder: ap(1),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (101, Write), (197, Read)]),
+    );
+}
+
+#[test]
+fn uint256_div_mod() {
+    let hint = CoreHint::Uint256DivMod {
+        dividend0: deref(fp(-6)),
+        dividend1: deref(fp(-5)),
+        divisor0: deref(fp(-4)),
+        divisor1: deref(fp(-3)),
+        quotient0: ap(0),
+        quotient1: ap(1),
+        remainder0: ap(2),
+        remainder1: ap(3),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([
+            (100, Write),
+            (101, Write),
+            (102, Write),
+            (103, Write),
+            (194, Read),
+            (195, Read),
+            (196, Read),
+            (197, Read),
+        ]),
+    );
+}
+
+#[test]
+fn uint512_div_mod_by_uint256() {
+    let hint = CoreHint::Uint512DivModByUint256 {
+        dividend0: deref(fp(-8)),
+        dividend1: deref(fp(-7)),
+        dividend2: deref(fp(-6)),
+        dividend3: deref(fp(-5)),
+        divisor0: deref(fp(-4)),
+        divisor1: deref(fp(-3)),
+        quotient0: ap(0),
+        quotient1: ap(1),
+        quotient2: ap(2),
+        quotient3: ap(3),
+        remainder0: ap(4),
+        remainder1: ap(5),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([
+            (100, Write),
+            (101, Write),
+            (102, Write),
+            (103, Write),
+            (104, Write),
+            (105, Write),
+            (192, Read),
+            (193, Read),
+            (194, Read),
+            (195, Read),
+            (196, Read),
+            (197, Read),
+        ]),
+    );
+}
+
+#[test]
+fn square_root() {
+    let hint = CoreHint::SquareRoot {
+        value: deref(fp(-3)),
+        dst: ap(0),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (197, Read)]),
+    );
+}
+
+#[test]
+fn uint256_square_root() {
+    let hint = CoreHint::Uint256SquareRoot {
+        value_low: deref(fp(-4)),
+        value_high: deref(fp(-3)),
+        sqrt0: ap(0),
+        sqrt1: ap(1),
+        remainder_low: ap(2),
+        remainder_high: ap(3),
+        sqrt_mul_2_minus_remainder_ge_u128: ap(4),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([
+            (100, Write),
+            (101, Write),
+            (102, Write),
+            (103, Write),
+            (104, Write),
+            (196, Read),
+            (197, Read),
+        ]),
+    );
+}
+
+#[test]
+fn linear_split() {
+    let hint = CoreHint::LinearSplit {
+        value: deref(fp(-3)),
+        scalar: imm(7),
+        max_x: deref(fp(-4)),
+        x: ap(0),
+        y: ap(1),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (101, Write), (196, Read), (197, Read)]),
+    );
+}
+
+#[test]
+fn alloc_felt252_dict() {
+    // The arena pointer is 300, with 1 allocated dictionary and the infos segment at 400.
+    let hint = CoreHint::AllocFelt252Dict {
+        segment_arena_ptr: deref(fp(-3)),
+    };
+    assert_eq!(
+        hint_accesses(&[(197, 300), (298, 1), (297, 400)], hint),
+        accesses([(197, Read), (297, Read), (298, Read), (403, Write)]),
+    );
+}
+
+#[test]
+fn felt252_dict_entry_init() {
+    let hint = CoreHint::Felt252DictEntryInit {
+        dict_ptr: deref(fp(-3)),
+        key: deref(fp(-4)),
+    };
+    assert_eq!(
+        hint_accesses(&[(197, 300)], hint),
+        accesses([(196, Read), (197, Read), (301, Write)]),
+    );
+}
+
+#[test]
+fn felt252_dict_entry_update() {
+    let hint = CoreHint::Felt252DictEntryUpdate {
+        dict_ptr: deref(fp(-3)),
+        value: deref(fp(-4)),
+    };
+    assert_eq!(
+        hint_accesses(&[(197, 300)], hint),
+        accesses([(196, Read), (197, Read), (297, Read)]),
+    );
+}
+
+#[test]
+fn get_segment_arena_index() {
+    let hint = CoreHint::GetSegmentArenaIndex {
+        dict_end_ptr: deref(fp(-3)),
+        dict_index: ap(0),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (197, Read)]),
+    );
+}
+
+#[test]
+fn init_squash_data() {
+    let hint = CoreHint::InitSquashData {
+        dict_accesses: deref(fp(-3)),
+        ptr_diff: deref(fp(-4)),
+        n_accesses: imm(2),
+        big_keys: ap(0),
+        first_key: ap(1),
+    };
+    assert_eq!(
+        hint_accesses(&[(197, 300)], hint),
+        accesses([
+            (100, Write),
+            (101, Write),
+            (196, Read),
+            (197, Read),
+            (300, Read),
+            (303, Read),
+        ]),
+    );
+}
+
+#[test]
+fn get_current_access_index() {
+    let hint = CoreHint::GetCurrentAccessIndex {
+        range_check_ptr: deref(fp(-3)),
+    };
+    assert_eq!(
+        hint_accesses(&[(197, 300)], hint),
+        accesses([(197, Read), (300, Write)]),
+    );
+}
+
+#[test]
+fn should_skip_squash_loop() {
+    let hint = CoreHint::ShouldSkipSquashLoop {
+        should_skip_loop: ap(0),
+    };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
+
+#[test]
+fn get_current_access_delta() {
+    let hint = CoreHint::GetCurrentAccessDelta {
+        index_delta_minus1: ap(0),
+    };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
+
+#[test]
+fn should_continue_squash_loop() {
+    let hint = CoreHint::ShouldContinueSquashLoop {
+        should_continue: ap(0),
+    };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
+
+#[test]
+fn get_next_dict_key() {
+    let hint = CoreHint::GetNextDictKey { next_key: ap(0) };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
+
+#[test]
+fn assert_le_find_small_arcs() {
+    let hint = CoreHint::AssertLeFindSmallArcs {
+        range_check_ptr: deref(fp(-5)),
+        a: deref(fp(-4)),
+        b: deref(fp(-3)),
+    };
+    assert_eq!(
+        hint_accesses(&[(195, 300)], hint),
+        accesses([
+            (195, Read),
+            (196, Read),
+            (197, Read),
+            (300, Write),
+            (301, Write),
+            (302, Write),
+            (303, Write),
+        ]),
+    );
+}
+
+#[test]
+fn assert_le_is_first_arc_excluded() {
+    let hint = CoreHint::AssertLeIsFirstArcExcluded {
+        skip_exclude_a_flag: ap(0),
+    };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
+
+#[test]
+fn assert_le_is_second_arc_excluded() {
+    let hint = CoreHint::AssertLeIsSecondArcExcluded {
+        skip_exclude_b_minus_a: ap(0),
+    };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
+
+#[test]
+fn random_ec_point() {
+    let hint = CoreHint::RandomEcPoint { x: ap(0), y: ap(1) };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (101, Write)]),
+    );
+}
+
+#[test]
+fn field_sqrt() {
+    let hint = CoreHint::FieldSqrt {
+        val: deref(fp(-3)),
+        sqrt: ap(0),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (197, Read)]),
+    );
+}
+
+#[test]
+fn debug_print() {
+    let hint = CoreHint::DebugPrint {
+        start: deref(fp(-4)),
+        end: deref(fp(-3)),
+    };
+    assert_eq!(
+        hint_accesses(&[(196, 300), (197, 302)], hint),
+        accesses([(196, Read), (197, Read), (300, Read), (301, Read)]),
+    );
+}
+
+#[test]
+fn alloc_constant_size() {
+    let hint = CoreHint::AllocConstantSize {
+        size: imm(4),
+        dst: ap(0),
+    };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
+
+#[test]
+fn u256_inv_mod_n() {
+    let hint = CoreHint::U256InvModN {
+        b0: deref(fp(-6)),
+        b1: deref(fp(-5)),
+        n0: deref(fp(-4)),
+        n1: deref(fp(-3)),
+        g0_or_no_inv: ap(0),
+        g1_option: ap(1),
+        s_or_r0: ap(2),
+        s_or_r1: ap(3),
+        t_or_k0: ap(4),
+        t_or_k1: ap(5),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([
+            (100, Write),
+            (101, Write),
+            (102, Write),
+            (103, Write),
+            (104, Write),
+            (105, Write),
+            (194, Read),
+            (195, Read),
+            (196, Read),
+            (197, Read),
+        ]),
+    );
+}
+
+#[test]
+fn eval_circuit() {
+    // A single add mod instance at 300 with its values at 400 and offsets at 500. The offsets
+    // table is `(0, 4, 8)`.
+    let hint = CoreHint::EvalCircuit {
+        n_add_mods: imm(1),
+        add_mod_builtin: deref(fp(-4)),
+        n_mul_mods: imm(0),
+        mul_mod_builtin: deref(fp(-3)),
+    };
+
+    let mut expected = BTreeMap::from([(196, Read), (197, Read)]);
+    expected.extend((300..307).map(|x| (x, Read)));
+    expected.extend((500..503).map(|x| (x, Read)));
+    expected.extend((400..408).map(|x| (x, Read)));
+    expected.extend((408..412).map(|x| (x, Write)));
+
+    assert_eq!(
+        hint_accesses(
+            &[
+                (196, 300),
+                (197, 600),
+                (304, 400),
+                (305, 500),
+                (500, 0),
+                (501, 4),
+                (502, 8),
+            ],
+            hint,
+        ),
+        expected,
+    );
+
+    // Two mul mod instances at 600, where the second one is filled from the first. The offsets
+    // table is `(0, 4, 8, 8, 4, 12)`, so the second operation reads the result of the first.
+    let hint = CoreHint::EvalCircuit {
+        n_add_mods: imm(0),
+        add_mod_builtin: deref(fp(-4)),
+        n_mul_mods: imm(2),
+        mul_mod_builtin: deref(fp(-3)),
+    };
+
+    let mut expected = BTreeMap::from([(196, Read), (197, Read)]);
+    expected.extend((600..607).map(|x| (x, Read)));
+    expected.extend((607..614).map(|x| (x, Write)));
+    expected.extend((500..506).map(|x| (x, Read)));
+    expected.extend((400..408).map(|x| (x, Read)));
+    expected.extend((408..416).map(|x| (x, Write)));
+
+    assert_eq!(
+        hint_accesses(
+            &[
+                (196, 300),
+                (197, 600),
+                (604, 400),
+                (605, 500),
+                (500, 0),
+                (501, 4),
+                (502, 8),
+                (503, 8),
+                (504, 4),
+                (505, 12),
+            ],
+            hint,
+        ),
+        expected,
+    );
+}
+
+#[test]
+fn assert_current_access_indices_is_empty() {
+    let hint = deprecated(DeprecatedHint::AssertCurrentAccessIndicesIsEmpty);
+    assert_eq!(hint_accesses(&[], hint), accesses([]));
+}
+
+#[test]
+fn assert_all_accesses_used() {
+    let hint = deprecated(DeprecatedHint::AssertAllAccessesUsed {
+        n_used_accesses: fp(-3),
+    });
+    assert_eq!(hint_accesses(&[], hint), accesses([(197, Read)]));
+}
+
+#[test]
+fn assert_all_keys_used() {
+    let hint = deprecated(DeprecatedHint::AssertAllKeysUsed);
+    assert_eq!(hint_accesses(&[], hint), accesses([]));
+}
+
+#[test]
+fn assert_le_assert_third_arc_excluded() {
+    let hint = deprecated(DeprecatedHint::AssertLeAssertThirdArcExcluded);
+    assert_eq!(hint_accesses(&[], hint), accesses([]));
+}
+
+#[test]
+fn assert_lt_assert_valid_input() {
+    let hint = deprecated(DeprecatedHint::AssertLtAssertValidInput {
+        a: deref(fp(-4)),
+        b: deref(fp(-3)),
+    });
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(196, Read), (197, Read)]),
+    );
+}
+
+#[test]
+fn felt252_dict_read() {
+    let hint = deprecated(DeprecatedHint::Felt252DictRead {
+        dict_ptr: deref(fp(-4)),
+        key: deref(fp(-3)),
+        value_dst: ap(0),
+    });
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(100, Write), (196, Read), (197, Read)]),
+    );
+}
+
+#[test]
+fn felt252_dict_write() {
+    let hint = deprecated(DeprecatedHint::Felt252DictWrite {
+        dict_ptr: deref(fp(-5)),
+        key: deref(fp(-4)),
+        value: deref(fp(-3)),
+    });
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(195, Read), (196, Read), (197, Read)]),
+    );
+}
+
+fn selector(name: &str) -> Felt {
+    Felt::from_bytes_be_slice(name.as_bytes())
+}
+
+#[test]
+fn system_call() {
+    let hint = StarknetHint::SystemCall {
+        system: deref(fp(-3)),
+    };
+    let cells = [
+        (197, Felt::from(300)),
+        (300, selector("StorageRead")),
+        (301, Felt::from(1000)),
+        (302, Felt::ZERO),
+        (303, Felt::from(7)),
+        (305, Felt::ZERO),
+    ];
+    assert_eq!(
+        felt_hint_accesses(&cells, hint),
+        accesses([
+            (197, Read),
+            (300, Read),
+            (301, Read),
+            (302, Read),
+            (303, Read),
+            (304, Write),
+            (305, Write),
+            (306, Write),
+        ]),
+    );
+}
+
+#[test]
+fn system_call_failure() {
+    // A failed syscall responds with its revert reason instead of its results.
+    let hint = StarknetHint::SystemCall {
+        system: deref(fp(-3)),
+    };
+    let cells = [
+        (197, Felt::from(300)),
+        (300, selector("Secp256k1GetXy")),
+        (301, Felt::from(1000)),
+        (302, Felt::from(400)),
+        (304, Felt::ONE),
+    ];
+    assert_eq!(
+        felt_hint_accesses(&cells, hint),
+        accesses([
+            (197, Read),
+            (300, Read),
+            (301, Read),
+            (302, Read),
+            (303, Write),
+            (304, Write),
+            (305, Write),
+            (306, Write),
+        ]),
+    );
+}
+
+#[test]
+fn system_call_unknown_selector() {
+    let hint = StarknetHint::SystemCall {
+        system: deref(fp(-3)),
+    };
+    assert_eq!(
+        hint_accesses(&[(197, 300), (300, 1)], hint),
+        accesses([(197, Read), (300, Read), (301, Read)]),
+    );
+}
+
+#[test]
+fn cheatcode() {
+    let hint = StarknetHint::Cheatcode {
+        selector: BigIntAsHex { value: 0.into() },
+        input_start: deref(fp(-4)),
+        input_end: deref(fp(-3)),
+        output_start: ap(0),
+        output_end: ap(1),
+    };
+    assert_eq!(
+        hint_accesses(&[(196, 300), (197, 302), (100, 400), (101, 401)], hint),
+        accesses([
+            (100, Write),
+            (101, Write),
+            (196, Read),
+            (197, Read),
+            (300, Read),
+            (301, Read),
+            (400, Write),
+        ]),
+    );
+}
+
+#[test]
+fn add_relocation_rule() {
+    let hint = ExternalHint::AddRelocationRule {
+        src: deref(fp(-4)),
+        dst: deref(fp(-3)),
+    };
+    assert_eq!(
+        hint_accesses(&[], hint),
+        accesses([(196, Read), (197, Read)]),
+    );
+}
+
+#[test]
+fn write_run_param() {
+    let hint = ExternalHint::WriteRunParam {
+        index: imm(0),
+        dst: ap(0),
+    };
+    assert_eq!(hint_accesses(&[], hint), accesses([(100, Write)]));
+}
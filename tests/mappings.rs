@@ -0,0 +1,182 @@
+use cairo_lang_casm::{
+    casm,
+    hints::{CoreHint, CoreHintBase, Hint},
+    instructions::{AddApInstruction, Instruction, InstructionBody},
+    operand::{CellRef, Register},
+    res,
+};
+use common::{assemble, build_memory, build_trace, entry, RET};
+use sierra2casm_dbg::{AccessKind, GraphMappings, StepId, ValueId};
+use starknet_types_core::felt::Felt;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+mod common;
+
+fn alloc_segment_hint(offset: i16) -> Hint {
+    Hint::Core(CoreHintBase::Core(CoreHint::AllocSegment {
+        dst: CellRef {
+            register: Register::AP,
+            offset,
+        },
+    }))
+}
+
+#[test]
+fn hints_are_keyed_by_pc() {
+    // The hint is attached to the second instruction, but the trace executes it first. Looking up
+    // hints by step index would attach it to the wrong step.
+    let memory = build_memory(&[(1, RET.into()), (2, RET.into())]);
+    let trace = build_trace(&[entry(2, 10, 10), entry(1, 20, 20)]);
+    let hints = HashMap::from([(1, vec![alloc_segment_hint(0)])]);
+
+    let mappings = GraphMappings::new(&memory, &trace, &hints, 1);
+
+    assert!(mappings[StepId(0)].contains(&ValueId(10)));
+    assert!(!mappings[StepId(1)].contains(&ValueId(20)));
+}
+
+#[test]
+fn hints_respect_program_offset() {
+    let memory = build_memory(&[(5, RET.into()), (6, RET.into())]);
+    let trace = build_trace(&[entry(5, 10, 10), entry(6, 20, 20)]);
+    let hints = HashMap::from([(0, vec![alloc_segment_hint(1)])]);
+
+    let mappings = GraphMappings::new(&memory, &trace, &hints, 5);
+
+    assert_eq!(mappings[ValueId(11)], [StepId(0)].into());
+    assert!(!mappings.value2step().contains_key(&ValueId(21)));
+}
+
+#[test]
+fn hint_outputs_are_writes() {
+    let memory = build_memory(&[(1, RET.into())]);
+    let trace = build_trace(&[entry(1, 10, 10)]);
+    let hints = HashMap::from([(0, vec![alloc_segment_hint(0)])]);
+
+    let mappings = GraphMappings::new(&memory, &trace, &hints, 1);
+
+    assert_eq!(
+        mappings.access_kind(StepId(0), ValueId(10)),
+        Some(AccessKind::Write)
+    );
+    assert_eq!(
+        mappings.inputs(StepId(0)).collect::<HashSet<_>>(),
+        [ValueId(8), ValueId(9)].into()
+    );
+    assert_eq!(
+        mappings.producers(ValueId(10)).collect::<Vec<_>>(),
+        [StepId(0)]
+    );
+}
+
+fn step_accesses(mappings: &GraphMappings, step: StepId) -> BTreeMap<usize, AccessKind> {
+    mappings[step]
+        .iter()
+        .map(|x| (x.0, mappings.access_kind(step, *x).unwrap()))
+        .collect()
+}
+
+#[test]
+fn add_ap_operands() {
+    let mut program = casm! {
+        ap += [fp + -3];
+        ap += [[fp + -3] + 1];
+    };
+    program.instructions.push(Instruction::new(
+        InstructionBody::AddAp(AddApInstruction {
+            operand: res!([fp + -3] * [ap + -1]),
+        }),
+        false,
+    ));
+    let memory = build_memory(&[assemble(program), vec![(197, Felt::from(300))]].concat());
+    let trace = build_trace(&[entry(1, 100, 200), entry(2, 100, 200), entry(3, 100, 200)]);
+
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    assert_eq!(
+        step_accesses(&mappings, StepId(0)),
+        [(197, AccessKind::Read)].into()
+    );
+    assert_eq!(
+        step_accesses(&mappings, StepId(1)),
+        [(197, AccessKind::Read), (301, AccessKind::Read)].into()
+    );
+    assert_eq!(
+        step_accesses(&mappings, StepId(2)),
+        [(99, AccessKind::Read), (197, AccessKind::Read)].into()
+    );
+}
+
+#[test]
+fn call_and_ret_frames() {
+    let memory = build_memory(&assemble(casm! {
+        call abs [fp + -3];
+        ret;
+    }));
+    let trace = build_trace(&[entry(1, 100, 200), entry(2, 102, 102)]);
+
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    assert_eq!(
+        step_accesses(&mappings, StepId(0)),
+        [
+            (100, AccessKind::Write),
+            (101, AccessKind::Write),
+            (197, AccessKind::Read)
+        ]
+        .into()
+    );
+    assert_eq!(
+        step_accesses(&mappings, StepId(1)),
+        [(100, AccessKind::Read), (101, AccessKind::Read)].into()
+    );
+    assert_eq!(
+        mappings.producers(ValueId(100)).collect::<Vec<_>>(),
+        [StepId(0)]
+    );
+    assert_eq!(
+        mappings.consumers(ValueId(100)).collect::<Vec<_>>(),
+        [StepId(1)]
+    );
+}
+
+#[test]
+fn jnz_with_deref_offset() {
+    let memory = build_memory(&assemble(casm! {
+        jmp rel [fp + -4] if [fp + -3] != 0;
+    }));
+    let trace = build_trace(&[entry(1, 100, 200)]);
+
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    assert_eq!(
+        step_accesses(&mappings, StepId(0)),
+        [(196, AccessKind::Read), (197, AccessKind::Read)].into()
+    );
+}
+
+#[test]
+fn generic_instruction_references() {
+    // `[ap + 0] = [[fp + -3] + 1], ap += [[fp + -3] + 1]`: not supported by the Sierra compiler's
+    // subset because of the `ap` update.
+    let raw = assemble(casm! { [ap + 0] = [[fp + -3] + 1], ap++; })[0].1;
+    let raw = u64::try_from(raw).unwrap();
+    let raw = Felt::from((raw & !(3 << 58)) | (1 << 58));
+
+    let memory = build_memory(&[(1, raw), (17, Felt::from(30)), (31, Felt::ONE)]);
+    let trace = build_trace(&[entry(1, 10, 20)]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    assert_eq!(
+        BTreeMap::from_iter(
+            mappings[StepId(0)]
+                .iter()
+                .map(|x| (x.0, mappings.access_kind(StepId(0), *x).unwrap()))
+        ),
+        BTreeMap::from([
+            (10, AccessKind::Assert),
+            (17, AccessKind::Read),
+            (31, AccessKind::Read),
+        ])
+    );
+}
@@ -0,0 +1,230 @@
+use cairo_lang_casm::{
+    casm,
+    hints::{CoreHint, CoreHintBase, Hint},
+    operand::{CellRef, Register, ResOperand},
+};
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    run_search_algorithm,
+    search::{
+        predicates::{Dataflow, NonIncreasing, PcInRange, ValueInRange, ValueInSet},
+        BfsQueue, NodeId, Predicates,
+    },
+    GraphMappings, Memory, StepId, Trace, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::{HashMap, HashSet};
+
+mod common;
+
+/// A graph where step 0 runs at pc 2 and every other step at pc 1.
+///
+/// There are two paths from `[50]` to `[72]`:
+///   - A short one through `[51]` and step 0.
+///   - A long one through `[70]` and `[71]`.
+///
+/// Every cell holds its address, unless overridden by `values` or listed in `uninitialized`.
+fn build_graph(values: &[(usize, u64)], uninitialized: &[usize]) -> (Memory, Trace, GraphMappings) {
+    let program = assemble(casm! {
+        [ap + 0] = [ap + -1] + [fp + -3], ap++;
+        [ap + 0] = [ap + -1] + [fp + -3], ap++;
+    });
+    let overrides = values.iter().copied().collect::<HashMap<_, _>>();
+    let cells = [50, 51, 69, 70, 71, 72, 79, 80, 81, 82, 297]
+        .into_iter()
+        .filter(|addr| !uninitialized.contains(addr))
+        .map(|addr| {
+            (
+                addr,
+                Felt::from(*overrides.get(&addr).unwrap_or(&(addr as u64))),
+            )
+        })
+        .collect::<Vec<_>>();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[
+        entry(2, 51, 53),
+        entry(1, 80, 300),
+        entry(1, 81, 300),
+        entry(1, 82, 300),
+        entry(1, 70, 53),
+        entry(1, 71, 300),
+        entry(1, 72, 54),
+    ]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    (memory, trace, mappings)
+}
+
+/// Return the number of nodes of the shortest path from `[50]` to `[72]`: 5 for the short one and
+/// 7 for the long one.
+fn path_len(values: &[(usize, u64)], predicates: Predicates<'_>) -> Option<usize> {
+    path_len_uninitialized(values, &[], predicates)
+}
+
+fn path_len_uninitialized(
+    values: &[(usize, u64)],
+    uninitialized: &[usize],
+    predicates: Predicates<'_>,
+) -> Option<usize> {
+    let (memory, trace, mappings) = build_graph(values, uninitialized);
+    let path = run_search_algorithm::<BfsQueue<_>>(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(72),
+        predicates,
+    )
+    .next();
+    path.map(|x| x.len())
+}
+
+fn felts(values: impl IntoIterator<Item = u64>) -> HashSet<Felt> {
+    values.into_iter().map(Felt::from).collect()
+}
+
+#[test]
+fn unconstrained() {
+    assert_eq!(path_len(&[], Predicates::new()), Some(5));
+}
+
+#[test]
+fn value_in_range() {
+    // `[51]` is out of range.
+    let predicates = Predicates::new().with_value(ValueInRange(Felt::from(60)..=Felt::from(80)));
+    assert_eq!(path_len(&[], predicates), Some(7));
+}
+
+#[test]
+fn non_increasing() {
+    // The short path goes from 100 up to 150, while the long one goes down to 70.
+    let values = [(50, 100), (51, 150), (70, 90), (71, 80), (72, 70)];
+    assert_eq!(
+        path_len(&values, Predicates::new().with_value(NonIncreasing)),
+        Some(7)
+    );
+
+    // Nothing can be compared to an uninitialized source.
+    assert_eq!(
+        path_len_uninitialized(&values, &[50], Predicates::new().with_value(NonIncreasing)),
+        None
+    );
+}
+
+#[test]
+fn value_in_set() {
+    let predicates = Predicates::new().with_value(ValueInSet(felts([70, 71, 72])));
+    assert_eq!(path_len(&[], predicates), Some(7));
+}
+
+#[test]
+fn pc_in_range() {
+    // Step 0 runs at pc 2.
+    let predicates = Predicates::new().with_step(PcInRange(1..=1));
+    assert_eq!(path_len(&[], predicates), Some(7));
+}
+
+#[test]
+fn combined() {
+    // Each predicate alone leaves one of the paths, but every predicate must hold.
+    let values = || ValueInSet(felts([51, 72]));
+    let steps = || PcInRange(1..=1);
+    assert_eq!(
+        path_len(&[], Predicates::new().with_value(values())),
+        Some(5)
+    );
+    assert_eq!(path_len(&[], Predicates::new().with_step(steps())), Some(7));
+    assert_eq!(
+        path_len(
+            &[],
+            Predicates::new().with_value(values()).with_step(steps())
+        ),
+        None
+    );
+}
+
+/// The dataflow of a hint and a call:
+///   0. `ap += 1`, whose hint writes `[20] = [18] < [19]`.
+///   1. `call rel 3`, which writes `[21]` and `[22]`.
+///   2. `[23] = [20] + [21]`, where `[21]` is `[fp - 2]`.
+///   3. `ret`, which reads `[21]` and `[22]`.
+///   4. `[24] = [21] + [23]`.
+#[test]
+fn dataflow_through_hints_and_calls() {
+    let program = assemble(casm! {
+        ap += 1;
+        call rel 3;
+        [ap + 0] = [ap + -3] + [ap + -1], ap++;
+        [ap + 0] = [fp + -3] + [fp + -2], ap++;
+        ret;
+    });
+    let cells = [
+        (18, 3),
+        (19, 7),
+        (20, 1),
+        (21, 20),
+        (22, 5),
+        (23, 21),
+        (24, 41),
+    ]
+    .map(|(addr, value)| (addr, Felt::from(value)));
+    let memory = build_memory(&[program, cells.to_vec()].concat());
+    let trace = build_trace(&[
+        entry(1, 20, 20),
+        entry(3, 21, 20),
+        entry(6, 23, 23),
+        entry(7, 24, 23),
+        entry(5, 24, 20),
+    ]);
+    let cell = |offset| CellRef {
+        register: Register::FP,
+        offset,
+    };
+    let hints = HashMap::from([(
+        0,
+        vec![Hint::Core(CoreHintBase::Core(CoreHint::TestLessThan {
+            lhs: ResOperand::Deref(cell(-2)),
+            rhs: ResOperand::Deref(cell(-1)),
+            dst: cell(0),
+        }))],
+    )]);
+    let mappings = GraphMappings::new(&memory, &trace, &hints, 1);
+    let search = |source, target| {
+        let predicates = Predicates::new()
+            .with_value(Dataflow(&mappings))
+            .with_step(Dataflow(&mappings));
+        run_search_algorithm::<BfsQueue<_>>(
+            &memory,
+            &trace,
+            &mappings,
+            ValueId(source),
+            ValueId(target),
+            predicates,
+        )
+        .next()
+    };
+
+    // The hint reads `[18]` and writes `[20]`.
+    assert_eq!(
+        search(18, 24),
+        Some(vec![
+            NodeId::Value(ValueId(18)),
+            NodeId::Step(StepId(0)),
+            NodeId::Value(ValueId(20)),
+            NodeId::Step(StepId(2)),
+            NodeId::Value(ValueId(23)),
+            NodeId::Step(StepId(4)),
+            NodeId::Value(ValueId(24)),
+        ])
+    );
+    // The call only writes the frame cells and the return only reads them.
+    assert_eq!(search(22, 24), None);
+    assert_eq!(
+        search(21, 24),
+        Some(vec![
+            NodeId::Value(ValueId(21)),
+            NodeId::Step(StepId(4)),
+            NodeId::Value(ValueId(24)),
+        ])
+    );
+}
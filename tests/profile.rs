@@ -0,0 +1,230 @@
+use cairo_lang_casm::casm;
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    calls::CallTree,
+    profile::{Cost, Profile},
+    GraphMappings, SierraMapping,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::{BTreeMap, HashMap};
+
+mod common;
+
+const PROGRAM: &str = "
+    type RangeCheck = RangeCheck;
+    type GasBuiltin = GasBuiltin;
+    type felt252 = felt252;
+
+    libfunc store_temp_felt = store_temp<felt252>;
+
+    store_temp_felt(a) -> (a);
+    store_temp_felt(rc, gas, a) -> (rc, gas, b);
+    store_temp_felt(b) -> (b);
+    return(b);
+    store_temp_felt(rc, gas, a) -> (rc, gas, a);
+    return(rc, gas, a);
+
+    foo::main@0() -> (felt252);
+    foo::f@4(rc: RangeCheck, gas: GasBuiltin, a: felt252) -> (RangeCheck, GasBuiltin, felt252);
+";
+
+const DEBUG_INFO: &str = r#"[
+    { "start_offset": 0, "end_offset": 6 },
+    { "start_offset": 6, "end_offset": 8 },
+    { "start_offset": 8, "end_offset": 9 },
+    { "start_offset": 9, "end_offset": 10 },
+    { "start_offset": 10, "end_offset": 16 },
+    { "start_offset": 16, "end_offset": 17 }
+]"#;
+
+/// `main` calls `f(100, 1000, 5)`, which uses 2 range checks and 7 gas.
+///
+/// Steps 0 to 3 and 8 to 9 belong to `main` (pc 1), and steps 4 to 7 to `f` (pc 11).
+fn run(with_sierra: bool) -> (CallTree, Profile) {
+    let program = assemble(casm! {
+        [ap + 0] = 100, ap++;
+        [ap + 0] = 1000, ap++;
+        [ap + 0] = 5, ap++;
+        call rel 4;
+        [ap + 0] = [ap + -3] + [ap + -1], ap++;
+        ret;
+        [ap + 0] = [fp + -5] + 2, ap++;
+        [ap + 0] = [fp + -4] + (-7), ap++;
+        [ap + 0] = [fp + -3] * 2, ap++;
+        ret;
+    });
+    let cells = [
+        (30, 100),
+        (31, 1000),
+        (32, 5),
+        (33, 30),
+        (34, 9),
+        (35, 102),
+        (36, 993),
+        (37, 10),
+        (38, 112),
+    ]
+    .map(|(addr, value)| (addr, Felt::from(value)));
+    let memory = build_memory(&[program, cells.to_vec()].concat());
+    let trace = build_trace(&[
+        entry(1, 30, 30),
+        entry(3, 31, 30),
+        entry(5, 32, 30),
+        entry(7, 33, 30),
+        entry(11, 35, 35),
+        entry(13, 36, 35),
+        entry(15, 37, 35),
+        entry(17, 38, 35),
+        entry(9, 38, 30),
+        entry(10, 39, 30),
+    ]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+    let sierra = SierraMapping::parse(PROGRAM, DEBUG_INFO).unwrap();
+
+    let tree = CallTree::new(&memory, &trace, &mappings);
+    let profile = Profile::new(&memory, &trace, &tree, with_sierra.then_some(&sierra), 1);
+    (tree, profile)
+}
+
+fn name(pc: usize) -> String {
+    match pc {
+        1 => "main".to_string(),
+        11 => "f".to_string(),
+        _ => format!("pc {pc}"),
+    }
+}
+
+fn cost(steps: usize, gas: i128, range_checks: usize) -> Cost {
+    Cost {
+        steps,
+        gas,
+        builtins: match range_checks {
+            0 => BTreeMap::new(),
+            n => BTreeMap::from([("RangeCheck".to_string(), n)]),
+        },
+    }
+}
+
+#[test]
+fn functions() {
+    let (_, profile) = run(true);
+
+    let main = &profile.functions[&1];
+    assert_eq!(main.count, 1);
+    assert_eq!(main.exclusive, cost(6, 0, 0));
+    assert_eq!(main.inclusive, cost(10, 7, 2));
+
+    let f = &profile.functions[&11];
+    assert_eq!(f.count, 1);
+    assert_eq!(f.exclusive, cost(4, 7, 2));
+    assert_eq!(f.inclusive, cost(4, 7, 2));
+}
+
+#[test]
+fn pcs() {
+    let (_, profile) = run(true);
+    assert_eq!(profile.pcs.len(), 10);
+
+    let call = &profile.pcs[&7];
+    assert_eq!(call.count, 1);
+    assert_eq!(call.exclusive, cost(1, 0, 0));
+    assert_eq!(call.inclusive, cost(5, 7, 2));
+
+    let add = &profile.pcs[&11];
+    assert_eq!(add.exclusive, cost(1, 0, 0));
+    assert_eq!(add.inclusive, cost(1, 0, 0));
+}
+
+#[test]
+fn without_sierra() {
+    let (_, profile) = run(false);
+
+    assert_eq!(profile.functions[&1].inclusive, cost(10, 0, 0));
+    assert_eq!(profile.functions[&11].exclusive, cost(4, 0, 0));
+}
+
+#[test]
+fn exports() {
+    let (tree, profile) = run(true);
+
+    assert_eq!(profile.folded_stacks(&tree, name), "main 6\nmain;f 4\n");
+
+    let pprof = profile.to_pprof(&tree, name);
+    // The first field is a sample type.
+    assert_eq!(pprof[0], 0x0a);
+    for string in ["steps", "gas", "RangeCheck", "main", "f"] {
+        assert!(pprof.windows(string.len()).any(|x| x == string.as_bytes()));
+    }
+}
+
+/// `main(1000)` withdraws 5 gas and returns it along with a felt, and no step follows its `ret`.
+#[test]
+fn entry_function_gas() {
+    const PROGRAM: &str = "
+        type GasBuiltin = GasBuiltin;
+        type felt252 = felt252;
+
+        libfunc store_temp_felt = store_temp<felt252>;
+
+        store_temp_felt(gas, a) -> (gas, b);
+        return(gas, b);
+
+        foo::main@0(gas: GasBuiltin) -> (GasBuiltin, felt252);
+    ";
+    const DEBUG_INFO: &str = r#"[
+        { "start_offset": 0, "end_offset": 4 },
+        { "start_offset": 4, "end_offset": 5 }
+    ]"#;
+
+    let program = assemble(casm! {
+        [ap + 0] = [fp + -3] + (-5), ap++;
+        [ap + 0] = 42, ap++;
+        ret;
+    });
+    let cells = [(27, 1000), (28, 0), (29, 0), (30, 995), (31, 42)]
+        .map(|(addr, value)| (addr, Felt::from(value)));
+    let memory = build_memory(&[program, cells.to_vec()].concat());
+    let trace = build_trace(&[entry(1, 30, 30), entry(3, 31, 30), entry(5, 32, 30)]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+    let sierra = SierraMapping::parse(PROGRAM, DEBUG_INFO).unwrap();
+
+    let tree = CallTree::new(&memory, &trace, &mappings);
+    let profile = Profile::new(&memory, &trace, &tree, Some(&sierra), 1);
+    assert_eq!(profile.functions[&1].inclusive, cost(3, 5, 0));
+}
+
+/// `main(7, 1000)` uses 2 range checks and 5 gas without accessing its range check argument.
+#[test]
+fn unused_argument() {
+    const PROGRAM: &str = "
+        type RangeCheck = RangeCheck;
+        type GasBuiltin = GasBuiltin;
+
+        libfunc store_temp_gas = store_temp<GasBuiltin>;
+
+        store_temp_gas(gas) -> (gas);
+        return(rc, gas);
+
+        foo::main@0(rc: RangeCheck, gas: GasBuiltin) -> (RangeCheck, GasBuiltin);
+    ";
+    const DEBUG_INFO: &str = r#"[
+        { "start_offset": 0, "end_offset": 4 },
+        { "start_offset": 4, "end_offset": 5 }
+    ]"#;
+
+    let program = assemble(casm! {
+        [ap + 0] = 9, ap++;
+        [ap + 0] = [fp + -3] + (-5), ap++;
+        ret;
+    });
+    let cells = [(26, 7), (27, 1000), (28, 0), (29, 0), (30, 9), (31, 995)]
+        .map(|(addr, value)| (addr, Felt::from(value)));
+    let memory = build_memory(&[program, cells.to_vec()].concat());
+    let trace = build_trace(&[entry(1, 30, 30), entry(3, 31, 30), entry(5, 32, 30)]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+    let sierra = SierraMapping::parse(PROGRAM, DEBUG_INFO).unwrap();
+
+    let tree = CallTree::new(&memory, &trace, &mappings);
+    let profile = Profile::new(&memory, &trace, &tree, Some(&sierra), 1);
+    assert_eq!(profile.functions[&1].inclusive, cost(3, 5, 2));
+}
@@ -0,0 +1,122 @@
+use cairo_lang_casm::{
+    casm,
+    hints::{CoreHint, CoreHintBase, Hint},
+    operand::{CellRef, Register},
+};
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    search::provenance::{find_provenance, Origin},
+    GraphMappings, StepId, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+mod common;
+
+/// Step 0 computes `[10] = [22] + [21]`, step 1 computes `[11] = [10] * [22]` and a hint of step 2
+/// writes `[12]`.
+fn build_mappings() -> GraphMappings {
+    let program = assemble(casm! {
+        [ap + 0] = [fp + -3] + [fp + -4], ap++;
+        [ap + 0] = [ap + -1] * [fp + -3], ap++;
+        ap += 1;
+    });
+    let cells = [10, 11, 12, 21, 22]
+        .map(|addr| (addr, Felt::from(addr)))
+        .to_vec();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[entry(1, 10, 25), entry(2, 11, 25), entry(3, 12, 25)]);
+    let hints = HashMap::from([(
+        2,
+        vec![Hint::Core(CoreHintBase::Core(CoreHint::AllocSegment {
+            dst: CellRef {
+                register: Register::AP,
+                offset: 0,
+            },
+        }))],
+    )]);
+
+    GraphMappings::new(&memory, &trace, &hints, 1)
+}
+
+#[test]
+fn full_slice() {
+    let mappings = build_mappings();
+    let provenance = find_provenance(&mappings, ValueId(11), None);
+
+    assert_eq!(
+        provenance.origins[&ValueId(11)],
+        Origin::Step {
+            step: StepId(1),
+            inputs: vec![ValueId(10), ValueId(22)],
+        }
+    );
+    assert_eq!(
+        provenance.origins[&ValueId(10)],
+        Origin::Step {
+            step: StepId(0),
+            inputs: vec![ValueId(21), ValueId(22)],
+        }
+    );
+    assert_eq!(
+        provenance.leaves().collect::<Vec<_>>(),
+        [(ValueId(21), &Origin::Input), (ValueId(22), &Origin::Input)]
+    );
+    assert_eq!(provenance.depths[&ValueId(21)], 2);
+    assert_eq!(provenance.depths[&ValueId(22)], 1);
+}
+
+#[test]
+fn depth_limit() {
+    let mappings = build_mappings();
+    let provenance = find_provenance(&mappings, ValueId(11), Some(1));
+
+    assert_eq!(provenance.origins.len(), 3);
+    assert_eq!(provenance.origins[&ValueId(10)], Origin::Truncated);
+    assert_eq!(provenance.origins[&ValueId(22)], Origin::Truncated);
+}
+
+#[test]
+fn hint_outputs_are_leaves() {
+    let mappings = build_mappings();
+    let provenance = find_provenance(&mappings, ValueId(12), None);
+
+    assert_eq!(
+        provenance.origins[&ValueId(12)],
+        Origin::Hint { step: StepId(2) }
+    );
+    assert_eq!(provenance.origins.len(), 1);
+}
+
+#[test]
+fn deduced_operands() {
+    // Step 0 writes `[21] = [20] + 5`, then step 1 deduces `[10]` from `[21] = [10] + [20]`.
+    let program = assemble(casm! {
+        [fp + -3] = [fp + -4] + 5;
+        [fp + -3] = [ap + 0] + [fp + -4];
+    });
+    let cells = [
+        (10, Felt::from(5)),
+        (20, Felt::from(7)),
+        (21, Felt::from(12)),
+    ];
+    let memory = build_memory(&[program, cells.to_vec()].concat());
+    let trace = build_trace(&[entry(1, 10, 24), entry(3, 10, 24)]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    let provenance = find_provenance(&mappings, ValueId(10), None);
+    assert_eq!(
+        provenance.origins[&ValueId(10)],
+        Origin::Step {
+            step: StepId(1),
+            inputs: vec![ValueId(20), ValueId(21)],
+        }
+    );
+    assert_eq!(
+        provenance.origins[&ValueId(21)],
+        Origin::Step {
+            step: StepId(0),
+            inputs: vec![ValueId(20)],
+        }
+    );
+}
@@ -0,0 +1,149 @@
+use bincode::de::read::SliceReader;
+use sierra2casm_dbg::{Error, Memory, RelocationTable, SegmentAddr, SegmentKind, Trace, ValueId};
+use starknet_types_core::felt::Felt;
+
+enum Value {
+    Felt(u64),
+    Relocatable(i64, u64),
+}
+
+/// Encode memory cells using the segment-relative memory dump format.
+fn encode_segments(cells: &[(i64, u64, Value)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (segment, offset, value) in cells {
+        data.extend(segment.to_le_bytes());
+        data.extend(offset.to_le_bytes());
+        match value {
+            Value::Felt(value) => {
+                data.extend(0u64.to_le_bytes());
+                data.extend(Felt::from(*value).to_bytes_le());
+            }
+            Value::Relocatable(segment, offset) => {
+                data.extend(1u64.to_le_bytes());
+                data.extend(segment.to_le_bytes());
+                data.extend(offset.to_le_bytes());
+                data.extend([0; 16]);
+            }
+        }
+    }
+    data
+}
+
+/// Encode trace entries using the unrelocated trace dump format.
+fn encode_unrelocated(entries: &[(i64, u64, u64, u64)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (pc_segment, pc_offset, ap, fp) in entries {
+        data.extend(pc_segment.to_le_bytes());
+        data.extend(pc_offset.to_le_bytes());
+        data.extend(ap.to_le_bytes());
+        data.extend(fp.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn segment_memory_is_relocated() {
+    let data = encode_segments(&[
+        (0, 0, Value::Felt(7)),
+        (0, 1, Value::Felt(8)),
+        (1, 0, Value::Relocatable(2, 1)),
+        (1, 2, Value::Relocatable(0, 1)),
+        (2, 1, Value::Felt(9)),
+    ]);
+    let (memory, table) = Memory::decode_segments(SliceReader::new(&data), None);
+
+    assert_eq!(table.bases(), [1, 3, 6]);
+    // The last segment ends after its highest cell.
+    assert_eq!(
+        table.unrelocate(7).map(|x| x.to_string()),
+        Some("2:1".into())
+    );
+    assert_eq!(table.unrelocate(8), None);
+    assert_eq!(
+        memory.iter().collect::<Vec<_>>(),
+        [
+            (1, Felt::from(7)),
+            (2, Felt::from(8)),
+            (3, Felt::from(7)),
+            (5, Felt::from(2)),
+            (7, Felt::from(9)),
+        ]
+    );
+}
+
+#[test]
+fn segment_memory_with_explicit_table() {
+    let data = encode_segments(&[(0, 0, Value::Felt(7)), (1, 0, Value::Relocatable(0, 0))]);
+    let table = RelocationTable::from_bases(vec![1, 100]);
+    let (memory, _) = Memory::decode_segments(SliceReader::new(&data), Some(table));
+
+    assert_eq!(memory.get(100), Some(Felt::ONE));
+}
+
+#[test]
+fn segment_memory_errors() {
+    let data = encode_segments(&[(-1, 0, Value::Felt(7))]);
+    let result = Memory::try_decode_segments(SliceReader::new(&data), None);
+    assert!(matches!(
+        result,
+        Err(Error::TemporarySegment { segment: -1 })
+    ));
+
+    let data = encode_segments(&[(2, 0, Value::Felt(7))]);
+    let table = RelocationTable::from_bases(vec![1, 100]);
+    let result = Memory::try_decode_segments(SliceReader::new(&data), Some(table));
+    assert!(matches!(result, Err(Error::UnknownSegment { segment: 2 })));
+
+    let mut data = encode_segments(&[(0, 0, Value::Felt(7))]);
+    data[16] = 2;
+    let result = Memory::try_decode_segments(SliceReader::new(&data), None);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidValueTag { offset: 0, tag: 2 })
+    ));
+}
+
+#[test]
+fn unrelocated_trace() {
+    let table = RelocationTable::from_bases(vec![1, 10]);
+    let data = encode_unrelocated(&[(0, 0, 2, 2), (0, 3, 4, 2)]);
+    let trace = Trace::decode_unrelocated(SliceReader::new(&data), &table);
+
+    assert_eq!(
+        trace.iter().map(|x| (x.pc, x.ap, x.fp)).collect::<Vec<_>>(),
+        [(1, 12, 12), (4, 14, 12)]
+    );
+}
+
+#[test]
+fn relocation_table() {
+    let table = "1 10 10 25"
+        .parse::<RelocationTable>()
+        .unwrap()
+        .with_builtins(vec!["range_check".into()]);
+
+    assert_eq!(table.unrelocate(0), None);
+    assert_eq!(
+        table.unrelocate(12),
+        Some(SegmentAddr {
+            segment: 2,
+            offset: 2
+        })
+    );
+    assert_eq!(table.display(ValueId(30)), "3:5");
+    assert_eq!(table.segment_kind(0), SegmentKind::Program);
+    assert_eq!(table.segment_kind(1), SegmentKind::Execution);
+    assert_eq!(table.segment_kind(2), SegmentKind::Builtin("range_check"));
+    assert_eq!(table.segment_kind(3), SegmentKind::Other);
+
+    // Without an end, the last segment extends indefinitely.
+    assert_eq!(table.display(ValueId(1000)), "3:975");
+    let table = table.with_end(30);
+    assert_eq!(table.display(ValueId(29)), "3:4");
+    assert_eq!(table.unrelocate(30), None);
+
+    assert!(matches!(
+        "1 10 5".parse::<RelocationTable>(),
+        Err(Error::InvalidRelocationTable { segment: 2 })
+    ));
+}
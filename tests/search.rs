@@ -0,0 +1,377 @@
+use cairo_lang_casm::casm;
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    search::{
+        bidirectional::find_path_bidirectional,
+        diverse,
+        predicates::Dataflow,
+        shortest::{find_k_shortest_paths, find_shortest_path, StepDistance, Unweighted},
+        NodeId, Predicates,
+    },
+    GraphMappings, Memory, StepId, Trace, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+mod common;
+
+/// Every step runs `[ap] = [ap - 1] + [fp - 3], ap++`.
+///
+/// There are two paths from `[50]` to `[72]`:
+///   - Through `[51]`, written by step 0 and read by step 6.
+///   - Through `[70]` and `[71]`, written by steps 4 and 5, which has more nodes but spans fewer
+///     steps.
+fn build_graph() -> (Memory, Trace, GraphMappings) {
+    let program = assemble(casm! { [ap + 0] = [ap + -1] + [fp + -3], ap++; });
+    let cells = [50, 51, 69, 70, 71, 72, 79, 80, 81, 82, 297]
+        .map(|addr| (addr, Felt::from(addr)))
+        .to_vec();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[
+        entry(1, 51, 53),
+        entry(1, 80, 300),
+        entry(1, 81, 300),
+        entry(1, 82, 300),
+        entry(1, 70, 53),
+        entry(1, 71, 300),
+        entry(1, 72, 54),
+    ]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    (memory, trace, mappings)
+}
+
+fn path(nodes: &[(bool, usize)]) -> Vec<NodeId> {
+    nodes
+        .iter()
+        .map(|&(is_step, id)| match is_step {
+            true => NodeId::Step(StepId(id)),
+            false => NodeId::Value(ValueId(id)),
+        })
+        .collect()
+}
+
+#[test]
+fn fewest_nodes() {
+    let (memory, trace, mappings) = build_graph();
+    let result = find_shortest_path(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(72),
+        Predicates::new(),
+        Unweighted,
+    );
+
+    assert_eq!(
+        result,
+        Some(path(&[
+            (false, 50),
+            (true, 0),
+            (false, 51),
+            (true, 6),
+            (false, 72)
+        ]))
+    );
+}
+
+#[test]
+fn fewest_steps() {
+    let (memory, trace, mappings) = build_graph();
+    let result = find_shortest_path(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(72),
+        Predicates::new(),
+        StepDistance,
+    );
+
+    assert_eq!(
+        result,
+        Some(path(&[
+            (false, 50),
+            (true, 4),
+            (false, 70),
+            (true, 5),
+            (false, 71),
+            (true, 6),
+            (false, 72)
+        ]))
+    );
+}
+
+#[test]
+fn with_predicates() {
+    let (memory, trace, mappings) = build_graph();
+
+    // Forbidding step 0 leaves only the longer path.
+    let predicates = Predicates::new()
+        .with_step(|_: &Trace, _: &[NodeId], step: StepId| step.0 != 0)
+        .with_value(Dataflow(&mappings))
+        .with_step(Dataflow(&mappings));
+    let result = find_shortest_path(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(72),
+        predicates,
+        Unweighted,
+    );
+
+    assert_eq!(result.map(|x| x.len()), Some(7));
+}
+
+#[test]
+fn no_path() {
+    let (memory, trace, mappings) = build_graph();
+
+    // Steps only move forward, and `[80]` is only accessed before `[72]` is.
+    let result = find_shortest_path(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(72),
+        ValueId(80),
+        Predicates::new(),
+        Unweighted,
+    );
+    assert_eq!(result, None);
+
+    let result = find_shortest_path(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(50),
+        Predicates::new(),
+        StepDistance,
+    );
+    assert_eq!(result, Some(path(&[(false, 50)])));
+}
+
+#[test]
+fn k_shortest() {
+    let (memory, trace, mappings) = build_graph();
+    let short = path(&[(false, 50), (true, 0), (false, 51), (true, 6), (false, 72)]);
+    let long = path(&[
+        (false, 50),
+        (true, 4),
+        (false, 70),
+        (true, 5),
+        (false, 71),
+        (true, 6),
+        (false, 72),
+    ]);
+
+    let paths = find_k_shortest_paths(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(72),
+        Predicates::new(),
+        Unweighted,
+    );
+    assert_eq!(paths.collect::<Vec<_>>(), [short.clone(), long.clone()]);
+
+    let paths = find_k_shortest_paths(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(72),
+        Predicates::new(),
+        StepDistance,
+    );
+    assert_eq!(paths.collect::<Vec<_>>(), [long, short]);
+}
+
+#[test]
+fn diverse_paths() {
+    let (memory, trace, mappings) = build_graph();
+    let paths = || {
+        find_k_shortest_paths(
+            &memory,
+            &trace,
+            &mappings,
+            ValueId(50),
+            ValueId(72),
+            Predicates::new(),
+            Unweighted,
+        )
+    };
+
+    // Both paths go through step 6, which is one of the 5 inner nodes of the longer one.
+    assert_eq!(diverse(paths(), 0.0).count(), 1);
+    assert_eq!(diverse(paths(), 0.2).count(), 2);
+}
+
+#[test]
+fn bidirectional() {
+    let (memory, trace, mappings) = build_graph();
+    let result = find_path_bidirectional(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(72),
+        Predicates::new(),
+    );
+    assert_eq!(
+        result,
+        Some(path(&[
+            (false, 50),
+            (true, 0),
+            (false, 51),
+            (true, 6),
+            (false, 72)
+        ]))
+    );
+
+    // Forbidding step 0 leaves only the longer path.
+    let predicates =
+        Predicates::new().with_step(|_: &Trace, _: &[NodeId], step: StepId| step.0 != 0);
+    let result = find_path_bidirectional(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(50),
+        ValueId(72),
+        predicates,
+    );
+    assert_eq!(result.map(|x| x.len()), Some(7));
+
+    let result = find_path_bidirectional(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(72),
+        ValueId(80),
+        Predicates::new(),
+    );
+    assert_eq!(result, None);
+}
+
+/// Every step runs `[ap] = [fp]`.
+///
+/// `[10]` reaches `[12]` first through step 5, and only later through steps 1 and 2. Since `[13]`
+/// is only accessed by step 3, it's only reachable through the later path to `[12]`.
+fn build_late_graph() -> (Memory, Trace, GraphMappings) {
+    let program = assemble(casm! { [ap + 0] = [fp + 0]; });
+    let cells = [10, 11, 12, 13, 20]
+        .map(|addr| (addr, Felt::from(addr)))
+        .to_vec();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[
+        entry(1, 20, 20),
+        entry(1, 10, 11),
+        entry(1, 11, 12),
+        entry(1, 12, 13),
+        entry(1, 20, 20),
+        entry(1, 10, 12),
+    ]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    (memory, trace, mappings)
+}
+
+#[test]
+fn value_reached_late_first() {
+    let (memory, trace, mappings) = build_late_graph();
+    let expected = path(&[
+        (false, 10),
+        (true, 1),
+        (false, 11),
+        (true, 2),
+        (false, 12),
+        (true, 3),
+        (false, 13),
+    ]);
+
+    for result in [
+        find_shortest_path(
+            &memory,
+            &trace,
+            &mappings,
+            ValueId(10),
+            ValueId(13),
+            Predicates::new(),
+            Unweighted,
+        ),
+        find_shortest_path(
+            &memory,
+            &trace,
+            &mappings,
+            ValueId(10),
+            ValueId(13),
+            Predicates::new(),
+            StepDistance,
+        ),
+        find_k_shortest_paths(
+            &memory,
+            &trace,
+            &mappings,
+            ValueId(10),
+            ValueId(13),
+            Predicates::new(),
+            Unweighted,
+        )
+        .next(),
+    ] {
+        assert_eq!(result.as_ref(), Some(&expected));
+    }
+}
+
+/// Every step runs `[ap] = [fp]`, and step 6 is forbidden.
+///
+/// The only path from `[10]` to `[13]` goes through steps 1, 2, 3 and 7. Searching forward,
+/// `[12]` is reached first through step 5, which is too late to continue. Searching backward,
+/// `[12]` is reached first through the forbidden step 6, so the first join at `[12]` fails.
+#[test]
+fn bidirectional_failed_join() {
+    let program = assemble(casm! { [ap + 0] = [fp + 0]; });
+    let cells = [10, 11, 12, 13, 14, 20]
+        .map(|addr| (addr, Felt::from(addr)))
+        .to_vec();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[
+        entry(1, 20, 20),
+        entry(1, 10, 11),
+        entry(1, 11, 12),
+        entry(1, 12, 14),
+        entry(1, 20, 20),
+        entry(1, 10, 12),
+        entry(1, 12, 13),
+        entry(1, 14, 13),
+    ]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    let predicates =
+        Predicates::new().with_step(|_: &Trace, _: &[NodeId], step: StepId| step.0 != 6);
+    let result = find_path_bidirectional(
+        &memory,
+        &trace,
+        &mappings,
+        ValueId(10),
+        ValueId(13),
+        predicates,
+    );
+    assert_eq!(
+        result,
+        Some(path(&[
+            (false, 10),
+            (true, 1),
+            (false, 11),
+            (true, 2),
+            (false, 12),
+            (true, 3),
+            (false, 14),
+            (true, 7),
+            (false, 13),
+        ]))
+    );
+}
@@ -0,0 +1,114 @@
+use cairo_lang_sierra::program::StatementIdx;
+use sierra2casm_dbg::{Error, SierraMapping};
+
+const PROGRAM: &str = "
+    type felt252 = felt252;
+
+    libfunc felt252_add = felt252_add;
+    libfunc drop_felt = drop<felt252>;
+    libfunc store_temp_felt = store_temp<felt252>;
+
+    felt252_add(a, b) -> (c);
+    drop_felt(a) -> ();
+    store_temp_felt(c) -> (c);
+    return(c);
+    store_temp_felt(a) -> (a);
+    return(a);
+
+    foo::add@0(a: felt252, b: felt252) -> (felt252);
+    foo::identity@4(a: felt252) -> (felt252);
+";
+
+/// Statement offsets for `PROGRAM`. The `drop` doesn't generate any code.
+const DEBUG_INFO: &str = r#"{
+    "sierra_statement_info": [
+        { "start_offset": 0, "end_offset": 0 },
+        { "start_offset": 0, "end_offset": 0 },
+        { "start_offset": 0, "end_offset": 1 },
+        { "start_offset": 1, "end_offset": 2 },
+        { "start_offset": 2, "end_offset": 3 },
+        { "start_offset": 3, "end_offset": 4 }
+    ]
+}"#;
+
+#[test]
+fn statements_by_offset() {
+    let mapping = SierraMapping::parse(PROGRAM, DEBUG_INFO).unwrap();
+
+    assert_eq!(mapping.statement_at(0), Some(StatementIdx(2)));
+    assert_eq!(mapping.statement_at(1), Some(StatementIdx(3)));
+    assert_eq!(mapping.statement_at(3), Some(StatementIdx(5)));
+    assert_eq!(mapping.statement_at(4), None);
+}
+
+#[test]
+fn locations() {
+    let mapping = SierraMapping::parse(PROGRAM, DEBUG_INFO).unwrap();
+
+    assert_eq!(
+        mapping.locate(0).unwrap().to_string(),
+        "#2 store_temp_felt in foo::add"
+    );
+    assert_eq!(
+        mapping.locate(1).unwrap().to_string(),
+        "#3 return in foo::add"
+    );
+    assert_eq!(
+        mapping.locate(2).unwrap().to_string(),
+        "#4 store_temp_felt in foo::identity"
+    );
+}
+
+#[test]
+fn statement_info_list() {
+    let statement_info = DEBUG_INFO
+        .split_once('[')
+        .and_then(|(_, x)| x.rsplit_once(']'))
+        .map(|(x, _)| format!("[{x}]"))
+        .unwrap();
+    let mapping = SierraMapping::parse(PROGRAM, &statement_info).unwrap();
+
+    assert_eq!(mapping.statement_at(2), Some(StatementIdx(4)));
+}
+
+#[test]
+fn mismatched_debug_info() {
+    let result = SierraMapping::parse(PROGRAM, r#"[{ "start_offset": 0, "end_offset": 1 }]"#);
+    assert!(matches!(result, Err(Error::InvalidSierraDebugInfo(_))));
+
+    let result = SierraMapping::parse("not a program", DEBUG_INFO);
+    assert!(matches!(result, Err(Error::InvalidSierraProgram(_))));
+}
+
+#[test]
+fn signature_sizes() {
+    const PROGRAM: &str = "
+        type u128 = u128;
+        type u256 = Struct<ut@core::integer::u256, u128, u128>;
+        type Unit = Struct<ut@Tuple>;
+        type OptionU256 = Enum<ut@core::option::Option, u256, Unit>;
+        type ArrayU128 = Array<u128>;
+        type Unknown = Unknown;
+
+        libfunc store_temp_u128 = store_temp<u128>;
+
+        store_temp_u128(a) -> (a);
+        return(a, b, c);
+        return(a);
+
+        foo::f@0(a: u128) -> (u256, OptionU256, ArrayU128);
+        foo::g@2(a: u128, b: u256) -> (Unknown);
+    ";
+    const DEBUG_INFO: &str = r#"[
+        { "start_offset": 0, "end_offset": 1 },
+        { "start_offset": 1, "end_offset": 2 },
+        { "start_offset": 2, "end_offset": 3 }
+    ]"#;
+    let mapping = SierraMapping::parse(PROGRAM, DEBUG_INFO).unwrap();
+
+    // A `u256` takes 2 cells, an `Option<u256>` 3 and an array 2.
+    assert_eq!(mapping.return_size(0), Some(7));
+    assert_eq!(mapping.return_size(2), None);
+    assert_eq!(mapping.param_size(0), Some(1));
+    assert_eq!(mapping.param_size(2), Some(3));
+}
@@ -0,0 +1,210 @@
+use cairo_lang_casm::{
+    casm,
+    hints::{Hint, StarknetHint},
+    operand::{CellRef, Register, ResOperand},
+};
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    search::taint::{find_taint, Sink, Taint},
+    Builtin, BuiltinSegment, BuiltinSegments, GraphMappings, StepId, ValueId,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+mod common;
+
+/// Steps:
+///   0. `[10] = [22] + [21]`, with a syscall hint whose request starts at `[[18]] = [40]`.
+///   1. `[11] = [21] + [20]`.
+///   2. `jnz` on `[10]`.
+///   3. `[22] = [10] + [19]`, where `[22]` already exists so `[19]` is deduced.
+fn run(seeds: &[usize]) -> Taint {
+    let program = assemble(casm! {
+        [ap + 0] = [fp + -3] + [fp + -4], ap++;
+        [ap + 0] = [fp + -4] + [fp + -5], ap++;
+        jmp rel 2 if [ap + -2] != 0;
+        [fp + -3] = [ap + -2] + [fp + -6];
+    });
+    let mut cells = [10, 11, 19, 20, 21, 22, 41, 42, 43, 44]
+        .map(|addr| (addr, Felt::from(addr)))
+        .to_vec();
+    cells.push((18, Felt::from(40)));
+    cells.push((40, Felt::from_bytes_be_slice(b"StorageWrite")));
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[
+        entry(1, 10, 25),
+        entry(2, 11, 25),
+        entry(3, 12, 25),
+        entry(5, 12, 25),
+    ]);
+    let hints = HashMap::from([(
+        0,
+        vec![Hint::Starknet(StarknetHint::SystemCall {
+            system: ResOperand::Deref(CellRef {
+                register: Register::FP,
+                offset: -7,
+            }),
+        })],
+    )]);
+    let mappings = GraphMappings::new(&memory, &trace, &hints, 1);
+
+    let seeds = seeds.iter().copied().map(ValueId).collect::<Vec<_>>();
+    find_taint(
+        &memory,
+        &trace,
+        &mappings,
+        &hints,
+        &BuiltinSegments::default(),
+        1,
+        &seeds,
+    )
+}
+
+#[test]
+fn propagation() {
+    let taint = run(&[22]);
+
+    assert_eq!(
+        taint.values.into_iter().collect::<Vec<_>>(),
+        [
+            (ValueId(10), Some(StepId(0))),
+            (ValueId(19), Some(StepId(3))),
+            (ValueId(22), None),
+        ]
+    );
+    assert_eq!(
+        taint.steps.into_iter().collect::<Vec<_>>(),
+        [StepId(0), StepId(2), StepId(3)]
+    );
+    assert_eq!(
+        taint.sinks,
+        [Sink::Branch {
+            step: StepId(2),
+            condition: ValueId(10),
+        }]
+    );
+}
+
+#[test]
+fn untainted_branch() {
+    let taint = run(&[20]);
+
+    assert!(taint.is_tainted(ValueId(11)));
+    assert!(!taint.is_tainted(ValueId(10)));
+    assert!(taint.sinks.is_empty());
+}
+
+#[test]
+fn syscall_sink() {
+    let taint = run(&[43]);
+
+    assert_eq!(
+        taint.sinks,
+        [Sink::Syscall {
+            step: StepId(0),
+            selector: Felt::from_bytes_be_slice(b"StorageWrite"),
+            cells: vec![ValueId(43)],
+        }]
+    );
+}
+
+#[test]
+fn syscall_request_layout() {
+    // The request of `Keccak` has the input's start and end pointers after the gas counter. The
+    // step at pc 50 has no instruction, so the mappings are built without it.
+    let program = assemble(casm! {
+        [ap + 0] = [fp + -3] + [fp + -4], ap++;
+    });
+    let mut cells = [21, 22, 41, 42, 43]
+        .map(|addr| (addr, Felt::from(addr)))
+        .to_vec();
+    cells.push((18, Felt::from(40)));
+    cells.push((40, Felt::from_bytes_be_slice(b"Keccak")));
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[entry(1, 10, 25), entry(50, 11, 25)]);
+    let hints = HashMap::from([(
+        0,
+        vec![Hint::Starknet(StarknetHint::SystemCall {
+            system: ResOperand::Deref(CellRef {
+                register: Register::FP,
+                offset: -7,
+            }),
+        })],
+    )]);
+    let mappings = GraphMappings::new(&memory, &build_trace(&[entry(1, 10, 25)]), &hints, 1);
+    let taint = find_taint(
+        &memory,
+        &trace,
+        &mappings,
+        &hints,
+        &BuiltinSegments::default(),
+        1,
+        &[ValueId(43)],
+    );
+
+    assert_eq!(
+        taint.sinks,
+        [Sink::Syscall {
+            step: StepId(0),
+            selector: Felt::from_bytes_be_slice(b"Keccak"),
+            cells: vec![ValueId(43)],
+        }]
+    );
+    // The response is written by the syscall handler from the request.
+    assert_eq!(taint.values[&ValueId(44)], Some(StepId(0)));
+    assert!(!taint.is_tainted(ValueId(10)));
+    assert_eq!(taint.undecodable, [StepId(1)]);
+}
+
+/// Steps:
+///   0. `[30] = [27] + 1`.
+///   1. `[30] = [50]`, which writes the first input of a pedersen instance.
+///   2. `[31] = [52]`, which reads its output.
+#[test]
+fn builtin_outputs() {
+    let program = assemble(casm! {
+        [ap + 0] = [fp + -3] + 1, ap++;
+        [ap + -1] = [[fp + -4]];
+        [ap + 0] = [[fp + -5] + 2], ap++;
+    });
+    let cells = [
+        (25, 50),
+        (26, 50),
+        (27, 4),
+        (30, 5),
+        (31, 99),
+        (50, 5),
+        (51, 7),
+        (52, 99),
+    ]
+    .map(|(addr, value)| (addr, Felt::from(value)))
+    .to_vec();
+    let memory = build_memory(&[program, cells].concat());
+    let trace = build_trace(&[entry(1, 30, 30), entry(3, 31, 30), entry(4, 31, 30)]);
+    let builtins = BuiltinSegments::new(vec![BuiltinSegment {
+        builtin: Builtin::Pedersen,
+        addrs: 50..53,
+    }]);
+    let mappings = GraphMappings::new(&memory, &trace, &HashMap::new(), 1);
+
+    let run = |builtins| {
+        find_taint(
+            &memory,
+            &trace,
+            &mappings,
+            &HashMap::new(),
+            builtins,
+            1,
+            &[ValueId(27)],
+        )
+    };
+
+    let taint = run(&builtins);
+    assert_eq!(taint.values[&ValueId(50)], Some(StepId(1)));
+    assert_eq!(taint.values[&ValueId(52)], Some(StepId(2)));
+    assert_eq!(taint.values[&ValueId(31)], Some(StepId(2)));
+
+    let taint = run(&BuiltinSegments::default());
+    assert!(!taint.is_tainted(ValueId(52)));
+    assert!(!taint.is_tainted(ValueId(31)));
+}
@@ -0,0 +1,112 @@
+use cairo_lang_casm::casm;
+use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
+use common::{assemble, build_memory, build_trace, entry};
+use sierra2casm_dbg::{
+    verify::{verify_trace, Divergence, DivergenceKind},
+    Error, StepId,
+};
+use starknet_types_core::felt::Felt;
+
+mod common;
+
+/// Steps:
+///   0. `call rel 3`, which writes `[20] = 20` and `[21] = 3`.
+///   1. `[22] = 5`.
+///   2. `[23] = [22] * 2`.
+///   3. `ret` back to pc 3.
+///   4. `ret` to the frame stored at `[18]` and `[19]`.
+fn run(
+    patch_cells: &[(usize, Option<Felt>)],
+    patch_trace: impl FnOnce(&mut Vec<RelocatedTraceEntry>),
+) -> Result<(), Divergence> {
+    let program = assemble(casm! {
+        call rel 3;
+        ret;
+        [ap + 0] = 5, ap++;
+        [ap + 0] = [ap + -1] * 2, ap++;
+        ret;
+    });
+    let mut cells = [(18, 20), (19, 100), (20, 20), (21, 3), (22, 5), (23, 10)]
+        .map(|(addr, value)| (addr, Some(Felt::from(value))))
+        .to_vec();
+    for (addr, value) in patch_cells {
+        cells.retain(|x| x.0 != *addr);
+        cells.push((*addr, *value));
+    }
+    let cells = cells
+        .into_iter()
+        .filter_map(|(addr, value)| Some((addr, value?)))
+        .collect::<Vec<_>>();
+    let memory = build_memory(&[program, cells].concat());
+
+    let mut entries = vec![
+        entry(1, 20, 20),
+        entry(4, 22, 22),
+        entry(6, 23, 22),
+        entry(8, 24, 22),
+        entry(3, 24, 20),
+    ];
+    patch_trace(&mut entries);
+
+    verify_trace(&memory, &build_trace(&entries))
+}
+
+#[test]
+fn consistent() {
+    run(&[], |_| {}).unwrap();
+}
+
+#[test]
+fn failed_assertion() {
+    let divergence = run(&[(23, Some(Felt::from(11)))], |_| {}).unwrap_err();
+    assert_eq!(divergence.step, StepId(2));
+    assert!(matches!(
+        divergence.kind,
+        DivergenceKind::Assertion { addr: 23, expected, actual }
+            if expected == Felt::from(10) && actual == Felt::from(11)
+    ));
+
+    // The return address written by a call is checked too.
+    let divergence = run(&[(21, Some(Felt::from(4)))], |_| {}).unwrap_err();
+    assert_eq!(divergence.step, StepId(0));
+    assert!(matches!(
+        divergence.kind,
+        DivergenceKind::Assertion { addr: 21, .. }
+    ));
+}
+
+#[test]
+fn register_mismatch() {
+    let divergence = run(&[], |entries| entries[2].ap = 24).unwrap_err();
+    assert_eq!(divergence.step, StepId(1));
+    assert!(matches!(
+        divergence.kind,
+        DivergenceKind::Register {
+            name: "ap",
+            expected: 23,
+            actual: 24
+        }
+    ));
+
+    // The `ret` at step 3 goes back to the pc stored in `[21]`.
+    let divergence = run(&[], |entries| entries[4].pc = 1).unwrap_err();
+    assert_eq!(divergence.step, StepId(3));
+    assert!(matches!(
+        divergence.kind,
+        DivergenceKind::Register {
+            name: "pc",
+            expected: 3,
+            actual: 1
+        }
+    ));
+}
+
+#[test]
+fn uninitialized_cell() {
+    let divergence = run(&[(19, None)], |_| {}).unwrap_err();
+    assert_eq!(divergence.step, StepId(4));
+    assert!(matches!(
+        divergence.kind,
+        DivergenceKind::Error(Error::UninitializedCell { addr: 19 })
+    ));
+}